@@ -10,8 +10,10 @@
 // copyright notice, and modified files need to carry a notice indicating
 // that they have been altered from the originals.
 
+use pyo3::exceptions::PyValueError;
 use pyo3::import_exception_bound;
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
 use qiskit_circuit::dag_circuit::DAGCircuit;
 use qiskit_circuit::imports::QUANTUM_REGISTER;
 use qiskit_circuit::packed_instruction::PackedInstruction;
@@ -19,20 +21,270 @@ use qiskit_circuit::Qubit;
 
 import_exception_bound!(qiskit.transpiler.exceptions, TranspilerError);
 
+/// QPY version 8 wire format for a `TranspileLayout`, kept entirely in Rust so that
+/// `apply_layout`'s output permutations never have to make a Python round trip just to be
+/// written out to a QPY file.
+///
+/// Layout: `{ char exists; int32_t initial_layout_size; int32_t input_mapping_size;
+/// int32_t final_layout_size; uint32_t extra_registers; }`, where a size of `-1` means the
+/// corresponding attribute is `None`. Immediately following the struct are `extra_registers`
+/// standalone v4 register definitions, then `initial_layout_size` `{ int32_t index;
+/// int32_t register_size; }` structs (`-1` meaning `None`) describing the virtual bits of the
+/// initial layout, then the `input_mapping_size` input-mapping indices and the
+/// `final_layout_size` final-layout indices, each as big-endian `int32_t`.
+mod qpy_layout {
+    use super::*;
+
+    const LAYOUT_NONE_SIZE: i32 = -1;
+
+    fn write_i32(buf: &mut Vec<u8>, value: i32) {
+        buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn read_i32(data: &[u8], offset: &mut usize) -> PyResult<i32> {
+        let bytes: [u8; 4] = data
+            .get(*offset..*offset + 4)
+            .ok_or_else(|| PyValueError::new_err("truncated QPY LAYOUT struct"))?
+            .try_into()
+            .unwrap();
+        *offset += 4;
+        Ok(i32::from_be_bytes(bytes))
+    }
+
+    /// Write a `TranspileLayout`'s component permutations out in the QPY v8 `LAYOUT` format.
+    ///
+    /// `extra_registers` holds the already-serialized bytes of any standalone v4 register
+    /// definitions that are not already present on the circuit being written (its length is
+    /// written as the `extra_registers` count, and each entry is copied out verbatim); the
+    /// common case coming out of `apply_layout` is a single pre-existing "q" register, so
+    /// callers with nothing extra to declare can pass an empty slice.
+    pub fn write_transpile_layout(
+        initial_layout: Option<&[Qubit]>,
+        initial_layout_regsizes: Option<&[i32]>,
+        input_qubit_mapping: Option<&[i32]>,
+        final_layout: Option<&[Qubit]>,
+        extra_registers: &[Vec<u8>],
+    ) -> PyResult<Vec<u8>> {
+        let mut buf = Vec::new();
+        buf.push(if initial_layout.is_some() { 1u8 } else { 0u8 });
+        write_i32(
+            &mut buf,
+            initial_layout.map_or(LAYOUT_NONE_SIZE, |l| l.len() as i32),
+        );
+        write_i32(
+            &mut buf,
+            input_qubit_mapping.map_or(LAYOUT_NONE_SIZE, |m| m.len() as i32),
+        );
+        write_i32(
+            &mut buf,
+            final_layout.map_or(LAYOUT_NONE_SIZE, |l| l.len() as i32),
+        );
+        buf.extend_from_slice(&(extra_registers.len() as u32).to_be_bytes());
+
+        for reg in extra_registers {
+            buf.extend_from_slice(reg);
+        }
+
+        if let Some(initial_layout) = initial_layout {
+            let regsizes = initial_layout_regsizes;
+            for (i, qubit) in initial_layout.iter().enumerate() {
+                write_i32(&mut buf, qubit.index() as i32);
+                write_i32(
+                    &mut buf,
+                    regsizes.and_then(|r| r.get(i).copied()).unwrap_or(LAYOUT_NONE_SIZE),
+                );
+            }
+        }
+        if let Some(mapping) = input_qubit_mapping {
+            for index in mapping {
+                write_i32(&mut buf, *index);
+            }
+        }
+        if let Some(final_layout) = final_layout {
+            for qubit in final_layout {
+                write_i32(&mut buf, qubit.index() as i32);
+            }
+        }
+        Ok(buf)
+    }
+
+    /// Inverse of [write_transpile_layout]; reconstructs the three permutations (each `None`
+    /// if the corresponding size field was `-1`) from a previously-written QPY v8 blob.
+    /// Extra register payloads are skipped over rather than parsed, since reconstructing a
+    /// `QuantumRegister` is left to the Python-side v4 register reader.
+    pub fn read_transpile_layout(
+        data: &[u8],
+        extra_register_len: impl Fn(&[u8]) -> PyResult<usize>,
+    ) -> PyResult<(
+        Option<Vec<(i32, i32)>>,
+        Option<Vec<i32>>,
+        Option<Vec<i32>>,
+    )> {
+        let mut offset = 0usize;
+        let exists = *data
+            .first()
+            .ok_or_else(|| PyValueError::new_err("empty QPY LAYOUT struct"))?;
+        offset += 1;
+        let initial_size = read_i32(data, &mut offset)?;
+        let mapping_size = read_i32(data, &mut offset)?;
+        let final_size = read_i32(data, &mut offset)?;
+        let extra_registers = u32::from_be_bytes(
+            data.get(offset..offset + 4)
+                .ok_or_else(|| PyValueError::new_err("truncated QPY LAYOUT struct"))?
+                .try_into()
+                .unwrap(),
+        );
+        offset += 4;
+
+        if exists == 0 {
+            return Ok((None, None, None));
+        }
+
+        for _ in 0..extra_registers {
+            offset += extra_register_len(&data[offset..])?;
+        }
+
+        let initial_layout = if initial_size == LAYOUT_NONE_SIZE {
+            None
+        } else {
+            let mut out = Vec::with_capacity(initial_size as usize);
+            for _ in 0..initial_size {
+                let index = read_i32(data, &mut offset)?;
+                let regsize = read_i32(data, &mut offset)?;
+                out.push((index, regsize));
+            }
+            Some(out)
+        };
+        let input_mapping = if mapping_size == LAYOUT_NONE_SIZE {
+            None
+        } else {
+            let mut out = Vec::with_capacity(mapping_size as usize);
+            for _ in 0..mapping_size {
+                out.push(read_i32(data, &mut offset)?);
+            }
+            Some(out)
+        };
+        let final_layout = if final_size == LAYOUT_NONE_SIZE {
+            None
+        } else {
+            let mut out = Vec::with_capacity(final_size as usize);
+            for _ in 0..final_size {
+                out.push(read_i32(data, &mut offset)?);
+            }
+            Some(out)
+        };
+        Ok((initial_layout, input_mapping, final_layout))
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature=(
+    initial_layout=None,
+    initial_layout_regsizes=None,
+    input_qubit_mapping=None,
+    final_layout=None
+))]
+pub fn write_transpile_layout_qpy(
+    py: Python,
+    initial_layout: Option<Vec<Qubit>>,
+    initial_layout_regsizes: Option<Vec<i32>>,
+    input_qubit_mapping: Option<Vec<i32>>,
+    final_layout: Option<Vec<Qubit>>,
+) -> PyResult<Py<PyBytes>> {
+    let buf = qpy_layout::write_transpile_layout(
+        initial_layout.as_deref(),
+        initial_layout_regsizes.as_deref(),
+        input_qubit_mapping.as_deref(),
+        final_layout.as_deref(),
+        &[],
+    )?;
+    Ok(PyBytes::new_bound(py, &buf).unbind())
+}
+
+#[pyfunction]
+pub fn read_transpile_layout_qpy(
+    data: &[u8],
+) -> PyResult<(Option<Vec<(i32, i32)>>, Option<Vec<i32>>, Option<Vec<i32>>)> {
+    // No extra register payloads are produced by `write_transpile_layout_qpy` today, so there
+    // is nothing for the skip callback to step over.
+    qpy_layout::read_transpile_layout(data, |_| Ok(0))
+}
+
+/// Check whether `op_name` acting on the given physical qubits is supported by `target`,
+/// mirroring the `Option<(String, [u32; 2])>` violation style of the oxidized `CheckMap` pass.
+/// Instructions on fewer than two qubits are not checked at all. Every instruction on two or
+/// more qubits is checked with `target.instruction_supported` on its mapped physical qargs in
+/// order, so a 2-qubit gate on the reversed direction of a directed edge counts as a violation;
+/// a violation reports the first two of those physical qubits.
+fn check_target_support(
+    target: &Bound<PyAny>,
+    op_name: &str,
+    physical_qargs: &[Qubit],
+) -> PyResult<Option<(String, [u32; 2])>> {
+    if physical_qargs.len() < 2 {
+        return Ok(None);
+    }
+    let py = target.py();
+    let qargs_tuple: Vec<u32> = physical_qargs.iter().map(|q| q.index() as u32).collect();
+    let supported: bool = target
+        .call_method1("instruction_supported", (op_name, PyTuple::new_bound(py, &qargs_tuple)))?
+        .extract()?;
+    if supported {
+        return Ok(None);
+    }
+    Ok(Some((
+        op_name.to_string(),
+        [qargs_tuple[0], qargs_tuple[1]],
+    )))
+}
+
+/// Complete a layout that only maps the circuit's active qubits into a total permutation over
+/// all `num_qubits` of the circuit (active and ancilla alike), by handing the unused physical
+/// qubits to the unused virtual ones in order. This lets callers hand `apply_layout` a layout
+/// sized to just the active qubits instead of pre-padding it with an ancilla allocation pass.
+fn embed_ancillas(layout: &[Qubit], num_qubits: usize) -> Vec<Qubit> {
+    let mut used = vec![false; num_qubits];
+    for phys in layout {
+        used[phys.index()] = true;
+    }
+    let mut full = layout.to_vec();
+    let mut spare = used.iter().enumerate().filter(|(_, u)| !**u).map(|(i, _)| i);
+    full.resize_with(num_qubits, || Qubit(spare.next().unwrap() as u32));
+    full
+}
+
 #[pyfunction]
-#[pyo3(signature=(dag, layout, post_layout=None, final_layout=None))]
+#[pyo3(signature=(dag, layout, post_layout=None, final_layout=None, target=None))]
 pub fn apply_layout(
     py: Python,
     dag: DAGCircuit,
     layout: Vec<Qubit>,
     post_layout: Option<Vec<Qubit>>,
     final_layout: Option<Vec<Qubit>>,
-) -> PyResult<(DAGCircuit, Option<Vec<Qubit>>, Option<Vec<Qubit>>)> {
-    if layout.len() != 1 + layout.iter().max().unwrap().index() {
+    target: Option<Bound<PyAny>>,
+) -> PyResult<(
+    DAGCircuit,
+    Option<Vec<Qubit>>,
+    Option<Vec<Qubit>>,
+    Option<(String, [u32; 2])>,
+)> {
+    let num_qubits = dag.num_qubits();
+    let layout = if layout.len() == num_qubits {
+        if layout.len() != 1 + layout.iter().max().unwrap().index() {
+            return Err(TranspilerError::new_err(
+                "The 'layout' must be full (with ancilla).",
+            ));
+        }
+        layout
+    } else if layout.len() < num_qubits {
+        // A layout that only covers the active qubits: embed the remaining ancillas onto
+        // whatever physical qubits are left over.
+        embed_ancillas(&layout, num_qubits)
+    } else {
         return Err(TranspilerError::new_err(
-            "The 'layout' must be full (with ancilla).",
+            "The 'layout' cannot be larger than the number of qubits in the circuit.",
         ));
-    }
+    };
     let reg = QUANTUM_REGISTER.get_bound(py).call1((layout.len(), "q"))?;
     let mut out_dag = DAGCircuit::with_capacity(
         py,
@@ -50,9 +302,9 @@ pub fn apply_layout(
     out_dag.add_qreg(py, &reg)?;
     out_dag.set_global_phase(dag.get_global_phase())?;
 
-    let mut rebuild_dag = |mapping: &[Qubit]| -> PyResult<()> {
-        // TODO: Use DAGCircuit::extend() to avoid extra edge ops when there is a pattern to do it
-        // with an iterator that interns qubits internally
+    let mut rebuild_dag = |mapping: &[Qubit]| -> PyResult<Option<(String, [u32; 2])>> {
+        let mut violation = None;
+        let mut mapped_instructions: Vec<PackedInstruction> = Vec::new();
         for node in dag.topological_op_nodes()? {
             let inst = dag.dag()[node].unwrap_operation();
             let mapped_qubits: Vec<Qubit> = dag
@@ -60,7 +312,15 @@ pub fn apply_layout(
                 .iter()
                 .map(|x| mapping[x.index()])
                 .collect();
-            let mapped_inst = PackedInstruction {
+            if let Some(target) = &target {
+                if let Some(found) =
+                    check_target_support(target, inst.op.name(), &mapped_qubits)?
+                {
+                    violation = Some(found);
+                    break;
+                }
+            }
+            mapped_instructions.push(PackedInstruction {
                 op: inst.op.clone(),
                 qubits: out_dag.qargs_interner.insert_owned(mapped_qubits),
                 clbits: inst.clbits,
@@ -68,10 +328,10 @@ pub fn apply_layout(
                 extra_attrs: inst.extra_attrs.clone(),
                 #[cfg(feature = "cache_pygates")]
                 py_op: inst.py_op.clone(),
-            };
-            out_dag.push_back(py, mapped_inst)?;
+            });
         }
-        Ok(())
+        out_dag.extend(py, mapped_instructions)?;
+        Ok(violation)
     };
 
     match post_layout {
@@ -91,25 +351,148 @@ pub fn apply_layout(
                 let old_phys = layout[new_virt];
                 full_layout[old_phys.index()] = *new_phys;
             }
-            rebuild_dag(&post_layout)?;
+            let violation = rebuild_dag(&post_layout)?;
             if let Some(final_layout) = final_layout {
                 let mut new_final = vec![Qubit(u32::MAX); dag.num_qubits()];
                 for (old_virt, old_phys) in final_layout.iter().enumerate() {
                     new_final[full_layout[old_virt].index()] = full_layout[old_phys.index()];
                 }
-                Ok((out_dag, Some(full_layout), Some(new_final)))
+                Ok((out_dag, Some(full_layout), Some(new_final), violation))
             } else {
-                Ok((out_dag, Some(full_layout), None))
+                Ok((out_dag, Some(full_layout), None, violation))
             }
         }
         None => {
-            rebuild_dag(&layout)?;
-            Ok((out_dag, None, None))
+            let violation = rebuild_dag(&layout)?;
+            Ok((out_dag, None, None, violation))
         }
     }
 }
 
+/// Apply a per-component layout to a circuit bound for a disjoint (weakly-connected) coupling
+/// map, following the SABRE disjoint-layout approach: each connected component of the target
+/// gets its own `Vec<Qubit>` layout, and `qubit_components[virtual]` says which of those
+/// layouts a given virtual qubit belongs to. The components are stitched back together into a
+/// single output DAG over one physical register spanning all of them, with each component's
+/// physical qubits placed at a distinct offset.
+///
+/// `barrier`/other directive instructions that act across more than one component are split
+/// into one copy per component, acting only on that component's share of the original qargs,
+/// so the recombined DAG stays semantically equivalent to the original.
+#[pyfunction]
+#[pyo3(signature=(dag, component_layouts, qubit_components, final_layout=None))]
+pub fn apply_layout_disjoint(
+    py: Python,
+    dag: DAGCircuit,
+    component_layouts: Vec<Vec<Qubit>>,
+    qubit_components: Vec<u32>,
+    final_layout: Option<Vec<Qubit>>,
+) -> PyResult<(DAGCircuit, Vec<Qubit>, Option<Vec<Qubit>>)> {
+    let num_qubits = dag.num_qubits();
+    if qubit_components.len() != num_qubits {
+        return Err(TranspilerError::new_err(
+            "'qubit_components' must assign a component to every qubit in the circuit.",
+        ));
+    }
+
+    // Physical qubits of component `c` are placed at [offset[c], offset[c] + size[c]).
+    let mut offsets = vec![0u32; component_layouts.len()];
+    let mut running = 0u32;
+    for (c, component_layout) in component_layouts.iter().enumerate() {
+        offsets[c] = running;
+        running += component_layout.len() as u32;
+    }
+    let total_physical = running as usize;
+
+    // The j-th virtual qubit assigned to component c (in increasing global-qubit order) maps
+    // through that component's own layout to a physical qubit, then gets shifted by the
+    // component's offset in the combined register.
+    let mut local_index = vec![0usize; component_layouts.len()];
+    let mut global_layout = vec![Qubit(0); num_qubits];
+    for virt in 0..num_qubits {
+        let c = qubit_components[virt] as usize;
+        let j = local_index[c];
+        local_index[c] += 1;
+        let local_phys = component_layouts[c][j];
+        global_layout[virt] = Qubit(offsets[c] + local_phys.index() as u32);
+    }
+
+    let reg = QUANTUM_REGISTER
+        .get_bound(py)
+        .call1((total_physical, "q"))?;
+    let mut out_dag = DAGCircuit::with_capacity(
+        py,
+        total_physical,
+        dag.num_clbits(),
+        Some(dag.num_vars()),
+        Some(dag.dag().node_count()),
+        Some(dag.dag().edge_count()),
+    )?;
+    out_dag.set_name(dag.name().map(|x| x.clone_ref(py)));
+    out_dag.set_metadata(dag.metadata().map(|x| x.clone_ref(py)));
+    out_dag.set_calibrations(dag.calibrations().clone());
+    out_dag.copy_vars_from(py, &dag)?;
+    out_dag.copy_clbits_from(py, &dag)?;
+    out_dag.add_qreg(py, &reg)?;
+    out_dag.set_global_phase(dag.get_global_phase())?;
+
+    let mut mapped_instructions: Vec<PackedInstruction> = Vec::new();
+    for node in dag.topological_op_nodes()? {
+        let inst = dag.dag()[node].unwrap_operation();
+        let qargs = dag.get_qargs(inst.qubits);
+        let components: Vec<u32> = qargs
+            .iter()
+            .map(|q| qubit_components[q.index()])
+            .collect();
+        let spans_components = components.windows(2).any(|w| w[0] != w[1]);
+
+        if !spans_components || inst.op.directive() {
+            // Either entirely within one component, or a directive (e.g. `barrier`) that we
+            // can freely split per-component.
+            let mut by_component: std::collections::BTreeMap<u32, Vec<Qubit>> =
+                std::collections::BTreeMap::new();
+            for qubit in qargs {
+                by_component
+                    .entry(qubit_components[qubit.index()])
+                    .or_default()
+                    .push(global_layout[qubit.index()]);
+            }
+            for mapped_qubits in by_component.into_values() {
+                mapped_instructions.push(PackedInstruction {
+                    op: inst.op.clone(),
+                    qubits: out_dag.qargs_interner.insert_owned(mapped_qubits),
+                    clbits: inst.clbits,
+                    params: inst.params.clone(),
+                    extra_attrs: inst.extra_attrs.clone(),
+                    #[cfg(feature = "cache_pygates")]
+                    py_op: inst.py_op.clone(),
+                });
+            }
+        } else {
+            return Err(TranspilerError::new_err(format!(
+                "Instruction '{}' acts across more than one component of a disjoint coupling \
+                 map and is not a directive that can be split.",
+                inst.op.name()
+            )));
+        }
+    }
+    out_dag.extend(py, mapped_instructions)?;
+
+    let new_final_layout = final_layout.map(|final_layout| {
+        let mut new_final = vec![Qubit(0); total_physical];
+        for (old_virt, old_phys) in final_layout.iter().enumerate() {
+            new_final[global_layout[old_virt].index()] = global_layout[old_phys.index()];
+        }
+        new_final
+    });
+
+    Ok((out_dag, global_layout, new_final_layout))
+}
+
 pub fn apply_layout_mod(m: &Bound<PyModule>) -> PyResult<()> {
     m.add_wrapped(wrap_pyfunction!(apply_layout))?;
+    m.add_wrapped(wrap_pyfunction!(apply_layout_disjoint))?;
+    m.add_wrapped(wrap_pyfunction!(write_transpile_layout_qpy))?;
+    m.add_wrapped(wrap_pyfunction!(read_transpile_layout_qpy))?;
     Ok(())
 }
@@ -0,0 +1,116 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2024
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+use hashbrown::HashMap;
+use rustworkx_core::petgraph::stable_graph::NodeIndex;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::wrap_pyfunction;
+
+use qiskit_circuit::dag_circuit::DAGCircuit;
+use qiskit_circuit::Qubit;
+
+use crate::commutation_checker::{commute_matrices, DEFAULT_ATOL, DEFAULT_RTOL};
+
+/// Per wire, the maximal runs of wire-consecutive operations that mutually commute (every pair
+/// within a run commutes, per [`commute_matrices`]); the grouping is the coarsest one compatible
+/// with circuit order, not a transitive-closure equivalence class, so something between two runs
+/// not commuting with either is enough to split them even if the two runs themselves would
+/// individually commute with each other.
+pub type CommutationSet = HashMap<Qubit, Vec<Vec<NodeIndex>>>;
+
+/// Which run within its wire's [`CommutationSet`] entry a given `(node, wire)` landed in.
+pub type NodeIndices = HashMap<(NodeIndex, Qubit), usize>;
+
+/// Build the commutation sets of `dag`: for each wire, the circuit-order runs of mutually
+/// commuting operations on that wire, plus the `(node, wire) -> run index` lookup every pass
+/// consuming those runs needs to find where a given node landed. A single topological pass
+/// collects each wire's operations in circuit order; the pairwise commutation checks only ever
+/// run within one wire's own operations, reusing [`commute_matrices`] rather than re-deriving
+/// commutation logic here.
+pub fn analyze_commutations_inner(dag: &DAGCircuit) -> PyResult<(CommutationSet, NodeIndices)> {
+    let num_qubits = dag.num_qubits();
+    let mut wire_nodes: Vec<Vec<NodeIndex>> = vec![Vec::new(); num_qubits];
+    for node in dag.topological_op_nodes()? {
+        let inst = dag.dag()[node].unwrap_operation();
+        for qubit in dag.get_qargs(inst.qubits) {
+            wire_nodes[qubit.0 as usize].push(node);
+        }
+    }
+
+    let mut commutation_set = CommutationSet::with_capacity(num_qubits);
+    let mut node_indices = NodeIndices::new();
+    for (wire_index, nodes) in wire_nodes.into_iter().enumerate() {
+        let wire = Qubit(wire_index as u32);
+        let mut runs: Vec<Vec<NodeIndex>> = Vec::new();
+        for node in nodes {
+            let inst = dag.dag()[node].unwrap_operation();
+            let qargs = dag.get_qargs(inst.qubits);
+            let matrix = inst.op.matrix(inst.params_view());
+            let commutes_with_run = runs.last().is_some_and(|run| {
+                run.iter().all(|&other| {
+                    let other_inst = dag.dag()[other].unwrap_operation();
+                    commute_matrices(
+                        matrix.clone(),
+                        qargs,
+                        other_inst.op.matrix(other_inst.params_view()),
+                        dag.get_qargs(other_inst.qubits),
+                        DEFAULT_RTOL,
+                        DEFAULT_ATOL,
+                    )
+                })
+            });
+            if commutes_with_run {
+                runs.last_mut().unwrap().push(node);
+            } else {
+                runs.push(vec![node]);
+            }
+            node_indices.insert((node, wire), runs.len() - 1);
+        }
+        commutation_set.insert(wire, runs);
+    }
+    Ok((commutation_set, node_indices))
+}
+
+/// Python-facing wrapper around [`analyze_commutations_inner`], so transpiler passes (e.g.
+/// commutative cancellation and gate reordering) can compute a `DAGCircuit`'s commutation sets
+/// once and share them, instead of each pass re-running pairwise `CommutationChecker.commute`
+/// calls over the same wires. Returns `(commutation_set, node_indices)`: `commutation_set` maps
+/// each wire index to its list of runs, each run a list of node indices in circuit order (e.g.
+/// `{0: [[0], [2, 3], [4], [1]]}`); `node_indices` maps `(node_index, wire_index)` to the index of
+/// the run that node landed in on that wire.
+#[pyfunction]
+pub fn analyze_commutations(py: Python, dag: &DAGCircuit) -> PyResult<(Py<PyDict>, Py<PyDict>)> {
+    let (commutation_set, node_indices) = analyze_commutations_inner(dag)?;
+
+    let py_commutation_set = PyDict::new_bound(py);
+    for (wire, runs) in commutation_set {
+        let py_runs: Vec<Vec<usize>> = runs
+            .into_iter()
+            .map(|run| run.into_iter().map(NodeIndex::index).collect())
+            .collect();
+        py_commutation_set.set_item(wire.0, py_runs)?;
+    }
+
+    let py_node_indices = PyDict::new_bound(py);
+    for ((node, wire), run_index) in node_indices {
+        py_node_indices.set_item((node.index(), wire.0), run_index)?;
+    }
+
+    Ok((py_commutation_set.unbind(), py_node_indices.unbind()))
+}
+
+pub fn commutation_analysis_mod(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_wrapped(wrap_pyfunction!(analyze_commutations))?;
+    Ok(())
+}
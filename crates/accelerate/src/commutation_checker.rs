@@ -10,18 +10,25 @@
 // copyright notice, and modified files need to carry a notice indicating
 // that they have been altered from the originals.
 
+use std::num::NonZeroUsize;
+
 use hashbrown::HashMap;
+use lru::LruCache;
+use ndarray::{Array2, Ix2};
+use num_complex::Complex64;
+use numpy::PyReadonlyArray2;
 use smallvec::SmallVec;
 
+use pyo3::exceptions::PyValueError;
 use pyo3::intern;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PySet, PyTuple};
+use pyo3::types::{PyDict, PySet};
 
 use qiskit_circuit::circuit_instruction::CircuitInstruction;
 use qiskit_circuit::operations::{Operation, OperationType, Param, StandardGate};
 use qiskit_circuit::Qubit;
 
-use crate::unitary_compose::compose_unitary;
+use crate::unitary_compose::embed;
 
 #[derive(Clone)]
 pub enum CommutationLibraryEntry {
@@ -56,12 +63,32 @@ pub struct CommutationLibrary {
 }
 
 impl CommutationLibrary {
+    /// Look `first_op`/`second_op` up in the precomputed table, without paying for a matrix
+    /// multiplication. Only answers for a pair of `StandardGate`s the library has an entry for;
+    /// everything else (non-standard gates, or a `[StandardGate; 2]` the table doesn't cover)
+    /// falls through to `None`, and the caller's `commute_matmul` path runs instead.
     fn check_commutation_entries(
         &self,
+        py: Python,
         first_op: &CircuitInstruction,
         second_op: &CircuitInstruction,
     ) -> Option<bool> {
-        None
+        let first_gate = standard_gate(first_op)?;
+        let second_gate = standard_gate(second_op)?;
+        match self.library.get(&[first_gate, second_gate])? {
+            CommutationLibraryEntry::Commutes(commutes) => Some(*commutes),
+            CommutationLibraryEntry::QubitMapping(mapping) => mapping
+                .get(&get_relative_placement(py, first_op, second_op))
+                .copied(),
+        }
+    }
+}
+
+/// `op`'s gate, if it's a `StandardGate` -- the only kind [`CommutationLibrary`] has entries for.
+fn standard_gate(op: &CircuitInstruction) -> Option<StandardGate> {
+    match op.operation {
+        OperationType::Standard(gate) => Some(gate),
+        _ => None,
     }
 }
 
@@ -73,20 +100,24 @@ impl CommutationLibrary {
     }
 }
 
-type CommutationCacheEntry = HashMap<
-    (
-        SmallVec<[Option<Qubit>; 2]>,
-        [SmallVec<[ParameterKey; 3]>; 2],
-    ),
-    bool,
->;
+/// Cache key for a commutation result: the two operations' names, the relative position of each
+/// of the first operation's qubits within the second operation's qargs (so the same structural
+/// pattern hits the cache regardless of which absolute qubits it's checked on), and each
+/// operation's rounded parameters.
+type CommutationCacheKey = (
+    [String; 2],
+    SmallVec<[Option<Qubit>; 2]>,
+    [SmallVec<[ParameterKey; 3]>; 2],
+);
 
 #[pyclass]
 struct CommutationChecker {
     library: CommutationLibrary,
-    cache_max_entries: usize,
-    cache: HashMap<[String; 2], CommutationCacheEntry>,
-    current_cache_entries: usize,
+    /// Bounded by `cache_max_entries` (see `py_new`). `lru::LruCache` evicts only its single
+    /// least-recently-used entry once full, on the `put` that would otherwise exceed capacity --
+    /// unlike a "clear everything past the limit" cache, a hot gate pair keeps surviving new
+    /// unrelated `commute` calls for as long as it keeps getting looked up.
+    cache: LruCache<CommutationCacheKey, bool>,
 }
 
 #[pymethods]
@@ -96,23 +127,25 @@ impl CommutationChecker {
     fn py_new(
         standard_gate_commutations: Option<CommutationLibrary>,
         cache_max_entries: usize,
-    ) -> Self {
-        CommutationChecker {
+    ) -> PyResult<Self> {
+        let cache_max_entries = NonZeroUsize::new(cache_max_entries)
+            .ok_or_else(|| PyValueError::new_err("cache_max_entries must be non-zero"))?;
+        Ok(CommutationChecker {
             library: standard_gate_commutations
                 .unwrap_or_else(|| CommutationLibrary::new(HashMap::new())),
-            cache: HashMap::with_capacity(cache_max_entries),
-            cache_max_entries,
-            current_cache_entries: 0,
-        }
+            cache: LruCache::new(cache_max_entries),
+        })
     }
 
-    #[pyo3(signature=(op1, op2, max_num_qubits=3))]
+    #[pyo3(signature=(op1, op2, max_num_qubits=3, rtol=DEFAULT_RTOL, atol=DEFAULT_ATOL))]
     fn commute(
-        &self,
+        &mut self,
         py: Python,
         op1: &CircuitInstruction,
         op2: &CircuitInstruction,
         max_num_qubits: u32,
+        rtol: f64,
+        atol: f64,
     ) -> PyResult<bool> {
         if let Some(commutes) = commutation_precheck(py, op1, op2, max_num_qubits)? {
             return Ok(commutes);
@@ -123,47 +156,27 @@ impl CommutationChecker {
             op1.operation.name() < op2.operation.name()
         };
         let (first_op, second_op) = if reversed { (op2, op1) } else { (op1, op2) };
-        if first_op.operation.name() == "annotated" || second_op.operation.name() == "annotated" {
-            return Ok(commute_matmul(first_op, second_op));
-        }
 
-        if let Some(commutes) = self.library.check_commutation_entries(first_op, second_op) {
+        if let Some(commutes) = self.library.check_commutation_entries(py, first_op, second_op) {
             return Ok(commutes);
         }
-        let is_commuting = commute_matmul(first_op, second_op);
-        // TODO: implement a LRU cache for this
-        if self.current_cache_entries >= self.cache_max_entries {
-            self.cache.clear();
-        }
 
-        let get_relative_placement =
-            |first_qargs: Bound<PyTuple>,
-             second_qargs: Bound<PyTuple>|
-             -> SmallVec<[Option<Qubit>; 2]> { smallvec::smallvec![None] };
-
-        self.cache
-            .entry([
+        let key = (
+            [
                 first_op.operation.name().to_string(),
                 second_op.operation.name().to_string(),
-            ])
-            .and_modify(|entries| {
-                if first_op.params.is_empty() && second_op.params.is_empty() {
-                    let key = (get_relative_placement(first_op, second_op), [None, None]);
-                    entries.insert(key, is_commuting);
-                    self.current_cache_entries += 1;
-                } else {
-                }
-            })
-            .or_insert_with(|| {
-                let mut entries = HashMap::with_capacity(1);
-                if first_op.params.is_empty() && second_op.params.is_empty() {
-                    let key = (get_relative_placement(first_op, second_op), [None, None]);
-                    entries.insert(key, is_commuting);
-                    self.current_cache_entries += 1;
-                } else {
-                }
-                entries
-            });
+            ],
+            get_relative_placement(py, first_op, second_op),
+            [
+                hashable_params(&first_op.params),
+                hashable_params(&second_op.params),
+            ],
+        );
+        if let Some(is_commuting) = self.cache.get(&key) {
+            return Ok(*is_commuting);
+        }
+        let is_commuting = commute_matmul(py, first_op, second_op, rtol, atol)?;
+        self.cache.put(key, is_commuting);
         Ok(is_commuting)
     }
 }
@@ -194,6 +207,13 @@ impl PartialEq for ParameterKey {
 
 impl Eq for ParameterKey {}
 
+/// Build a hashable cache key out of `params`. Only called once `is_commutation_skipped` (via
+/// `commutation_precheck`) has already ruled out any op with a free `Parameter`, so every
+/// parameter here is guaranteed to be a concrete `Param::Float`. `ParameterKey` hashes the value's
+/// exact `f64::to_bits`, so this only ever hits the cache for bit-for-bit identical parameters --
+/// two angles that are mathematically equal but constructed differently (e.g. `pi / 2` versus
+/// `0.5 * pi`) are cached separately, which is the safe default since rounding them together risks
+/// conflating two gates that don't actually commute.
 fn hashable_params(params: &[Param]) -> SmallVec<[ParameterKey; 3]> {
     params
         .iter()
@@ -207,33 +227,314 @@ fn hashable_params(params: &[Param]) -> SmallVec<[ParameterKey; 3]> {
         .collect()
 }
 
-fn get_qarg_indices(
+/// The canonical *relative* placement of `second_op`'s qargs against `first_op`'s: for each qubit
+/// of `second_op`, the index of that same qubit within `first_op`'s qargs, or `None` if it isn't
+/// one of `first_op`'s qubits. This is independent of which physical qubits either op actually
+/// sits on -- only the overlap *pattern* between the two qarg lists matters -- so a commutation
+/// result computed for one pair of physical qubits is reusable for any other pair sharing the
+/// same pattern. Both the cache key in `CommutationChecker::commute` and a `QubitMapping` library
+/// entry's lookup key rely on that property, and on using this exact direction: a precomputed
+/// library entry's key is built the same way, off the *second* op's qargs against the first's.
+fn get_relative_placement(
+    py: Python,
+    first_op: &CircuitInstruction,
+    second_op: &CircuitInstruction,
+) -> SmallVec<[Option<Qubit>; 2]> {
+    let first_qargs = first_op.qubits.bind(py);
+    second_op
+        .qubits
+        .bind(py)
+        .iter()
+        .map(|qubit| {
+            first_qargs
+                .iter()
+                .position(|other| other.is(&qubit))
+                .map(|index| Qubit(index as u32))
+        })
+        .collect()
+}
+
+/// The largest combined qubit-register `commute_matrices` will build a dense unitary over before
+/// giving up and reporting non-commutation. Two 2-qubit gates sharing at most one qubit need 3
+/// wires; beyond that the `2^n x 2^n` matrix this builds stops being worth the cost of an exact
+/// check.
+const MAX_NUM_QUBITS: usize = 3;
+
+/// Default relative tolerance for [`commute_up_to_global_phase`]'s `allclose`-style comparison,
+/// matching `numpy.allclose`'s own default.
+pub(crate) const DEFAULT_RTOL: f64 = 1e-5;
 
-fn commute_matmul(first_op: &CircuitInstruction, second_op: &CircuitInstruction) -> bool {
-    //    let qargs
-    let num_qubits = first_op.operation.num_qubits();
-    let first_mat = match first_op.operation.matrix(&first_op.params) {
-        Some(mat) => mat,
-        None => return false,
+/// Default absolute tolerance for [`commute_up_to_global_phase`]'s `allclose`-style comparison,
+/// matching `numpy.allclose`'s own default.
+pub(crate) const DEFAULT_ATOL: f64 = 1e-8;
+
+/// Whether `matrix_a` acting on `qubits_a` commutes with `matrix_b` acting on `qubits_b`. Disjoint
+/// qubits commute trivially, without either matrix ever being built. Otherwise both are expanded
+/// onto the union of their qubits (qubit identity given by `Q`'s `PartialEq`, capped at
+/// `MAX_NUM_QUBITS`) and `A*B` is compared against `B*A` up to a global phase: against
+/// `e^{i*phi}*B*A` where `phi = arg(tr(A*B*(B*A)^dagger))`, using an `allclose`-style elementwise
+/// tolerance of `rtol`/`atol`. Returns `false` (rather than erroring) whenever a matrix is
+/// unavailable (e.g. a gate with a free `Parameter`) or the qubit union would exceed
+/// `MAX_NUM_QUBITS`.
+pub(crate) fn commute_matrices<Q: PartialEq + Clone>(
+    matrix_a: Option<Array2<Complex64>>,
+    qubits_a: &[Q],
+    matrix_b: Option<Array2<Complex64>>,
+    qubits_b: &[Q],
+    rtol: f64,
+    atol: f64,
+) -> bool {
+    if qubits_a.iter().all(|q| !qubits_b.contains(q)) {
+        return true;
+    }
+    let mut union: Vec<Q> = qubits_a.to_vec();
+    for qubit in qubits_b {
+        if !union.contains(qubit) {
+            union.push(qubit.clone());
+        }
+    }
+    if union.len() > MAX_NUM_QUBITS {
+        return false;
+    }
+    let (Some(matrix_a), Some(matrix_b)) = (matrix_a, matrix_b) else {
+        return false;
     };
-    let second_mat = match second_op.operation.matrix(&second_op.params) {
-        Some(mat) => mat,
-        None => return false,
+    let expanded_a = expand_to_union(matrix_a, qubits_a, &union);
+    let expanded_b = expand_to_union(matrix_b, qubits_b, &union);
+    commute_up_to_global_phase(
+        &expanded_a.dot(&expanded_b),
+        &expanded_b.dot(&expanded_a),
+        rtol,
+        atol,
+    )
+}
+
+/// Expand `matrix`, acting on `qubits`, into the full dense unitary over `union` (a superset of
+/// `qubits`), by tensoring it with identities on the rest of `union` and permuting axes so each
+/// wire of `union` lands in its assigned position.
+fn expand_to_union<Q: PartialEq>(
+    matrix: Array2<Complex64>,
+    qubits: &[Q],
+    union: &[Q],
+) -> Array2<Complex64> {
+    let local_positions: Vec<usize> = qubits
+        .iter()
+        .map(|qubit| {
+            union
+                .iter()
+                .position(|u| u == qubit)
+                .expect("qubits is a subset of union")
+        })
+        .collect();
+    let dims = vec![2usize; union.len()];
+    let num_rows = 1usize << union.len();
+    embed(&matrix, &local_positions, &dims)
+        .as_standard_layout()
+        .into_shape((num_rows, num_rows))
+        .unwrap()
+        .into_dimensionality::<Ix2>()
+        .unwrap()
+        .to_owned()
+}
+
+/// Whether `ab` equals `ba` up to a global phase, i.e. whether `ab == e^{i*phi}*ba` where `phi =
+/// arg(tr(ab * ba^dagger))`, comparing element-by-element with an `allclose`-style tolerance:
+/// `|a - phase_correction*b| <= atol + rtol*|b|`.
+fn commute_up_to_global_phase(
+    ab: &Array2<Complex64>,
+    ba: &Array2<Complex64>,
+    rtol: f64,
+    atol: f64,
+) -> bool {
+    let trace: Complex64 = ab.iter().zip(ba.iter()).map(|(a, b)| a * b.conj()).sum();
+    if trace.norm() < atol {
+        return false;
+    }
+    let phase_correction = Complex64::from_polar(1.0, trace.arg());
+    ab.iter()
+        .zip(ba.iter())
+        .all(|(a, b)| (a - phase_correction * b).norm() <= atol + rtol * b.norm())
+}
+
+/// Whether `gate_a` acting on `qubits_a` commutes with `gate_b` acting on `qubits_b`, decided
+/// numerically from [`Gate::matrix`](qiskit_circuit::operations::Gate::matrix) rather than a
+/// lookup table. This is the entry point a DAG-level commutative-cancellation pass reaches for:
+/// it works directly off the compact [`Qubit`] indices a [`DAGCircuit`](qiskit_circuit::dag_circuit::DAGCircuit)
+/// uses, with no GIL needed, unlike [`CommutationChecker`] which additionally has to go through
+/// `CircuitInstruction`'s Python-space qubit objects.
+pub fn commutes(
+    gate_a: StandardGate,
+    params_a: &[Param],
+    qubits_a: &[Qubit],
+    gate_b: StandardGate,
+    params_b: &[Param],
+    qubits_b: &[Qubit],
+) -> bool {
+    commutes_with_tolerance(
+        gate_a, params_a, qubits_a, gate_b, params_b, qubits_b, DEFAULT_RTOL, DEFAULT_ATOL,
+    )
+}
+
+/// As [`commutes`], but with explicit `allclose` tolerances rather than the defaults.
+#[allow(clippy::too_many_arguments)]
+pub fn commutes_with_tolerance(
+    gate_a: StandardGate,
+    params_a: &[Param],
+    qubits_a: &[Qubit],
+    gate_b: StandardGate,
+    params_b: &[Param],
+    qubits_b: &[Qubit],
+    rtol: f64,
+    atol: f64,
+) -> bool {
+    commute_matrices(
+        gate_a.matrix(params_a),
+        qubits_a,
+        gate_b.matrix(params_b),
+        qubits_b,
+        rtol,
+        atol,
+    )
+}
+
+/// The `CircuitInstruction`-level entry point for [`commute_matrices`]: identifies each op's
+/// qubits by their Python object identity (so it works whether `first_op`/`second_op` share a
+/// register or not) and hands off to the qubit-union-and-embed machinery shared with the pure
+/// [`commutes`]. `embed` (from `unitary_compose`) is what actually does the Kronecker-product
+/// placement onto the union register, in the same qubit-0-is-right-most convention the rest of
+/// the crate uses, so overlapping-but-different qarg orders (e.g. `cx(0, 1)` against `cx(1, 2)`)
+/// land each gate's matrix on the right wires of the shared space before `A*B`/`B*A` are compared.
+/// Each op's matrix comes from [`effective_matrix`], which reconstructs one for an `"annotated"`
+/// op instead of the bare `None` its own `Operation::matrix` would give.
+fn commute_matmul(
+    py: Python,
+    first_op: &CircuitInstruction,
+    second_op: &CircuitInstruction,
+    rtol: f64,
+    atol: f64,
+) -> PyResult<bool> {
+    let qubits_a = qubit_identities(py, first_op);
+    let qubits_b = qubit_identities(py, second_op);
+    Ok(commute_matrices(
+        effective_matrix(py, first_op)?,
+        &qubits_a,
+        effective_matrix(py, second_op)?,
+        &qubits_b,
+        rtol,
+        atol,
+    ))
+}
+
+/// `op`'s qubits, identified by Python object identity rather than index -- usable as the `Q` of
+/// [`commute_matrices`] whether or not `op` shares a register with whatever it's compared against.
+fn qubit_identities(py: Python, op: &CircuitInstruction) -> SmallVec<[usize; 2]> {
+    op.qubits
+        .bind(py)
+        .iter()
+        .map(|qubit| qubit.as_ptr() as usize)
+        .collect()
+}
+
+/// `op`'s unitary matrix, for use by [`commute_matmul`]. For anything but an `"annotated"` op this
+/// is just `op.operation.matrix(&op.params)`; an `"annotated"` op (a Python `AnnotatedOperation`)
+/// has no native `Operation::matrix` of its own; see [`annotated_matrix`] for how one gets
+/// reconstructed instead.
+fn effective_matrix(py: Python, op: &CircuitInstruction) -> PyResult<Option<Array2<Complex64>>> {
+    if op.operation.name() == "annotated" {
+        if let Some(matrix) = annotated_matrix(py, op)? {
+            return Ok(Some(matrix));
+        }
+    }
+    Ok(op.operation.matrix(&op.params))
+}
+
+/// `op`'s underlying Python gate object, if it's backed by one (i.e. `op.operation` is
+/// [`OperationType::Gate`] rather than a native [`StandardGate`] or something non-gate-like).
+fn py_gate_object(op: &CircuitInstruction) -> Option<&PyObject> {
+    match &op.operation {
+        OperationType::Gate(gate) => Some(&gate.gate),
+        _ => None,
+    }
+}
+
+/// Reconstruct the effective unitary of an `"annotated"` op (a Python `AnnotatedOperation`) by
+/// folding its `modifiers` -- in the order Qiskit stores them -- onto its `base_op`'s own matrix:
+/// an `InverseModifier` conjugate-transposes it (exact for a unitary, so no separate matrix
+/// inversion is needed), a `ControlModifier` expands it into a larger controlled unitary via
+/// [`controlled_matrix`], and a `PowerModifier` repeatedly multiplies it by itself (negative
+/// integer powers reuse the same conjugate-transpose shortcut as `InverseModifier`). Returns
+/// `None` for anything this can't handle purely numerically -- a non-integer power, or a
+/// `base_op` whose `to_matrix()` doesn't return one -- in which case the caller falls back to
+/// treating the op as having no matrix at all, same as before annotated ops got any special
+/// handling.
+fn annotated_matrix(py: Python, op: &CircuitInstruction) -> PyResult<Option<Array2<Complex64>>> {
+    let Some(py_op) = py_gate_object(op) else {
+        return Ok(None);
     };
-    let [op12, op21] = if first_op.qubits == second_op.qubits {
-        [second_mat.dot(&first_mat), first_mat.dot(&second_mat)]
-    } else {
-        let first_mat = if second_op.qubits.len() > num_qubits {
-            let id_op = Array2::eye(second_op.qubits.len());
-            id_op.tensor(operator_1)
-        } else {
-            first_mat
-        };
-        let op12 = compose_unitary(second_mat, first_mat, second_qarg);
-        let op21 = compose_unitary(first_mat, second_mat, second_qarg);
-        [op12, op21]
+    let py_op = py_op.bind(py);
+    let base_op = py_op.getattr(intern!(py, "base_op"))?;
+    let Some(base_matrix) = base_op
+        .call_method0(intern!(py, "to_matrix"))
+        .ok()
+        .and_then(|m| m.extract::<PyReadonlyArray2<Complex64>>().ok())
+    else {
+        return Ok(None);
     };
-    op12 == op21
+    let mut matrix = base_matrix.as_array().to_owned();
+
+    let modifiers: Vec<Bound<PyAny>> = py_op.getattr(intern!(py, "modifiers"))?.extract()?;
+    for modifier in &modifiers {
+        if let Ok(power) = modifier.getattr(intern!(py, "power")) {
+            let power: f64 = power.extract()?;
+            if power.fract() != 0.0 {
+                return Ok(None);
+            }
+            let mut powered = Array2::eye(matrix.nrows());
+            for _ in 0..(power.abs() as u32) {
+                powered = powered.dot(&matrix);
+            }
+            matrix = if power < 0.0 {
+                powered.t().mapv(|x| x.conj())
+            } else {
+                powered
+            };
+        } else if let Ok(num_ctrl_qubits) = modifier.getattr(intern!(py, "num_ctrl_qubits")) {
+            let num_ctrl_qubits: u32 = num_ctrl_qubits.extract()?;
+            let ctrl_state: Option<u32> =
+                modifier.getattr(intern!(py, "ctrl_state"))?.extract()?;
+            let ctrl_state = ctrl_state.unwrap_or((1u32 << num_ctrl_qubits) - 1);
+            matrix = controlled_matrix(&matrix, num_ctrl_qubits, ctrl_state);
+        } else {
+            // `InverseModifier` carries no attributes of its own beyond identifying the kind of
+            // modifier, so it's the fallback once `power`/`num_ctrl_qubits` have been ruled out.
+            matrix = matrix.t().mapv(|x| x.conj());
+        }
+    }
+    Ok(Some(matrix))
+}
+
+/// Expand `base` (acting on some register) into the unitary of `base` controlled on
+/// `num_ctrl_qubits` additional qubits active under `ctrl_state`: an identity on the full register
+/// except the block where every control qubit matches `ctrl_state`, which is `base` itself. The
+/// control qubits are the high-order wires above `base`'s own (Qiskit places them first in a
+/// controlled gate's qargs, and qubit 0 is the right-most, lowest-order wire throughout this
+/// crate), so that block is the contiguous `base`-sized slice starting at `ctrl_state *
+/// base.nrows()`.
+fn controlled_matrix(
+    base: &Array2<Complex64>,
+    num_ctrl_qubits: u32,
+    ctrl_state: u32,
+) -> Array2<Complex64> {
+    let base_dim = base.nrows();
+    let full_dim = base_dim * (1usize << num_ctrl_qubits);
+    let mut result = Array2::<Complex64>::eye(full_dim);
+    let offset = ctrl_state as usize * base_dim;
+    for i in 0..base_dim {
+        for j in 0..base_dim {
+            result[[offset + i, offset + j]] = base[[i, j]];
+        }
+    }
+    result
 }
 
 fn is_commutation_supported(op: &CircuitInstruction) -> bool {
@@ -274,7 +575,6 @@ fn commutation_precheck(
     }
     let qargs_vec: SmallVec<[PyObject; 2]> = op1.qubits.extract(py)?;
     let cargs_vec: SmallVec<[PyObject; 2]> = op1.clbits.extract(py)?;
-    // bind(py).iter().map(|x| x.clone_ref(py)).collect();
 
     let qargs_set = PySet::new_bound(py, &qargs_vec)?;
     let cargs_set = PySet::new_bound(py, &cargs_vec)?;
@@ -16,64 +16,198 @@ use lru::LruCache;
 use pyo3::exceptions::{PyKeyError, PyValueError};
 use pyo3::gc::PyVisit;
 use pyo3::prelude::*;
+use pyo3::types::{PyAny, PyList};
 use pyo3::PyTraverseError;
 
+/// The entries sharing a single Python `hash()` value. Almost always has exactly one element;
+/// more than one means a hash collision, which [`position_in_bucket`] resolves with a genuine
+/// Python `==` check rather than conflating the keys the way indexing straight off the hash used
+/// to.
+type Bucket = Vec<(PyObject, PyObject)>;
+
 #[derive(Clone)]
 #[pyclass(mapping)]
 struct LRUDict {
-    inner_dict: LruCache<isize, PyObject>,
+    inner_dict: LruCache<isize, Bucket>,
+    on_evict: Option<PyObject>,
+}
+
+impl LRUDict {
+    /// The `(index-in-bucket, value)` of `key`'s entry in its hash bucket, if present. Looking the
+    /// bucket up through `inner_dict.get` also promotes it to most-recently-used, matching a
+    /// plain dict `__getitem__`/`get`/`__contains__`'s "reading counts as a use" LRU semantics.
+    fn find(&mut self, key: &Bound<PyAny>) -> PyResult<Option<(usize, PyObject)>> {
+        let hash = key.hash()?;
+        let Some(bucket) = self.inner_dict.get(&hash) else {
+            return Ok(None);
+        };
+        position_in_bucket(key, bucket)?
+            .map(|index| Ok((index, bucket[index].1.clone_ref(key.py()))))
+            .transpose()
+    }
+
+    /// Invoke `on_evict` (if set) on every `(key, value)` pair in a bucket genuinely evicted by
+    /// capacity pressure, e.g. from [`LRUDict::__setitem__`] or [`LRUDict::set_capacity`].
+    fn report_evicted(&self, py: Python, bucket: Bucket) -> PyResult<()> {
+        if let Some(on_evict) = &self.on_evict {
+            for (key, value) in bucket {
+                on_evict.call1(py, (key, value))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[pymethods]
 impl LRUDict {
+    #[pyo3(signature = (maxsize, on_evict=None))]
     #[new]
-    pub fn new(maxsize: usize) -> PyResult<Self> {
+    pub fn new(maxsize: usize, on_evict: Option<PyObject>) -> PyResult<Self> {
         let max_size = match NonZeroUsize::new(maxsize) {
             Some(size) => size,
             None => return Err(PyValueError::new_err("maxsize must be non-zero")),
         };
         Ok(LRUDict {
             inner_dict: LruCache::new(max_size),
+            on_evict,
         })
     }
 
-    fn get(&mut self, py: Python, key: &PyAny, default: Option<PyObject>) -> PyResult<PyObject> {
-        let hash = key.hash()?;
-        match self.inner_dict.get(&hash) {
-            Some(obj) => Ok(obj.clone_ref(py)),
-            None => match default {
-                Some(default) => Ok(default.clone_ref(py)),
-                None => Ok(py.None()),
-            }
+    fn get(
+        &mut self,
+        py: Python,
+        key: &Bound<PyAny>,
+        default: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        match self.find(key)? {
+            Some((_, value)) => Ok(value),
+            None => Ok(default.unwrap_or_else(|| py.None())),
         }
     }
 
     fn __len__(&self) -> usize {
-        self.inner_dict.len()
+        self.inner_dict.iter().map(|(_, bucket)| bucket.len()).sum()
     }
 
-    fn __contains__(&self, key: &PyAny) -> PyResult<bool> {
+    fn __contains__(&mut self, key: &Bound<PyAny>) -> PyResult<bool> {
+        Ok(self.find(key)?.is_some())
+    }
+
+    fn __getitem__(&mut self, key: &Bound<PyAny>) -> PyResult<PyObject> {
+        match self.find(key)? {
+            Some((_, value)) => Ok(value),
+            None => Err(PyKeyError::new_err(key.repr()?.to_string())),
+        }
+    }
+
+    fn __setitem__(&mut self, key: &Bound<PyAny>, value: PyObject) -> PyResult<()> {
+        let py = key.py();
         let hash = key.hash()?;
-        Ok(self.inner_dict.contains(&hash))
+        let mut bucket = self.inner_dict.pop(&hash).unwrap_or_default();
+        match position_in_bucket(key, &bucket)? {
+            Some(index) => bucket[index].1 = value,
+            None => bucket.push((key.clone().unbind(), value)),
+        }
+        // `bucket` was just popped out, so any eviction `push` now reports is a different hash
+        // bucket genuinely pushed out by capacity, never the one we're re-inserting.
+        if let Some((_, evicted_bucket)) = self.inner_dict.push(hash, bucket) {
+            self.report_evicted(py, evicted_bucket)?;
+        }
+        Ok(())
     }
 
-    fn __getitem__(&mut self, key: &PyAny) -> PyResult<&PyObject> {
+    fn __delitem__(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
         let hash = key.hash()?;
-        match self.inner_dict.get(&hash) {
-            Some(obj) => Ok(obj),
-            None => Err(PyKeyError::new_err(format!("{} not found", key.str()?))),
+        let Some(mut bucket) = self.inner_dict.pop(&hash) else {
+            return Err(PyKeyError::new_err(key.repr()?.to_string()));
+        };
+        let index = position_in_bucket(key, &bucket)?;
+        let result = match index {
+            Some(index) => {
+                bucket.remove(index);
+                Ok(())
+            }
+            None => Err(PyKeyError::new_err(key.repr()?.to_string())),
+        };
+        if !bucket.is_empty() {
+            self.inner_dict.put(hash, bucket);
         }
+        result
+    }
+
+    fn __iter__(&self, py: Python) -> PyResult<PyObject> {
+        list_iter(py, self.keys(py))
     }
 
-    fn __setitem__(&mut self, key: &PyAny, value: PyObject) -> PyResult<()> {
+    fn keys(&self, py: Python) -> Vec<PyObject> {
+        self.inner_dict
+            .iter()
+            .flat_map(|(_, bucket)| bucket.iter().map(|(key, _)| key.clone_ref(py)))
+            .collect()
+    }
+
+    fn values(&self, py: Python) -> Vec<PyObject> {
+        self.inner_dict
+            .iter()
+            .flat_map(|(_, bucket)| bucket.iter().map(|(_, value)| value.clone_ref(py)))
+            .collect()
+    }
+
+    fn items(&self, py: Python) -> Vec<(PyObject, PyObject)> {
+        self.inner_dict
+            .iter()
+            .flat_map(|(_, bucket)| {
+                bucket
+                    .iter()
+                    .map(|(key, value)| (key.clone_ref(py), value.clone_ref(py)))
+            })
+            .collect()
+    }
+
+    /// Evict and return the least-recently-used `(key, value)` pair.
+    fn popitem(&mut self) -> PyResult<(PyObject, PyObject)> {
+        let Some((hash, mut bucket)) = self.inner_dict.pop_lru() else {
+            return Err(PyKeyError::new_err("popitem(): LRUDict is empty"));
+        };
+        let item = bucket.remove(0);
+        if !bucket.is_empty() {
+            self.inner_dict.put(hash, bucket);
+        }
+        Ok(item)
+    }
+
+    /// Mark `key` as most-recently-used without changing its value.
+    fn move_to_end(&mut self, key: &Bound<PyAny>) -> PyResult<()> {
         let hash = key.hash()?;
-        self.inner_dict.push(hash, value);
+        if self.find(key)?.is_none() {
+            return Err(PyKeyError::new_err(key.repr()?.to_string()));
+        }
+        self.inner_dict.promote(&hash);
+        Ok(())
+    }
+
+    /// Resize the cache to hold at most `maxsize` hash buckets, evicting least-recently-used
+    /// entries (and calling `on_evict` for each) if it's currently over the new size.
+    fn set_capacity(&mut self, py: Python, maxsize: usize) -> PyResult<()> {
+        let maxsize = match NonZeroUsize::new(maxsize) {
+            Some(size) => size,
+            None => return Err(PyValueError::new_err("maxsize must be non-zero")),
+        };
+        while self.inner_dict.len() > maxsize.get() {
+            if let Some((_, bucket)) = self.inner_dict.pop_lru() {
+                self.report_evicted(py, bucket)?;
+            }
+        }
+        self.inner_dict.resize(maxsize);
         Ok(())
     }
 
     fn __traverse__(&self, visit: PyVisit) -> Result<(), PyTraverseError> {
-        for (_hash, obj) in &self.inner_dict {
-            visit.call(obj)?;
+        for (_hash, bucket) in &self.inner_dict {
+            for (key, value) in bucket {
+                visit.call(key)?;
+                visit.call(value)?;
+            }
         }
         Ok(())
     }
@@ -83,6 +217,27 @@ impl LRUDict {
     }
 }
 
+/// `key`'s position within `bucket`, found by genuine Python `==` (not the hash both already
+/// share). Used by `__setitem__`/`__delitem__`, which pop their bucket out of `inner_dict` before
+/// searching it so they can freely mutate or drop it without disturbing the cache's recency order
+/// any more than the write itself should.
+fn position_in_bucket(key: &Bound<PyAny>, bucket: &Bucket) -> PyResult<Option<usize>> {
+    for (index, (candidate, _)) in bucket.iter().enumerate() {
+        if candidate.bind(key.py()).eq(key)? {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+/// Build a genuine Python iterator (not just an iterable) over `items`, as `__iter__` must
+/// return.
+fn list_iter(py: Python, items: Vec<PyObject>) -> PyResult<PyObject> {
+    Ok(PyList::new_bound(py, items)
+        .call_method0(pyo3::intern!(py, "__iter__"))?
+        .unbind())
+}
+
 #[pymodule]
 pub fn lru_dict(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<LRUDict>()?;
@@ -0,0 +1,213 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2024
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+//! Rust-native synthesis of multi-controlled `X` gates, feeding the `definition` of
+//! `MCXGate`/`MCXRecursive`/`MCXVChain` without a round trip through the Python `mcx` library.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use smallvec::{smallvec, SmallVec};
+
+use qiskit_circuit::circuit_data::CircuitData;
+use qiskit_circuit::operations::{Param, StandardGate};
+use qiskit_circuit::Qubit;
+
+const FLOAT_ZERO: Param = Param::Float(0.0);
+
+type GateTriple = (StandardGate, SmallVec<[Param; 3]>, SmallVec<[Qubit; 2]>);
+
+fn gate(standard: StandardGate, qubits: &[usize]) -> GateTriple {
+    (
+        standard,
+        smallvec![],
+        qubits.iter().map(|&q| Qubit(q as u32)).collect(),
+    )
+}
+
+/// The exact multi-controlled `X` on `controls` (at least one) and `target`, using only the
+/// hardcoded standard gates for up to 4 controls.
+fn base_case(controls: &[usize], target: usize) -> GateTriple {
+    let standard = match controls.len() {
+        1 => StandardGate::CXGate,
+        2 => StandardGate::CCXGate,
+        3 => StandardGate::C3XGate,
+        4 => StandardGate::C4XGate,
+        n => panic!("no hardcoded standard gate for a {n}-controlled X"),
+    };
+    let mut qubits = controls.to_vec();
+    qubits.push(target);
+    gate(standard, &qubits)
+}
+
+/// Recursive (Barenco et al., Lemma 7.3) construction of an exact multi-controlled `X` using a
+/// single borrowed ancilla, splitting `controls` into two roughly-even halves `a`/`b` at each
+/// level: `a` is first exactly-MCX'd onto `ancilla`, `b` (plus the now-set `ancilla`) is then
+/// exactly-MCX'd onto `target`, and the first step is repeated to uncompute `ancilla` back to
+/// its original value. Each of those three sub-calls recurses the same way if it still has more
+/// than 4 controls, borrowing further qubits that are provably idle at that point in the circuit
+/// -- `target` while computing/uncomputing `ancilla` (since `a`'s sub-circuit never touches it),
+/// and `a` while computing onto `target` (since it is idle once `ancilla` is set) -- so the
+/// whole construction only ever needs the one real ancilla qubit beyond controls and target.
+///
+/// `ancilla_clean` must be `false` for every one of those borrowed qubits, since they hold live
+/// circuit state rather than a known `|0>`: computing `a` onto a dirty `ancilla` leaves behind
+/// `ancilla`'s original value XORed into the combine step below, so the compute/combine/uncompute
+/// sequence alone (correct when `ancilla` starts at `|0>`) needs the combine step repeated a
+/// second time, after `ancilla` is restored, to cancel that leftover contribution exactly. Only
+/// the single real ancilla `mcx_recursive` allocates up front is ever `ancilla_clean`.
+fn recursive_mcx(
+    controls: &[usize],
+    target: usize,
+    dirty: &[usize],
+    ancilla_clean: bool,
+) -> Vec<GateTriple> {
+    if controls.len() <= 4 {
+        return vec![base_case(controls, target)];
+    }
+    let mid = controls.len().div_ceil(2);
+    let (a, b) = controls.split_at(mid);
+    let ancilla = dirty[0];
+
+    let mut a_pool = vec![target];
+    a_pool.extend(&dirty[1..]);
+
+    let mut b_controls = b.to_vec();
+    b_controls.push(ancilla);
+    let mut b_pool = a.to_vec();
+    b_pool.extend(&dirty[1..]);
+
+    let compute = recursive_mcx(a, ancilla, &a_pool, false);
+    let combine = recursive_mcx(&b_controls, target, &b_pool, false);
+
+    let mut gates = Vec::new();
+    gates.extend(compute.iter().cloned());
+    gates.extend(combine.iter().cloned());
+    gates.extend(compute);
+    if !ancilla_clean {
+        gates.extend(combine);
+    }
+    gates
+}
+
+/// Build the `CircuitData` for an exact `num_ctrl_qubits`-controlled `X`, over qubits
+/// `0..num_ctrl_qubits` (controls), `num_ctrl_qubits` (target), and, when `num_ctrl_qubits > 4`,
+/// one extra ancilla qubit `num_ctrl_qubits + 1` borrowed and returned clean. See
+/// [`recursive_mcx`].
+#[pyfunction]
+pub fn mcx_recursive(num_ctrl_qubits: u32) -> PyResult<CircuitData> {
+    assert!(
+        num_ctrl_qubits >= 1,
+        "an MCX gate needs at least one control qubit"
+    );
+    let target = num_ctrl_qubits as usize;
+    let controls: Vec<usize> = (0..target).collect();
+    let (gates, num_qubits) = if num_ctrl_qubits <= 4 {
+        (vec![base_case(&controls, target)], num_ctrl_qubits + 1)
+    } else {
+        let ancilla = target + 1;
+        (
+            recursive_mcx(&controls, target, &[ancilla], true),
+            num_ctrl_qubits + 2,
+        )
+    };
+    Python::with_gil(|py| CircuitData::from_standard_gates(py, num_qubits, gates, FLOAT_ZERO))
+}
+
+/// Build the `CircuitData` for an exact `num_ctrl_qubits`-controlled `X` using `num_ctrl_qubits
+/// - 2` clean ancilla qubits arranged as a linear chain of relative-phase Toffolis (Barenco et
+/// al., Lemma 7.2): the chain AND-accumulates the controls pairwise into the ancillas using
+/// `RCCXGate` (cheap because each one's relative phase is cancelled when the chain is later
+/// uncomputed in reverse), applies one genuine `CCXGate` from the last ancilla onto `target`, and
+/// then uncomputes the chain. Requires `num_ctrl_qubits >= 3` (use [`mcx_recursive`]'s hardcoded
+/// base cases below that). Qubits are `0..num_ctrl_qubits` (controls), `num_ctrl_qubits`
+/// (target), then `num_ctrl_qubits - 2` ancillas; the ancillas must be clean (known `|0>`) on
+/// entry -- a dirty-ancilla variant needs an extra phase-correction preamble/postamble around
+/// this chain that isn't implemented here.
+#[pyfunction]
+pub fn mcx_vchain(num_ctrl_qubits: u32) -> PyResult<CircuitData> {
+    assert!(
+        num_ctrl_qubits >= 3,
+        "the V-chain construction needs at least one ancilla; use a hardcoded gate below that"
+    );
+    let target = num_ctrl_qubits as usize;
+    let num_ancillas = num_ctrl_qubits as usize - 2;
+    let ancilla = |i: usize| target + 1 + i;
+    let control = |i: usize| i;
+
+    let mut gates = Vec::with_capacity(4 * num_ancillas + 1);
+    gates.push(gate(
+        StandardGate::RCCXGate,
+        &[control(0), control(1), ancilla(0)],
+    ));
+    for j in 2..num_ctrl_qubits as usize - 1 {
+        gates.push(gate(
+            StandardGate::RCCXGate,
+            &[control(j), ancilla(j - 2), ancilla(j - 1)],
+        ));
+    }
+    gates.push(gate(
+        StandardGate::CCXGate,
+        &[
+            control(num_ctrl_qubits as usize - 1),
+            ancilla(num_ancillas - 1),
+            target,
+        ],
+    ));
+    for j in (2..num_ctrl_qubits as usize - 1).rev() {
+        gates.push(gate(
+            StandardGate::RCCXGate,
+            &[control(j), ancilla(j - 2), ancilla(j - 1)],
+        ));
+    }
+    gates.push(gate(
+        StandardGate::RCCXGate,
+        &[control(0), control(1), ancilla(0)],
+    ));
+
+    let num_qubits = num_ctrl_qubits + 1 + num_ancillas as u32;
+    Python::with_gil(|py| CircuitData::from_standard_gates(py, num_qubits, gates, FLOAT_ZERO))
+}
+
+/// Synthesize an arbitrary-arity multi-controlled `X`, choosing a strategy by how many ancilla
+/// qubits are available: the hardcoded base gates need none at all (`num_ctrl_qubits <= 4`), the
+/// V-chain needs `num_ctrl_qubits - 2` *clean* ancillas and gives the cheapest CX count, and the
+/// recursive method is the fallback when that many aren't available, needing only one clean
+/// ancilla regardless of `num_ctrl_qubits`. `num_dirty_ancillas` is accepted for forward
+/// compatibility with a dirty-ancilla V-chain variant (see [`mcx_vchain`]'s docs on why that
+/// isn't implemented yet) but isn't consulted by either strategy here.
+#[pyfunction]
+pub fn mcx_synthesis(
+    num_ctrl_qubits: u32,
+    num_clean_ancillas: u32,
+    num_dirty_ancillas: u32,
+) -> PyResult<CircuitData> {
+    let _ = num_dirty_ancillas;
+    if num_ctrl_qubits <= 4 {
+        mcx_recursive(num_ctrl_qubits)
+    } else if num_clean_ancillas >= num_ctrl_qubits - 2 {
+        mcx_vchain(num_ctrl_qubits)
+    } else if num_clean_ancillas >= 1 {
+        mcx_recursive(num_ctrl_qubits)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "synthesizing a {num_ctrl_qubits}-controlled X needs at least one clean ancilla qubit"
+        )))
+    }
+}
+
+pub fn mcx_synthesis_mod(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_wrapped(wrap_pyfunction!(mcx_recursive))?;
+    m.add_wrapped(wrap_pyfunction!(mcx_vchain))?;
+    m.add_wrapped(wrap_pyfunction!(mcx_synthesis))?;
+    Ok(())
+}
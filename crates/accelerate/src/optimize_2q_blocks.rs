@@ -13,16 +13,133 @@
 use hashbrown::HashMap;
 use ndarray::ArrayView2;
 use num_complex::Complex64;
-use numpy::PyReadonlyArray2;
+use numpy::{IntoPyArray, PyReadonlyArray2};
+use pyo3::intern;
 use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
 use pyo3::wrap_pyfunction;
 use rayon::prelude::*;
 use smallvec::SmallVec;
 
+use qiskit_circuit::dag_circuit::{DAGCircuit, NodeType};
+use qiskit_circuit::operations::Operation;
+
 use crate::convert_2q_block_matrix::blocks_to_matrix_inner;
 use crate::getenv_use_multiple_threads;
 use crate::two_qubit_decompose::{TwoQubitBasisDecomposer, TwoQubitGateSequence};
 
+/// An `XXDecomposer` Python object paired with the representative basis gate it was registered
+/// for (e.g. `RZXGate(theta)`), the same (decomposer, gate) split
+/// [`unitary_synthesis`][crate::unitary_synthesis]'s `DecomposerElement` uses -- unlike
+/// `TwoQubitBasisDecomposer`, `XXDecomposer` has no single fixed basis gate of its own to read a
+/// name or error rate off of, so the two travel together.
+#[derive(Clone)]
+pub struct XXDecomposerEntry {
+    decomposer: PyObject,
+    gate_obj: PyObject,
+}
+
+/// The result of resynthesizing a 2q block through one candidate [`Decomposer`]: a flat gate
+/// sequence for a `TwoQubitBasisDecomposer`, or a full `DAGCircuit` for an `XXDecomposer`
+/// candidate. Unlike a fixed basis gate, `XXDecomposer` picks how many applications of (possibly
+/// several) available interaction strengths to combine, interleaved with 1q gates solved from the
+/// Weyl chamber at each step -- a real synthesis algorithm, not a lookup -- so its result isn't
+/// expressible as a single flat [`TwoQubitGateSequence`] the way `TwoQubitBasisDecomposer`'s is;
+/// see [`unitary_synthesis::synth_su4_dag`][crate::unitary_synthesis] for the same split.
+enum Synthesized {
+    Sequence(TwoQubitGateSequence),
+    Dag(DAGCircuit),
+}
+
+impl IntoPy<PyObject> for Synthesized {
+    fn into_py(self, py: Python) -> PyObject {
+        match self {
+            Self::Sequence(sequence) => sequence.into_py(py),
+            Self::Dag(dag) => dag.into_py(py),
+        }
+    }
+}
+
+/// A two-qubit-block resynthesizer, covering every decomposer kind `optimize_blocks` can draw
+/// candidate sequences from for a given qubit pair. `synthesize` is the common entry point shared
+/// by every variant, so `optimize_blocks` can try every decomposer registered for a qubit pair
+/// and let [best_synthesis] pick the lowest-error result without needing to know which kind
+/// produced it.
+///
+/// `XX` wraps the Python `XXDecomposer` object rather than porting its KAK-coordinate search to
+/// Rust, the same way `unitary_synthesis.rs`'s `DecomposerType::XXDecomposer` does -- it stays a
+/// follow-up to actually port the algorithm, but in the meantime hardware with a
+/// continuously-parameterized two-qubit basis (e.g. an `RZX`- or `RXX`-family coupler) can still
+/// be routed through it.
+#[derive(Clone)]
+pub enum Decomposer {
+    TwoQubitBasis(TwoQubitBasisDecomposer),
+    XX(XXDecomposerEntry),
+}
+
+impl Decomposer {
+    fn synthesize(
+        &self,
+        py: Python,
+        unitary: ArrayView2<Complex64>,
+        approximation_degree: Option<f64>,
+        use_dag: bool,
+        basis_fidelity: Option<f64>,
+    ) -> PyResult<Synthesized> {
+        match self {
+            Self::TwoQubitBasis(decomposer) => Ok(Synthesized::Sequence(decomposer.synthesize(
+                unitary,
+                approximation_degree,
+                use_dag,
+                basis_fidelity,
+            )?)),
+            Self::XX(entry) => {
+                let is_approximate =
+                    approximation_degree.is_none() || approximation_degree.unwrap() != 1.0;
+                let kwargs: HashMap<&str, bool> =
+                    [("approximate", is_approximate), ("use_dag", true)]
+                        .into_iter()
+                        .collect();
+                let dag = entry
+                    .decomposer
+                    .call_bound(
+                        py,
+                        (unitary.to_owned().into_pyarray_bound(py),),
+                        Some(&kwargs.into_py_dict_bound(py)),
+                    )?
+                    .extract::<DAGCircuit>(py)?;
+                Ok(Synthesized::Dag(dag))
+            }
+        }
+    }
+
+    fn gate_obj(&self, py: Python) -> PyObject {
+        match self {
+            Self::TwoQubitBasis(decomposer) => decomposer.gate_obj.clone_ref(py),
+            Self::XX(entry) => entry.gate_obj.clone_ref(py),
+        }
+    }
+
+    /// The name of this decomposer's representative basis gate, read off its Python-side gate
+    /// object.
+    fn gate_name(&self, py: Python) -> Option<String> {
+        let gate_obj = match self {
+            Self::TwoQubitBasis(decomposer) => &decomposer.gate_obj,
+            Self::XX(entry) => &entry.gate_obj,
+        };
+        gate_obj.getattr(py, intern!(py, "name")).ok()?.extract(py).ok()
+    }
+
+    /// The fidelity this decomposer should approximate against for `qubits`, derived from the
+    /// basis gate's reported error rate in `target`. `None` when the target has no error rate on
+    /// record for this gate/qubit pair, in which case the caller should fall back to an exact
+    /// synthesis budget.
+    fn basis_fidelity(&self, py: Python, qubits: [u32; 2], target: &TargetErrorMap) -> Option<f64> {
+        let name = self.gate_name(py)?;
+        target.get_error_rate(&name, qubits).map(|error| 1. - error)
+    }
+}
+
 #[pyclass]
 pub struct TargetErrorMap {
     error_map: HashMap<String, HashMap<[u32; 2], Option<f64>>>,
@@ -61,7 +178,7 @@ impl TargetErrorMap {
 #[derive(Clone)]
 #[pyclass]
 pub struct DecomposerMap {
-    decomposer_map: HashMap<[u32; 2], Vec<TwoQubitBasisDecomposer>>,
+    decomposer_map: HashMap<[u32; 2], Vec<Decomposer>>,
 }
 
 #[pymethods]
@@ -74,12 +191,32 @@ impl DecomposerMap {
     }
 
     fn add_decomposer(&mut self, qubits: [u32; 2], decomposer: &TwoQubitBasisDecomposer) {
+        self.push(qubits, Decomposer::TwoQubitBasis(decomposer.clone()));
+    }
+
+    /// Register an `XXDecomposer` Python object (e.g. for a continuously-tunable `RZX`/`RXX`/
+    /// `RYY`/`RZZ`-family coupler) as a candidate for `qubits`, alongside `gate_obj` -- the
+    /// representative basis gate [`Decomposer::gate_name`]/[`Decomposer::basis_fidelity`] read
+    /// error rates and the gate name off of, since the `XXDecomposer` object itself has no single
+    /// fixed basis gate the way a `TwoQubitBasisDecomposer` does.
+    fn add_xx_decomposer(&mut self, qubits: [u32; 2], decomposer: PyObject, gate_obj: PyObject) {
+        self.push(
+            qubits,
+            Decomposer::XX(XXDecomposerEntry {
+                decomposer,
+                gate_obj,
+            }),
+        );
+    }
+}
+
+impl DecomposerMap {
+    fn push(&mut self, qubits: [u32; 2], decomposer: Decomposer) {
         if !self.decomposer_map.contains_key(&qubits) {
-            let decomposer_list = vec![decomposer.clone()];
-            self.decomposer_map.insert(qubits, decomposer_list);
+            self.decomposer_map.insert(qubits, vec![decomposer]);
         } else {
             let res = self.decomposer_map.get_mut(&qubits).unwrap();
-            res.push(decomposer.clone());
+            res.push(decomposer);
         }
     }
 }
@@ -93,15 +230,13 @@ type BlockInputType<'a> = Vec<(
     [u32; 2],
 )>;
 
-// TODO: When XX decomposer is ported to rust add an enum that can be used for either
-// decomposer type
 #[pyfunction]
 pub fn optimize_blocks(
     py: Python,
     blocks: BlockInputType,
     decomposers: &DecomposerMap,
     target: &TargetErrorMap,
-) -> Vec<Option<(TwoQubitGateSequence, PyObject)>> {
+) -> PyResult<Vec<Option<(Synthesized, PyObject)>>> {
     let run_in_parallel = getenv_use_multiple_threads();
     let blocks: InnerBlockType = blocks
         .iter()
@@ -131,16 +266,29 @@ pub fn optimize_blocks(
                             None => panic!("invalid qubits: {:?} or {:?}", qubits, reverse_qubits),
                         },
                     };
-                    let sequences = decomposer_lists
-                        .iter()
-                        .filter_map(|decomposer| {
-                            let synthesis = decomposer.synthesize(unitary.view(), None, true, None);
-                            match synthesis {
-                                Ok(s) => Some((s, decomposer.gate_obj.clone())),
-                                Err(_) => None,
-                            }
-                        })
-                        .collect();
+                    let sequences = Python::with_gil(|py| {
+                        decomposer_lists
+                            .iter()
+                            .filter_map(|decomposer| {
+                                let basis_fidelity = decomposer.basis_fidelity(py, qubits, target);
+                                let synthesis = decomposer.synthesize(
+                                    py,
+                                    unitary.view(),
+                                    None,
+                                    true,
+                                    basis_fidelity,
+                                );
+                                match synthesis {
+                                    Ok(s) => Some((
+                                        s,
+                                        decomposer.gate_obj(py),
+                                        basis_fidelity.unwrap_or(1.0),
+                                    )),
+                                    Err(_) => None,
+                                }
+                            })
+                            .collect()
+                    });
                     best_synthesis(sequences, qubits, target)
                 })
                 .collect()
@@ -157,9 +305,15 @@ pub fn optimize_blocks(
                 let sequences = decomposer_lists
                     .iter()
                     .filter_map(|decomposer| {
-                        let synthesis = decomposer.synthesize(unitary.view(), None, true, None);
+                        let basis_fidelity = decomposer.basis_fidelity(py, qubits, target);
+                        let synthesis =
+                            decomposer.synthesize(py, unitary.view(), None, true, basis_fidelity);
                         match synthesis {
-                            Ok(s) => Some((s, decomposer.gate_obj.clone_ref(py))),
+                            Ok(s) => Some((
+                                s,
+                                decomposer.gate_obj(py),
+                                basis_fidelity.unwrap_or(1.0),
+                            )),
                             Err(_) => None,
                         }
                     })
@@ -170,12 +324,18 @@ pub fn optimize_blocks(
     }
 }
 
+/// The combined objective `1 − F_approx·(∏(1−error_i))` for a candidate sequence: the
+/// decomposer-reported approximation fidelity `approx_fidelity` discounted further by the
+/// per-instruction error rates `target` reports for `qubits`. Lower is better. An instruction
+/// the target has no error rate for is treated as error-free, matching `TargetErrorMap`'s
+/// "unknown means ideal" convention.
 fn error_for_sequence(
     sequence: &TwoQubitGateSequence,
+    approx_fidelity: f64,
     qubits: [u32; 2],
     target: &TargetErrorMap,
 ) -> f64 {
-    let mut fidelity = 1.0;
+    let mut fidelity = approx_fidelity;
     for inst in &sequence.gates {
         let qubits = if inst.2.len() == 1 {
             [qubits[inst.2[0] as usize], qubits[inst.2[0] as usize]]
@@ -190,19 +350,63 @@ fn error_for_sequence(
     1. - fidelity
 }
 
+/// [`error_for_sequence`]'s counterpart for an `XXDecomposer` candidate's result: walks the
+/// synthesized `dag`'s nodes in topological order instead of a flat `TwoQubitGateSequence`, since
+/// `XXDecomposer`'s result isn't expressible as one (see [`Synthesized`]).
+fn error_for_dag(
+    dag: &DAGCircuit,
+    approx_fidelity: f64,
+    qubits: [u32; 2],
+    target: &TargetErrorMap,
+) -> PyResult<f64> {
+    let mut fidelity = approx_fidelity;
+    for node in dag.topological_op_nodes()? {
+        let NodeType::Operation(ref inst) = dag.dag()[node] else {
+            unreachable!("All dag nodes produced by synthesis will be ops")
+        };
+        let local_qubits = dag.get_qargs(inst.qubits);
+        let node_qubits = if local_qubits.len() == 1 {
+            [
+                qubits[local_qubits[0].0 as usize],
+                qubits[local_qubits[0].0 as usize],
+            ]
+        } else {
+            [
+                qubits[local_qubits[0].0 as usize],
+                qubits[local_qubits[1].0 as usize],
+            ]
+        };
+        if let Some(error) = target.get_error_rate(inst.op.name(), node_qubits) {
+            fidelity *= 1. - error;
+        }
+    }
+    Ok(1. - fidelity)
+}
+
 fn best_synthesis(
-    sequences: Vec<(TwoQubitGateSequence, PyObject)>,
+    sequences: Vec<(Synthesized, PyObject, f64)>,
     qubits: [u32; 2],
     target: &TargetErrorMap,
-) -> Option<(TwoQubitGateSequence, PyObject)> {
+) -> PyResult<Option<(Synthesized, PyObject)>> {
     if sequences.is_empty() {
-        return None;
+        return Ok(None);
     }
-    sequences.into_iter().min_by(|sequence_a, sequence_b| {
-        error_for_sequence(&sequence_a.0, qubits, target)
-            .partial_cmp(&error_for_sequence(&sequence_b.0, qubits, target))
-            .unwrap()
-    })
+    let scored = sequences
+        .into_iter()
+        .map(|(synthesized, gate_obj, approx_fidelity)| {
+            let error = match &synthesized {
+                Synthesized::Sequence(sequence) => {
+                    error_for_sequence(sequence, approx_fidelity, qubits, target)
+                }
+                Synthesized::Dag(dag) => error_for_dag(dag, approx_fidelity, qubits, target)?,
+            };
+            Ok((synthesized, gate_obj, error))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(scored
+        .into_iter()
+        .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+        .map(|(synthesized, gate_obj, _)| (synthesized, gate_obj)))
 }
 
 #[pymodule]
@@ -10,34 +10,97 @@
 // copyright notice, and modified files need to carry a notice indicating
 // that they have been altered from the originals.
 
+use std::f64::consts::PI;
+use std::sync::Mutex;
+
 use approx::abs_diff_eq;
-use ndarray::{aview2, Array2};
+use ndarray::Array2;
 use num_complex::Complex64;
 use pyo3::prelude::*;
+use rayon::prelude::*;
 use rustworkx_core::petgraph::stable_graph::NodeIndex;
 
 use crate::nlayout::PhysicalQubit;
 use crate::target_transpiler::Target;
 use qiskit_circuit::dag_circuit::DAGCircuit;
-use qiskit_circuit::gate_matrix::ONE_QUBIT_IDENTITY;
 use qiskit_circuit::operations::Operation;
 use qiskit_circuit::operations::OperationRef;
 use qiskit_circuit::operations::Param;
+use qiskit_circuit::operations::StandardGate;
 use qiskit_circuit::packed_instruction::PackedInstruction;
-use qiskit_circuit::util::{C_ONE, C_ZERO};
 
-static TWO_QUBIT_IDENTITY: [[Complex64; 4]; 4] = [
-    [C_ONE, C_ZERO, C_ZERO, C_ZERO],
-    [C_ZERO, C_ONE, C_ZERO, C_ZERO],
-    [C_ZERO, C_ZERO, C_ONE, C_ZERO],
-    [C_ZERO, C_ZERO, C_ZERO, C_ONE],
-];
+/// The angle `theta` reduced into `[0, period)`, within floating-point rounding.
+fn reduce_angle(theta: f64, period: f64) -> f64 {
+    theta.rem_euclid(period)
+}
+
+/// If `gate(angle)` is a scalar multiple of the identity for a reduced `angle` within `tol` of
+/// `0`, or -- when `half_period_phase` is `Some`, meaning this rotation also collapses at its
+/// half-period point -- within `tol` of `period / 2`, return the discarded global phase needed to
+/// keep the circuit exactly equivalent once the gate itself is dropped. `P`/`U1` never pass
+/// `half_period_phase`: their fixed top-left `1` entry means they only ever reach `I` exactly, at
+/// `angle == 0`, unlike the spinor rotations `RX`/`RY`/`RZ`, which become `-I` (phase `pi`) at
+/// `angle == period / 2` too.
+fn periodic_rotation_phase(
+    angle: f64,
+    period: f64,
+    half_period_phase: Option<f64>,
+    tol: f64,
+) -> Option<f64> {
+    let reduced = reduce_angle(angle, period);
+    if reduced.abs() < tol || (period - reduced).abs() < tol {
+        Some(0.0)
+    } else if let Some(half_period_phase) = half_period_phase {
+        if (reduced - period / 2.0).abs() < tol {
+            Some(half_period_phase)
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+/// Check whether `standard` is one of the single-parameter rotations whose period and half-period
+/// global phase we know how to reason about, returning `(period, half_period_phase)` if so.
+fn rotation_period(standard: StandardGate) -> Option<(f64, Option<f64>)> {
+    match standard {
+        StandardGate::PhaseGate | StandardGate::U1Gate => Some((2.0 * PI, None)),
+        StandardGate::RXGate | StandardGate::RYGate | StandardGate::RZGate => {
+            Some((4.0 * PI, Some(PI)))
+        }
+        _ => None,
+    }
+}
+
+/// Check whether `matrix` is close (within `tol`) to `lambda * I` for some scalar `lambda` with
+/// `|lambda| == 1`, returning `lambda` if so. This subsumes an exact-identity check (`lambda ==
+/// 1`) while also catching gates that are only the identity up to a global phase, as long as that
+/// phase is folded back into the circuit afterwards.
+fn scalar_multiple_of_identity(matrix: &Array2<Complex64>, tol: f64) -> Option<Complex64> {
+    let n = matrix.nrows();
+    if matrix.ncols() != n || n == 0 {
+        return None;
+    }
+    let lambda = matrix[[0, 0]];
+    let scaled_identity: Array2<Complex64> = Array2::<Complex64>::eye(n) * lambda;
+    if abs_diff_eq!(lambda.norm(), 1.0, epsilon = tol)
+        && abs_diff_eq!(*matrix, scaled_identity, epsilon = tol)
+    {
+        Some(lambda)
+    } else {
+        None
+    }
+}
 
 #[pyfunction]
 #[pyo3(signature=(dag, tol=Some(f64::EPSILON), target=None))]
-fn remove_identity_equiv(dag: &mut DAGCircuit, tol: Option<f64>, target: Option<&Target>) {
-    let mut remove_list: Vec<NodeIndex> = Vec::new();
-
+fn remove_identity_equiv(
+    py: Python,
+    dag: &mut DAGCircuit,
+    tol: Option<f64>,
+    target: Option<&Target>,
+) -> PyResult<()> {
     let get_tolerance = |inst: &PackedInstruction| -> f64 {
         match tol {
             Some(tol) => tol,
@@ -59,47 +122,62 @@ fn remove_identity_equiv(dag: &mut DAGCircuit, tol: Option<f64>, target: Option<
         }
     };
 
-    for op_node in dag.op_nodes(false) {
+    // Each node's identity check only reads that node's own instruction, params, and the target
+    // error rate, so the candidates can be scored independently in parallel, mirroring
+    // `two_qubit_unitary_peephole_optimize`'s `par_iter` + `Mutex`-collected-results pattern;
+    // only the actual graph mutation below has to stay serial. Each removable node also carries
+    // the global phase it discards (usually `0.0`), so dropping it still leaves the circuit
+    // exactly equivalent.
+    let op_nodes: Vec<NodeIndex> = dag.op_nodes(false).collect();
+    let locked_remove_list: Mutex<Vec<(NodeIndex, f64)>> = Mutex::new(Vec::new());
+
+    op_nodes.par_iter().for_each(|&op_node| {
         let inst = dag.dag()[op_node].unwrap_operation();
-        match inst.op.view() {
+        let removable_phase = match inst.op.view() {
             OperationRef::Standard(gate) => {
                 let tol = get_tolerance(inst);
-                if gate.num_params() > 0
-                    && inst.params_view().iter().all(|x| match x {
-                        Param::Float(param) => param.abs() < tol,
-                        _ => false,
-                    })
-                {
-                    remove_list.push(op_node);
+                let params = inst.params_view();
+                match (rotation_period(gate), params.first()) {
+                    (Some((period, half_period_phase)), Some(Param::Float(angle)))
+                        if params.len() == 1 =>
+                    {
+                        periodic_rotation_phase(*angle, period, half_period_phase, tol)
+                    }
+                    _ => {
+                        if gate.num_params() > 0
+                            && params.iter().all(|x| match x {
+                                Param::Float(param) => param.abs() < tol,
+                                _ => false,
+                            })
+                        {
+                            Some(0.0)
+                        } else {
+                            None
+                        }
+                    }
                 }
             }
             OperationRef::Gate(gate) => {
                 if let Some(matrix) = gate.matrix(inst.params_view()) {
-                    if gate.num_qubits() == 1 {
-                        let tol = get_tolerance(inst);
-                        if abs_diff_eq!(matrix, aview2(&ONE_QUBIT_IDENTITY), epsilon = tol) {
-                            remove_list.push(op_node);
-                        }
-                    } else if gate.num_qubits() == 2 {
-                        let tol = get_tolerance(inst);
-                        if abs_diff_eq!(matrix, aview2(&TWO_QUBIT_IDENTITY), epsilon = tol) {
-                            remove_list.push(op_node);
-                        }
-                    } else {
-                        let tol = get_tolerance(inst);
-                        let identity = Array2::eye(gate.num_qubits().pow(2) as usize);
-                        if abs_diff_eq!(matrix, identity, epsilon = tol) {
-                            remove_list.push(op_node);
-                        }
-                    }
+                    let tol = get_tolerance(inst);
+                    scalar_multiple_of_identity(&matrix, tol).map(|lambda| lambda.arg())
+                } else {
+                    None
                 }
             }
-            _ => continue,
+            _ => None,
+        };
+        if let Some(phase) = removable_phase {
+            locked_remove_list.lock().unwrap().push((op_node, phase));
         }
-    }
-    for node in remove_list {
+    });
+    for (node, phase) in locked_remove_list.into_inner().unwrap() {
         dag.remove_op_node(node);
+        if phase != 0.0 {
+            dag.add_global_phase(py, &Param::Float(phase))?;
+        }
     }
+    Ok(())
 }
 
 pub fn remove_identity_equiv_mod(m: &Bound<PyModule>) -> PyResult<()> {
@@ -14,6 +14,8 @@ use std::cmp::Ordering;
 use std::sync::Mutex;
 
 use hashbrown::{HashMap, HashSet};
+use ndarray::{Array2, ArrayView2};
+use num_complex::Complex64;
 use pyo3::prelude::*;
 use rayon::prelude::*;
 use rustworkx_core::petgraph::stable_graph::NodeIndex;
@@ -25,19 +27,40 @@ use qiskit_circuit::operations::{Operation, OperationRef, Param, StandardGate};
 use qiskit_circuit::packed_instruction::PackedOperation;
 use qiskit_circuit::Qubit;
 
+use crate::commutation_checker::{commute_matrices, DEFAULT_ATOL, DEFAULT_RTOL};
 use crate::convert_2q_block_matrix::compose_2q_matrix;
 use crate::euler_one_qubit_decomposer::{
-    EulerBasis, EulerBasisSet, EULER_BASES, EULER_BASIS_NAMES,
+    unitary_to_gate_sequence_inner, EulerBasis, EulerBasisSet, OneQubitGateSequence, EULER_BASES,
+    EULER_BASIS_NAMES,
 };
 use crate::nlayout::PhysicalQubit;
 use crate::target_transpiler::{NormalOperation, Target};
-use crate::two_qubit_decompose::{TwoQubitBasisDecomposer, TwoQubitGateSequence};
+use crate::two_qubit_decompose::{
+    TwoQubitBasisDecomposer, TwoQubitGateSequence, TwoQubitWeylDecomposition,
+};
+
+/// Single-parameter two-qubit `StandardGate`s whose interaction strength is continuously tunable
+/// on hardware with a variable coupler, rather than fixed at calibration time.
+fn variable_angle_interaction(gate: StandardGate) -> bool {
+    matches!(
+        gate,
+        StandardGate::RZXGate | StandardGate::RZZGate | StandardGate::RXXGate | StandardGate::RYYGate
+    )
+}
 
 fn get_decomposers_from_target(
     target: &Target,
     qubits: &[Qubit],
     fidelity: f64,
+    matrix: ArrayView2<Complex64>,
 ) -> PyResult<Vec<TwoQubitBasisDecomposer>> {
+    // The canonical Weyl-chamber coordinates of the unitary we're actually about to synthesize.
+    // `kak.a()` is the interaction strength a maximally-entangling basis gate would need to supply
+    // in a single application; a tunable-coupler gate (see `variable_angle_interaction`) can be
+    // dialed to exactly that strength instead of always being evaluated at its maximally-entangling
+    // angle, so its `TwoQubitBasisDecomposer` candidate is built around the angle this block's
+    // target unitary actually calls for.
+    let kak = TwoQubitWeylDecomposition::new_inner(matrix, None, None)?;
     let physical_qubits = smallvec![PhysicalQubit(qubits[0].0), PhysicalQubit(qubits[1].0)];
     let gate_names = match target.operation_names_for_qargs(Some(&physical_qubits)) {
         Ok(names) => names,
@@ -95,12 +118,26 @@ fn get_decomposers_from_target(
     available_kak_gate
         .iter()
         .filter_map(|(two_qubit_name, two_qubit_gate)| {
-            let matrix = two_qubit_gate.matrix();
-            matrix.map(|matrix| {
+            // `NormalOperation::matrix()` returns `None` for a gate calibrated with a free
+            // `Parameter` rather than a fixed angle -- the case for a continuously-tunable
+            // coupler's native interaction (`RZXGate`, `RZZGate`, `RXXGate`, `RYYGate`). Rather
+            // than dropping it, evaluate that interaction at the angle its single canonical KAK
+            // coordinate `kak.a()` actually calls for (`theta = 2 * kak.a()`, the inverse of the
+            // `a = theta / 2` relation each of these gates' generator has to the canonical
+            // `exp(i*a*XX)`-style interaction), so the decomposer this block gets is built around
+            // the interaction strength this specific unitary needs rather than always assuming the
+            // maximally-entangling case.
+            let gate_matrix = two_qubit_gate.matrix().or_else(|| match two_qubit_gate.operation.view() {
+                OperationRef::Standard(gate) if variable_angle_interaction(gate) => {
+                    gate.matrix(&[Param::Float(2. * kak.a())])
+                }
+                _ => None,
+            });
+            gate_matrix.map(|gate_matrix| {
                 euler_bases.iter().map(move |euler_basis| {
                     TwoQubitBasisDecomposer::new_inner(
                         two_qubit_name.to_string(),
-                        matrix.view(),
+                        gate_matrix.view(),
                         fidelity,
                         *euler_basis,
                         None,
@@ -112,50 +149,352 @@ fn get_decomposers_from_target(
         .collect()
 }
 
+/// The set of single-qubit Euler bases `qubit` natively supports on `target`, built the same way
+/// as the per-pair single-qubit basis list above, just keyed by one physical qubit instead of
+/// being derived from a 2q pair's first qubit.
+fn get_1q_target_basis_set(target: &Target, qubit: PhysicalQubit) -> EulerBasisSet {
+    let mut target_basis_set = EulerBasisSet::new();
+    let target_basis_list = target.operation_names_for_qargs(Some(&smallvec![qubit]));
+    match target_basis_list {
+        Ok(basis_list) => {
+            EULER_BASES
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, gates)| {
+                    if !gates.iter().all(|gate| basis_list.contains(gate)) {
+                        return None;
+                    }
+                    let basis = EULER_BASIS_NAMES[idx];
+                    Some(basis)
+                })
+                .for_each(|basis| target_basis_set.add_basis(basis));
+        }
+        Err(_) => target_basis_set.support_all(),
+    }
+    if target_basis_set.basis_supported(EulerBasis::U3)
+        && target_basis_set.basis_supported(EulerBasis::U321)
+    {
+        target_basis_set.remove(EulerBasis::U3);
+    }
+    if target_basis_set.basis_supported(EulerBasis::ZSX)
+        && target_basis_set.basis_supported(EulerBasis::ZSXX)
+    {
+        target_basis_set.remove(EulerBasis::ZSX);
+    }
+    target_basis_set
+}
+
+/// Compose a single-qubit run's per-gate matrices (in application order) into the run's overall
+/// unitary, the 1q analogue of [`compose_2q_matrix`]: with only one qubit involved there's no
+/// qubit-index tensor bookkeeping to do, just left-multiplication by each successive gate.
+fn compose_1q_matrix(
+    mats: impl Iterator<Item = PyResult<Array2<Complex64>>>,
+) -> PyResult<Array2<Complex64>> {
+    let mut acc: Option<Array2<Complex64>> = None;
+    for mat in mats {
+        let mat = mat?;
+        acc = Some(match acc {
+            Some(prev) => mat.dot(&prev),
+            None => mat,
+        });
+    }
+    Ok(acc.unwrap_or_else(|| Array2::eye(2)))
+}
+
+/// The synthesized replacement for a single collected run, whether it came from
+/// `collect_2q_runs` or `collect_1q_runs`; [`node_mapping`][HashMap] indices into a combined
+/// `Vec` of these so both kinds of run can be replaced in a single traversal over the DAG.
+enum RunResult {
+    TwoQubit((TwoQubitGateSequence, String), [Qubit; 2]),
+    OneQubit(OneQubitGateSequence, Qubit),
+}
+
+/// How [`score_sequence`] ranks a candidate sequence: purely by the product of per-gate
+/// `1 - target.get_error(...)` (`FidelityOnly`, the historical behavior), or with an added
+/// duration- and idle-decoherence-aware penalty (`FidelityAndDuration`) so a sequence that is
+/// shorter in time -- or that doesn't leave one qubit of a pair idling through the other's extra
+/// Euler layer -- isn't scored as a tie against a same-fidelity but slower alternative.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CostModel {
+    FidelityOnly,
+    FidelityAndDuration,
+}
+
+/// Score a sequence of `(gate_name, local_qubits)` pairs -- whichever of `target`'s own gate names
+/// the caller has already resolved each entry down to, whether that's a `StandardGate::name()` or
+/// a KAK decomposer's own two-qubit gate name for its basis-gate interaction -- so a synthesized
+/// replacement and the run it would replace can be scored through the exact same cost model
+/// (`cost_model`) for an apples-to-apples comparison.
 #[inline]
 fn score_sequence<'a>(
     target: &'a Target,
-    kak_gate_name: &str,
-    sequence: impl Iterator<Item = (Option<StandardGate>, SmallVec<[Qubit; 2]>)> + 'a,
+    sequence: impl Iterator<Item = (&'a str, SmallVec<[Qubit; 2]>)> + Clone + 'a,
+    cost_model: CostModel,
 ) -> f64 {
-    1. - sequence
-        .map(|(gate, local_qubits)| {
+    let fidelity = sequence
+        .clone()
+        .map(|(name, local_qubits)| {
             let qubits = local_qubits
                 .iter()
                 .map(|qubit| PhysicalQubit(qubit.0))
                 .collect::<Vec<_>>();
-            let name = match gate.as_ref() {
-                Some(g) => g.name(),
-                None => kak_gate_name,
-            };
             1. - target.get_error(name, qubits.as_slice()).unwrap_or(0.)
         })
-        .product::<f64>()
+        .product::<f64>();
+    if cost_model == CostModel::FidelityOnly {
+        return 1. - fidelity;
+    }
+    // The idle-decoherence term only means anything once more than one qubit is involved: a 1q
+    // sequence has no partner qubit to idle while it runs.
+    let block_qubits: Vec<PhysicalQubit> = sequence
+        .clone()
+        .flat_map(|(_, local_qubits)| {
+            local_qubits
+                .iter()
+                .map(|qubit| PhysicalQubit(qubit.0))
+                .collect::<Vec<_>>()
+        })
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if block_qubits.len() < 2 {
+        return 1. - fidelity;
+    }
+    let mut idle_time: HashMap<PhysicalQubit, f64> =
+        block_qubits.iter().map(|&qubit| (qubit, 0.)).collect();
+    for (name, local_qubits) in sequence {
+        let qubits = local_qubits
+            .iter()
+            .map(|qubit| PhysicalQubit(qubit.0))
+            .collect::<Vec<_>>();
+        let duration = target.get_duration(name, qubits.as_slice()).unwrap_or(0.);
+        if duration <= 0. {
+            continue;
+        }
+        for &qubit in &block_qubits {
+            if !qubits.contains(&qubit) {
+                *idle_time.get_mut(&qubit).unwrap() += duration;
+            }
+        }
+    }
+    let decoherence_survival: f64 = block_qubits
+        .iter()
+        .map(|qubit| match target.qubit_properties(*qubit).and_then(|p| p.t1) {
+            Some(t1) if t1 > 0. => (-idle_time[qubit] / t1).exp(),
+            _ => 1.,
+        })
+        .product();
+    1. - fidelity * decoherence_survival
 }
 
-type MappingIterItem = Option<((TwoQubitGateSequence, String), [Qubit; 2])>;
+type MappingIterItem = Option<RunResult>;
+
+/// Extend `dag.collect_2q_runs()` by merging adjacent runs on the same physical qubit pair when
+/// every node between them (in topological order) is either disjoint from that pair's qubits, or
+/// -- if it does share a qubit with the pair, e.g. a diagonal `RZGate` riding through a `CZGate` --
+/// provably commutes (per [`commute_matrices`]) with every operation of both runs. Either way the
+/// bridging node can be treated as transparent to the block, so the two runs can be merged into one
+/// contiguous block and resynthesized together; the bridging node keeps being emitted by the
+/// default (non-run) path further down, after the merged block (which is emitted at the position of
+/// its first node), since it's never added to `node_mapping`.
+fn merge_commuting_2q_runs(
+    dag: &DAGCircuit,
+    runs: Vec<Vec<NodeIndex>>,
+) -> PyResult<Vec<Vec<NodeIndex>>> {
+    if runs.len() < 2 {
+        return Ok(runs);
+    }
+    let topo_order: Vec<NodeIndex> = dag.topological_op_nodes()?.collect();
+    let topo_pos: HashMap<NodeIndex, usize> =
+        topo_order.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+    let run_qubits = |node_indices: &[NodeIndex]| -> Option<(Qubit, Qubit)> {
+        node_indices.iter().find_map(|node_index| {
+            let NodeType::Operation(ref inst) = dag.dag()[*node_index] else {
+                return None;
+            };
+            let qubits = dag.get_qargs(inst.qubits);
+            (qubits.len() == 2).then(|| {
+                if qubits[0] < qubits[1] {
+                    (qubits[0], qubits[1])
+                } else {
+                    (qubits[1], qubits[0])
+                }
+            })
+        })
+    };
+
+    // Whether `bridge` (some node sitting between two runs we'd like to merge) provably commutes
+    // with every operation already accumulated into `prev`, so it's safe to treat `prev` as though
+    // `bridge` weren't interleaved with it at all.
+    let commutes_with_run = |bridge: NodeIndex, prev: &[NodeIndex]| -> bool {
+        let NodeType::Operation(ref bridge_inst) = dag.dag()[bridge] else {
+            return true;
+        };
+        let bridge_qubits = dag.get_qargs(bridge_inst.qubits);
+        let bridge_matrix = bridge_inst.op.matrix(bridge_inst.params_view());
+        prev.iter().all(|&other| {
+            let NodeType::Operation(ref other_inst) = dag.dag()[other] else {
+                return true;
+            };
+            commute_matrices(
+                bridge_matrix.clone(),
+                bridge_qubits,
+                other_inst.op.matrix(other_inst.params_view()),
+                dag.get_qargs(other_inst.qubits),
+                DEFAULT_RTOL,
+                DEFAULT_ATOL,
+            )
+        })
+    };
+
+    let mut indexed_runs: Vec<(usize, Vec<NodeIndex>)> = runs
+        .into_iter()
+        .map(|run| {
+            let first_pos = run.iter().map(|n| topo_pos[n]).min().unwrap_or(0);
+            (first_pos, run)
+        })
+        .collect();
+    indexed_runs.sort_by_key(|(pos, _)| *pos);
+
+    let mut merged: Vec<Vec<NodeIndex>> = Vec::with_capacity(indexed_runs.len());
+    for (_, run) in indexed_runs {
+        let Some(qubits) = run_qubits(&run) else {
+            merged.push(run);
+            continue;
+        };
+        let can_merge = merged
+            .last()
+            .and_then(|prev| {
+                let prev_qubits = run_qubits(prev)?;
+                if prev_qubits != qubits {
+                    return None;
+                }
+                let prev_last = prev.iter().map(|n| topo_pos[n]).max()?;
+                let this_first = run.iter().map(|n| topo_pos[n]).min()?;
+                let all_bridgeable = (prev_last + 1..this_first).all(|pos| {
+                    let bridge = topo_order[pos];
+                    let NodeType::Operation(ref inst) = dag.dag()[bridge] else {
+                        return true;
+                    };
+                    let disjoint = dag
+                        .get_qargs(inst.qubits)
+                        .iter()
+                        .all(|q| *q != qubits.0 && *q != qubits.1);
+                    disjoint
+                        || (commutes_with_run(bridge, prev) && commutes_with_run(bridge, &run))
+                });
+                all_bridgeable.then_some(())
+            })
+            .is_some();
+
+        if can_merge {
+            let prev = merged.last_mut().unwrap();
+            prev.extend(run);
+            prev.sort_by_key(|n| topo_pos[n]);
+        } else {
+            merged.push(run);
+        }
+    }
+    Ok(merged)
+}
 
 /// This transpiler pass can only run in a context where we've translated the circuit gates (or
 /// where we know all gates have a matrix). If any gate identified in the run fails to have a
 /// matrix defined (either in rust or python) it will be skipped
+///
+/// This also folds in what used to be a separate `Optimize1qGatesDecomposition` pass: alongside
+/// the 2q runs from `collect_2q_runs`, it collects every `collect_1q_runs` chain, resynthesizes
+/// each through the same Euler-basis machinery `unitary_synthesis` uses for standalone 1q
+/// unitaries, and replaces it on the same traversal -- so a single Rust pass does the job of both.
+/// Adjacent 2q runs on the same qubit pair that are only separated by commutation-safe nodes are
+/// first merged by [`merge_commuting_2q_runs`] so the block handed to the decomposer is as large as
+/// possible.
 #[pyfunction]
+#[pyo3(signature=(dag, target, fidelity, duration_aware=false))]
 pub(crate) fn two_qubit_unitary_peephole_optimize(
     py: Python,
     dag: &DAGCircuit,
     target: &Target,
     fidelity: f64,
+    duration_aware: bool,
 ) -> PyResult<DAGCircuit> {
-    let runs: Vec<Vec<NodeIndex>> = dag.collect_2q_runs().unwrap();
+    let cost_model = if duration_aware {
+        CostModel::FidelityAndDuration
+    } else {
+        CostModel::FidelityOnly
+    };
+    let runs_2q: Vec<Vec<NodeIndex>> = dag.collect_2q_runs().unwrap();
+    let runs_2q = merge_commuting_2q_runs(dag, runs_2q)?;
+    let num_2q_runs = runs_2q.len();
+    let runs_1q: Vec<Vec<NodeIndex>> = dag.collect_1q_runs(py)?.unwrap_or_default();
+    // `run_index < num_2q_runs` is a 2q run from `runs_2q`; everything from there on is a 1q run
+    // from `runs_1q`, so both kinds share one combined index space and one `node_mapping`.
+    let runs: Vec<Vec<NodeIndex>> = runs_2q.into_iter().chain(runs_1q).collect();
     let node_mapping: HashMap<NodeIndex, usize> =
         HashMap::with_capacity(runs.iter().map(|run| run.len()).sum());
     let locked_node_mapping = Mutex::new(node_mapping);
 
-    // Build a vec of all the best synthesized two qubit gate sequences from the collected runs.
-    // This is done in parallel
+    // Build a vec of all the best synthesized gate sequences from the collected runs, 2q and 1q
+    // alike. This is done in parallel.
     let run_mapping: PyResult<Vec<MappingIterItem>> = runs
         .par_iter()
         .enumerate()
         .map(|(run_index, node_indices)| {
+            if run_index >= num_2q_runs {
+                let qubit = {
+                    let NodeType::Operation(ref inst) = dag.dag()[node_indices[0]] else {
+                        unreachable!("All run nodes will be ops")
+                    };
+                    dag.get_qargs(inst.qubits)[0]
+                };
+                let physical_qubit = PhysicalQubit(qubit.0);
+                let matrix = compose_1q_matrix(node_indices.iter().map(|node_index| {
+                    let NodeType::Operation(ref inst) = dag.dag()[*node_index] else {
+                        unreachable!("All run nodes will be ops")
+                    };
+                    Ok(inst.op.matrix(inst.params_view()).unwrap())
+                }))?;
+                let target_basis_set = get_1q_target_basis_set(target, physical_qubit);
+                let Some(sequence) = unitary_to_gate_sequence_inner(
+                    matrix.view(),
+                    &target_basis_set,
+                    physical_qubit.0 as usize,
+                    None,
+                    true,
+                    None,
+                ) else {
+                    return Ok(None);
+                };
+                let original_score = score_sequence(
+                    target,
+                    node_indices.iter().map(|node_index| {
+                        let NodeType::Operation(ref inst) = dag.dag()[*node_index] else {
+                            unreachable!("All run nodes will be ops")
+                        };
+                        (inst.op.name(), smallvec![qubit])
+                    }),
+                    cost_model,
+                );
+                let new_score = score_sequence(
+                    target,
+                    sequence
+                        .gates
+                        .iter()
+                        .map(|(gate, _params)| (gate.name(), smallvec![qubit])),
+                    cost_model,
+                );
+                if new_score > original_score
+                    || (new_score == original_score && sequence.gates.len() >= node_indices.len())
+                {
+                    return Ok(None);
+                }
+                let mut node_mapping = locked_node_mapping.lock().unwrap();
+                for node in node_indices {
+                    node_mapping.insert(*node, run_index);
+                }
+                return Ok(Some(RunResult::OneQubit(sequence, qubit)));
+            }
             let block_qubit_map = node_indices
                 .iter()
                 .filter_map(|node_index| {
@@ -191,7 +530,8 @@ pub(crate) fn two_qubit_unitary_peephole_optimize(
                 Ok((op_matrix, qubit_indices))
             }))?;
 
-            let decomposers = get_decomposers_from_target(target, &block_qubit_map, fidelity)?;
+            let decomposers =
+                get_decomposers_from_target(target, &block_qubit_map, fidelity, matrix.view())?;
             let mut decomposer_scores: Vec<Option<f64>> = vec![None; decomposers.len()];
 
             let order_sequence =
@@ -203,16 +543,18 @@ pub(crate) fn two_qubit_unitary_peephole_optimize(
                             let score: f64 =
                                 score_sequence(
                                     target,
-                                    sequence_a.1.as_str(),
                                     sequence_a.0.gates.iter().map(
                                         |(gate, _params, local_qubits)| {
                                             let qubits = local_qubits
                                                 .iter()
                                                 .map(|qubit| block_qubit_map[*qubit as usize])
                                                 .collect();
-                                            (*gate, qubits)
+                                            let name =
+                                                gate.map_or(sequence_a.1.as_str(), |g| g.name());
+                                            (name, qubits)
                                         },
                                     ),
+                                    cost_model,
                                 );
                             decomposer_scores[*index_a] = Some(score);
                             score
@@ -225,16 +567,18 @@ pub(crate) fn two_qubit_unitary_peephole_optimize(
                             let score: f64 =
                                 score_sequence(
                                     target,
-                                    sequence_b.1.as_str(),
                                     sequence_b.0.gates.iter().map(
                                         |(gate, _params, local_qubits)| {
                                             let qubits = local_qubits
                                                 .iter()
                                                 .map(|qubit| block_qubit_map[*qubit as usize])
                                                 .collect();
-                                            (*gate, qubits)
+                                            let name =
+                                                gate.map_or(sequence_b.1.as_str(), |g| g.name());
+                                            (name, qubits)
                                         },
                                     ),
+                                    cost_model,
                                 );
                             decomposer_scores[*index_b] = Some(score);
                             score
@@ -257,25 +601,19 @@ pub(crate) fn two_qubit_unitary_peephole_optimize(
                 .min_by(order_sequence)
                 .unwrap()
                 .1;
-            let original_score = 1.
-                - node_indices
-                    .iter()
-                    .map(|node_index| {
-                        let NodeType::Operation(ref inst) = dag.dag()[*node_index] else {
-                            unreachable!("All run nodes will be ops")
-                        };
-                        let qubits = dag
-                            .get_qargs(inst.qubits)
-                            .iter()
-                            .map(|qubit| PhysicalQubit(qubit.0))
-                            .collect::<Vec<_>>();
-                        let name = inst.op.name();
-                        1. - target.get_error(name, qubits.as_slice()).unwrap_or(0.)
-                    })
-                    .product::<f64>();
+            let original_score = score_sequence(
+                target,
+                node_indices.iter().map(|node_index| {
+                    let NodeType::Operation(ref inst) = dag.dag()[*node_index] else {
+                        unreachable!("All run nodes will be ops")
+                    };
+                    let qubits = dag.get_qargs(inst.qubits).iter().copied().collect();
+                    (inst.op.name(), qubits)
+                }),
+                cost_model,
+            );
             let new_score = score_sequence(
                 target,
-                sequence.1.as_str(),
                 sequence
                     .0
                     .gates
@@ -285,8 +623,10 @@ pub(crate) fn two_qubit_unitary_peephole_optimize(
                             .iter()
                             .map(|qubit| block_qubit_map[*qubit as usize])
                             .collect();
-                        (*gate, qubits)
+                        let name = gate.map_or(sequence.1.as_str(), |g| g.name());
+                        (name, qubits)
                     }),
+                cost_model,
             );
 
             if new_score > original_score
@@ -317,7 +657,7 @@ pub(crate) fn two_qubit_unitary_peephole_optimize(
             for node in node_indices {
                 node_mapping.insert(*node, run_index);
             }
-            Ok(Some((sequence, block_qubit_map)))
+            Ok(Some(RunResult::TwoQubit((sequence, block_qubit_map))))
         })
         .collect();
 
@@ -332,79 +672,117 @@ pub(crate) fn two_qubit_unitary_peephole_optimize(
                 if processed_runs.contains(run_index) {
                     continue;
                 }
-                if run_mapping[*run_index].is_none() {
-                    let NodeType::Operation(ref instr) = dag.dag()[node] else {
-                        unreachable!("Must be an op node")
-                    };
-                    out_dag.push_back(py, instr.clone())?;
-                    continue;
-                }
-                let (sequence, qubit_map) = &run_mapping[*run_index].as_ref().unwrap();
-                for (gate, params, local_qubits) in &sequence.0.gates {
-                    let qubits: Vec<Qubit> = local_qubits
-                        .iter()
-                        .map(|index| qubit_map[*index as usize])
-                        .collect();
-                    let out_params = if params.is_empty() {
-                        None
-                    } else {
-                        Some(params.iter().map(|val| Param::Float(*val)).collect())
-                    };
-                    match gate {
-                        Some(gate) => {
-                            #[cfg(feature = "cache_pygates")]
-                            {
-                                out_dag.apply_operation_back(
-                                    py,
-                                    PackedOperation::from_standard(*gate),
-                                    qubits.as_slice(),
-                                    &[],
-                                    out_params,
-                                    ExtraInstructionAttributes::default(),
-                                    None,
-                                )
-                            }
-                            #[cfg(not(feature = "cache_pygates"))]
-                            {
-                                out_dag.apply_operation_back(
-                                    py,
-                                    PackedOperation::from_standard(*gate),
-                                    qubits.as_slice(),
-                                    &[],
-                                    out_params,
-                                    ExtraInstructionAttributes::default(),
-                                )
-                            }
+                match &run_mapping[*run_index] {
+                    None => {
+                        let NodeType::Operation(ref instr) = dag.dag()[node] else {
+                            unreachable!("Must be an op node")
+                        };
+                        out_dag.push_back(py, instr.clone())?;
+                    }
+                    Some(RunResult::TwoQubit((sequence, qubit_map))) => {
+                        for (gate, params, local_qubits) in &sequence.0.gates {
+                            let qubits: Vec<Qubit> = local_qubits
+                                .iter()
+                                .map(|index| qubit_map[*index as usize])
+                                .collect();
+                            let out_params = if params.is_empty() {
+                                None
+                            } else {
+                                Some(params.iter().map(|val| Param::Float(*val)).collect())
+                            };
+                            match gate {
+                                Some(gate) => {
+                                    #[cfg(feature = "cache_pygates")]
+                                    {
+                                        out_dag.apply_operation_back(
+                                            py,
+                                            PackedOperation::from_standard(*gate),
+                                            qubits.as_slice(),
+                                            &[],
+                                            out_params,
+                                            ExtraInstructionAttributes::default(),
+                                            None,
+                                        )
+                                    }
+                                    #[cfg(not(feature = "cache_pygates"))]
+                                    {
+                                        out_dag.apply_operation_back(
+                                            py,
+                                            PackedOperation::from_standard(*gate),
+                                            qubits.as_slice(),
+                                            &[],
+                                            out_params,
+                                            ExtraInstructionAttributes::default(),
+                                        )
+                                    }
+                                }
+                                None => {
+                                    let gate =
+                                        target.operation_from_name(sequence.1.as_str()).unwrap();
+                                    #[cfg(feature = "cache_pygates")]
+                                    {
+                                        out_dag.apply_operation_back(
+                                            py,
+                                            gate.operation.clone(),
+                                            qubits.as_slice(),
+                                            &[],
+                                            out_params,
+                                            ExtraInstructionAttributes::default(),
+                                            None,
+                                        )
+                                    }
+                                    #[cfg(not(feature = "cache_pygates"))]
+                                    {
+                                        out_dag.apply_operation_back(
+                                            py,
+                                            gate.operation.clone(),
+                                            qubits.as_slice(),
+                                            &[],
+                                            out_params,
+                                            ExtraInstructionAttributes::default(),
+                                        )
+                                    }
+                                }
+                            }?;
                         }
-                        None => {
-                            let gate = target.operation_from_name(sequence.1.as_str()).unwrap();
-                            #[cfg(feature = "cache_pygates")]
-                            {
-                                out_dag.apply_operation_back(
-                                    py,
-                                    gate.operation.clone(),
-                                    qubits.as_slice(),
-                                    &[],
-                                    out_params,
-                                    ExtraInstructionAttributes::default(),
-                                    None,
-                                )
-                            }
-                            #[cfg(not(feature = "cache_pygates"))]
+                        out_dag.add_global_phase(py, &Param::Float(sequence.0.global_phase))?;
+                    }
+                    Some(RunResult::OneQubit(sequence, qubit)) => {
+                        for (gate, params) in &sequence.gates {
+                            let out_params = if params.is_empty() {
+                                None
+                            } else {
+                                Some(params.iter().map(|val| Param::Float(*val)).collect())
+                            };
                             {
-                                out_dag.apply_operation_back(
-                                    py,
-                                    gate.operation.clone(),
-                                    qubits.as_slice(),
-                                    &[],
-                                    out_params,
-                                    ExtraInstructionAttributes::default(),
-                                )
-                            }
+                                #[cfg(feature = "cache_pygates")]
+                                {
+                                    out_dag.apply_operation_back(
+                                        py,
+                                        PackedOperation::from_standard(*gate),
+                                        &[*qubit],
+                                        &[],
+                                        out_params,
+                                        ExtraInstructionAttributes::default(),
+                                        None,
+                                    )
+                                }
+                                #[cfg(not(feature = "cache_pygates"))]
+                                {
+                                    out_dag.apply_operation_back(
+                                        py,
+                                        PackedOperation::from_standard(*gate),
+                                        &[*qubit],
+                                        &[],
+                                        out_params,
+                                        ExtraInstructionAttributes::default(),
+                                    )
+                                }
+                            }?;
                         }
-                    }?;
+                        out_dag.add_global_phase(py, &Param::Float(sequence.global_phase))?;
+                    }
                 }
-                out_dag.add_global_phase(py, &Param::Float(sequence.0.global_phase))?;
                 processed_runs.insert(*run_index);
             }
             None => {
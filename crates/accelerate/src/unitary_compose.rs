@@ -10,9 +10,16 @@
 // copyright notice, and modified files need to carry a notice indicating
 // that they have been altered from the originals.
 
+use hashbrown::HashSet;
 use ndarray::{Array, Array2, Ix2, IxDyn};
 use ndarray_einsum_beta::*;
 use num_complex::{Complex, Complex64};
+use numpy::PyReadonlyArray2;
+use pyo3::prelude::*;
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+
+use crate::getenv_use_multiple_threads;
 
 static LOWERCASE: [u8; 26] = [
     b'a', b'b', b'c', b'd', b'e', b'f', b'g', b'h', b'i', b'j', b'k', b'l', b'm', b'n', b'o', b'p',
@@ -27,8 +34,21 @@ static _UPPERCASE: [u8; 26] = [
 // Compose the operators given by `gate_unitary` and `overall_unitary`, i.e. apply one to the other
 // as specified by the involved qubits given in `qubits` and the `front` parameter
 pub fn compose(gate_unitary: Array<Complex<f64>, Ix2>, overall_unitary: Array<Complex<f64>, Ix2>, qubits: &[usize], front: bool) -> Array2<Complex<f64>> {
-    let gate_qubits = gate_unitary.shape()[0].ilog2() as usize;
+    let num_wires = overall_unitary.shape()[0].ilog2() as usize;
+    compose_wires(gate_unitary, overall_unitary, qubits, front, &vec![2usize; num_wires])
+}
 
+/// Generalized form of [compose] for registers of qudits of arbitrary (and possibly mixed)
+/// dimension: `dims[i]` is the dimension of wire `i` of `overall_unitary`, in the same
+/// qubit-0-is-rightmost convention as `qubits`. `gate_unitary`'s own wires are `dims` restricted
+/// to the positions named by `qubits`, so its row count must equal their product.
+pub fn compose_wires(
+    gate_unitary: Array<Complex<f64>, Ix2>,
+    overall_unitary: Array<Complex<f64>, Ix2>,
+    qubits: &[usize],
+    front: bool,
+    dims: &[usize],
+) -> Array2<Complex<f64>> {
     // Full composition of operators
     if qubits.len() == 0 {
         if front {
@@ -39,35 +59,51 @@ pub fn compose(gate_unitary: Array<Complex<f64>, Ix2>, overall_unitary: Array<Co
         }
     }
     // Compose with other on subsystem
-    let num_indices = gate_qubits;
-    let shift = if front {gate_qubits} else {0usize};
-    let right_mul = front;
+    let num_rows = overall_unitary.shape()[0];
+    let gate_dims: Vec<usize> = qubits.iter().map(|&q| dims[q]).collect();
+    let tensor = wire_shaped(gate_unitary, &gate_dims);
+    let mat = wire_shaped(overall_unitary, dims);
 
-    //Reshape current matrix
+    fold_subsystem(tensor, mat, qubits, gate_dims.len(), front).as_standard_layout().
+        into_shape((num_rows, num_rows)).unwrap().
+        into_dimensionality::<ndarray::Ix2>().unwrap().to_owned()
+}
+
+// Fold `tensor` (a gate's unitary, already reshaped to per-wire form) into `mat` (the overall
+// unitary, likewise reshaped) on the given `qubits`, leaving the result in per-wire form rather
+// than reshaping back down to a matrix. This is the tensor-contraction step `compose_wires` itself
+// wraps; factored out so a caller folding many gates in sequence (see `compose_unitaries` below)
+// can keep the accumulator in tensor form across the whole sequence instead of paying the
+// `into_shape` round-trip on every gate.
+fn fold_subsystem(
+    tensor: Array<Complex64, IxDyn>,
+    mat: Array<Complex64, IxDyn>,
+    qubits: &[usize],
+    gate_qubits: usize,
+    front: bool,
+) -> Array<Complex64, IxDyn> {
     //Note that we must reverse the subsystem dimension order as
     //qubit 0 corresponds to the right-most position in the tensor
     //product, which is the last tensor wire index.
-    let tensor = per_qubit_shaped(gate_unitary.clone());
-    let mat = per_qubit_shaped(overall_unitary.clone());
-    let indices = qubits.iter().map(|q| num_indices-1-q).collect::<Vec<usize>>();
-    let num_rows = usize::pow(2, num_indices as u32);
-
-    _einsum_matmul(tensor, mat, indices, shift, right_mul).as_standard_layout().
-        into_shape((num_rows, num_rows)).unwrap().
-        into_dimensionality::<ndarray::Ix2>().unwrap().to_owned()
+    debug_assert_eq!(tensor.ndim(), 2 * gate_qubits);
+    // `_einsum_matmul` contracts its second operand (the subsystem operator) into its first (the
+    // full-register operator), so `mat` goes first and the indices count over `mat`'s wires.
+    let num_wires = mat.ndim() / 2;
+    let shift = if front { num_wires } else { 0usize };
+    let indices = qubits.iter().map(|q| num_wires - 1 - q).collect::<Vec<usize>>();
+    _einsum_matmul(mat, tensor, indices, shift, front)
 }
 
-// Reshape an input matrix to (2, 2, ..., 2) depending on its dimensionality
-fn per_qubit_shaped(array: Array<Complex<f64>, Ix2>) -> Array<Complex64, IxDyn> {
-    let overall_shape = (0..array.shape()[0].ilog2() as usize)
-        .map(|_| [2, 2])
-        .flatten()
-        .collect::<Vec<usize>>();
-    array
-        .into_shape(
-            overall_shape
-        )
-        .unwrap().into_owned()
+// Reshape an input matrix into its per-wire tensor form: each wire `i` contributes the pair of
+// axes of size `dims[i]` (one for the matrix's row half, one for its column half), ordered so
+// that wire 0 is the last (right-most) axis of each half, matching the usual qubit-0-is-
+// right-most tensor product convention. `dims` may be any fixed or mixed set of wire
+// dimensions, e.g. `[2, 2, 2]` for a 3-qubit operator or `[3, 3]` for two qutrits.
+fn wire_shaped(array: Array<Complex<f64>, Ix2>, dims: &[usize]) -> Array<Complex64, IxDyn> {
+    let wire_axes: Vec<usize> = dims.iter().rev().copied().collect();
+    let mut shape = wire_axes.clone();
+    shape.extend(wire_axes);
+    array.into_shape(shape).unwrap().into_owned()
 }
 
 // Determine einsum strings for perform a matrix multiplication on the input matrices
@@ -124,5 +160,88 @@ fn _einsum_matmul_index(qubits: &[usize], num_qubits: usize) -> String {
     )
 }
 
+/// Embed `gate_unitary`, acting on `qubits` of a register whose wires have the given `dims`,
+/// into an otherwise untouched identity on the full register, in per-wire tensor form.
+pub(crate) fn embed(
+    gate_unitary: &Array2<Complex64>,
+    qubits: &[usize],
+    dims: &[usize],
+) -> Array<Complex64, IxDyn> {
+    let num_rows: usize = dims.iter().product();
+    let gate_dims: Vec<usize> = qubits.iter().map(|&q| dims[q]).collect();
+    let identity = wire_shaped(Array2::eye(num_rows), dims);
+    let tensor = wire_shaped(gate_unitary.clone(), &gate_dims);
+    fold_subsystem(tensor, identity, qubits, gate_dims.len(), true)
+}
+
+/// Build the full `2^num_qubits x 2^num_qubits` unitary of an ordered sequence of `(gate_unitary,
+/// qubits)` pairs applied to a `num_qubits`-qubit register, by repeatedly folding each gate into
+/// an identity accumulator with the same tensor contraction `compose` uses on a single pair of
+/// operators. The accumulator is kept in its per-qubit `(2, 2, ..., 2)` shape for the whole
+/// sequence and only reshaped down to a matrix once at the end, avoiding the `into_shape`
+/// round-trip `compose` alone would pay on every gate.
+///
+/// Any maximal run of consecutive gates acting on pairwise disjoint qubits commutes, so such a
+/// run is embedded into the full register in parallel via `rayon` before being folded into the
+/// accumulator one at a time.
+#[pyfunction]
+pub fn compose_unitaries(
+    py: Python,
+    num_qubits: u32,
+    gates: Vec<(PyReadonlyArray2<Complex64>, Vec<usize>)>,
+) -> Array2<Complex64> {
+    let num_qubits = num_qubits as usize;
+    let dims = vec![2usize; num_qubits];
+    let num_rows = 1usize << num_qubits;
+    let run_in_parallel = getenv_use_multiple_threads();
+    let gates: Vec<(Array2<Complex64>, Vec<usize>)> = gates
+        .into_iter()
+        .map(|(unitary, qubits)| (unitary.as_array().to_owned(), qubits))
+        .collect();
+
+    let mut accumulator = wire_shaped(Array2::eye(num_rows), &dims);
+    let all_qubits: Vec<usize> = (0..num_qubits).collect();
+    let mut start = 0;
+    while start < gates.len() {
+        let mut end = start + 1;
+        let mut used: HashSet<usize> = gates[start].1.iter().copied().collect();
+        while end < gates.len() {
+            let next_qubits = &gates[end].1;
+            if next_qubits.iter().any(|q| used.contains(q)) {
+                break;
+            }
+            used.extend(next_qubits.iter().copied());
+            end += 1;
+        }
+        let wave = &gates[start..end];
+        let embedded: Vec<Array<Complex64, IxDyn>> = if run_in_parallel && wave.len() > 1 {
+            py.allow_threads(|| {
+                wave.par_iter()
+                    .map(|(unitary, qubits)| embed(unitary, qubits, &dims))
+                    .collect()
+            })
+        } else {
+            wave.iter()
+                .map(|(unitary, qubits)| embed(unitary, qubits, &dims))
+                .collect()
+        };
+        for gate_tensor in embedded {
+            accumulator = fold_subsystem(gate_tensor, accumulator, &all_qubits, num_qubits, false);
+        }
+        start = end;
+    }
+    accumulator
+        .as_standard_layout()
+        .into_shape((num_rows, num_rows))
+        .unwrap()
+        .into_dimensionality::<Ix2>()
+        .unwrap()
+        .to_owned()
+}
+
+pub fn unitary_compose_mod(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_wrapped(wrap_pyfunction!(compose_unitaries))?;
+    Ok(())
+}
 
 
@@ -14,15 +14,17 @@
 #[cfg(feature = "cache_pygates")]
 use std::cell::OnceCell;
 use std::f64::consts::PI;
+use std::sync::Mutex;
 
 use approx::relative_eq;
 use core::panic;
 use hashbrown::{HashMap, HashSet};
 use indexmap::IndexMap;
 use ndarray::prelude::*;
-use num_complex::{Complex, Complex64};
+use num_complex::Complex64;
 use numpy::IntoPyArray;
 use qiskit_circuit::circuit_instruction::ExtraInstructionAttributes;
+use rayon::prelude::*;
 use smallvec::{smallvec, SmallVec};
 
 use pyo3::intern;
@@ -41,12 +43,14 @@ use qiskit_circuit::packed_instruction::{PackedInstruction, PackedOperation};
 use qiskit_circuit::Qubit;
 
 use crate::euler_one_qubit_decomposer::{
-    unitary_to_gate_sequence_inner, EulerBasis, EulerBasisSet, EULER_BASES, EULER_BASIS_NAMES,
+    unitary_to_gate_sequence_inner, EulerBasis, EulerBasisSet, OneQubitGateErrorMap, EULER_BASES,
+    EULER_BASIS_NAMES,
 };
 use crate::nlayout::PhysicalQubit;
 use crate::target_transpiler::{NormalOperation, Target};
 use crate::two_qubit_decompose::{
-    TwoQubitBasisDecomposer, TwoQubitGateSequence, TwoQubitWeylDecomposition,
+    TwoQubitBasisDecomposer, TwoQubitDecomposeUpToDiagonal, TwoQubitGateSequence,
+    TwoQubitWeylDecomposition,
 };
 use crate::QiskitError;
 
@@ -57,8 +61,12 @@ const PI4: f64 = PI / 4.;
 enum DecomposerType {
     TwoQubitBasisDecomposer(Box<TwoQubitBasisDecomposer>),
     XXDecomposer(PyObject),
+    /// Synthesizes up to a leftover diagonal rather than the exact target unitary; see
+    /// [`get_2q_up_to_diagonal_decomposer`] and [`synth_su4_up_to_diagonal_sequence`].
+    UpToDiagonal(Box<TwoQubitDecomposeUpToDiagonal>),
 }
 
+#[derive(Clone)]
 struct DecomposerElement {
     decomposer: DecomposerType,
     gate: NormalOperation,
@@ -70,6 +78,182 @@ struct TwoQubitUnitarySequence {
     decomp_gate: NormalOperation,
 }
 
+/// The outcome of synthesizing a single two-qubit `unitary` node, deferred from
+/// [`run_2q_unitary_synthesis`] so it can be produced on a rayon worker thread and then applied
+/// to `out_dag` back on the thread doing the (necessarily serial, topological-order) DAG mutation.
+enum UnitarySynthOutcome {
+    Sequence(TwoQubitUnitarySequence),
+    Dag(DAGCircuit),
+    /// The two qubits don't interact at all: `unitary` is a tensor product of two 1q operators,
+    /// synthesized independently (see [`run_2q_unitary_synthesis`]) with no entangling gate spent
+    /// on it at all.
+    Separable {
+        qubit0: Vec<(StandardGate, SmallVec<[Param; 3]>)>,
+        qubit1: Vec<(StandardGate, SmallVec<[Param; 3]>)>,
+        global_phase: f64,
+    },
+    /// No decomposer improved on the original `unitary` node; keep it as-is.
+    Original,
+}
+
+/// A single `unitary` node pulled out of the main loop's topological walk, along with just
+/// enough information to synthesize it with no further access to `dag`. Built up serially (one
+/// pass over `dag.topological_op_nodes()`), then synthesized in parallel; see
+/// [`py_run_main_loop`].
+enum NodeWork {
+    OneQubit {
+        qubit: Qubit,
+        unitary: Array2<Complex64>,
+    },
+    TwoQubit {
+        ref_qubits: [PhysicalQubit; 2],
+        unitary: Array2<Complex64>,
+    },
+    ThreeQubitPlus {
+        unitary: Array2<Complex64>,
+        out_qargs: Vec<Qubit>,
+    },
+}
+
+/// The synthesis result for one [`NodeWork`] item, still unapplied.
+enum NodeOutcome {
+    OneQubit(Option<(Vec<(StandardGate, SmallVec<[Param; 3]>)>, f64)>),
+    TwoQubit(PyResult<UnitarySynthOutcome>),
+    ThreeQubitPlus(PyResult<ThreeQubitPlusOutcome>),
+}
+
+type QsdGate = (StandardGate, SmallVec<[Param; 3]>, SmallVec<[Qubit; 2]>);
+
+/// The outcome of synthesizing a single `unitary` node on 3 or more qubits: either the native
+/// Rust decomposition from [`quantum_shannon_decompose`], expressed over local qubits
+/// `0..num_qubits`, or a `DAGCircuit` from the Python `QS_DECOMPOSITION` fallback (see
+/// [`quantum_shannon_decompose`]'s docs for when that fallback is still needed).
+enum ThreeQubitPlusOutcome {
+    Native(Vec<QsdGate>, f64),
+    Fallback(DAGCircuit),
+}
+
+#[derive(Clone, Copy)]
+enum MultiplexAxis {
+    Ry,
+    Rz,
+}
+
+/// Expand a rotation uniformly controlled on `controls` (MSB first) and applied to `target` into
+/// `2^controls.len()` single-axis rotations interleaved with CX gates, via the standard
+/// recursive "quantum multiplexor" construction: halve `angles` into per-branch sum/difference
+/// pairs, recurse on the tail of `controls` for each half, and sandwich the second half between
+/// two CXs on `controls[0]`. Each level emits its two CXs and recurses twice, so `k` controls
+/// produce `2^(k+1) - 2` CX gates; no CX pairs are cancelled or mirrored away.
+fn multiplexed_rotation(
+    angles: &[f64],
+    axis: MultiplexAxis,
+    controls: &[Qubit],
+    target: Qubit,
+    gates: &mut Vec<QsdGate>,
+) {
+    let gate = match axis {
+        MultiplexAxis::Ry => StandardGate::RYGate,
+        MultiplexAxis::Rz => StandardGate::RZGate,
+    };
+    if controls.is_empty() {
+        gates.push((gate, smallvec![Param::Float(angles[0])], smallvec![target]));
+        return;
+    }
+    let half = angles.len() / 2;
+    let sum_angles: Vec<f64> = angles[..half]
+        .iter()
+        .zip(&angles[half..])
+        .map(|(a, b)| (a + b) / 2.0)
+        .collect();
+    let diff_angles: Vec<f64> = angles[..half]
+        .iter()
+        .zip(&angles[half..])
+        .map(|(a, b)| (a - b) / 2.0)
+        .collect();
+    multiplexed_rotation(&sum_angles, axis, &controls[1..], target, gates);
+    gates.push((StandardGate::CXGate, smallvec![], smallvec![controls[0], target]));
+    multiplexed_rotation(&diff_angles, axis, &controls[1..], target, gates);
+    gates.push((StandardGate::CXGate, smallvec![], smallvec![controls[0], target]));
+}
+
+/// Synthesize a `2^n x 2^n` unitary over `qubits` into `StandardGate`s natively, for `n == 1` and
+/// `n == 2` only. No cosine-sine (quantum Shannon) split is performed, and
+/// [`multiplexed_rotation`] is not called from here.
+///
+/// Bottoms out at `n == 1` (the existing Euler decomposer) and `n == 2` (the existing CX-basis
+/// `TwoQubitBasisDecomposer`, used directly rather than through a `Target` since this is
+/// basis-fixed general-purpose synthesis, not target-aware routing). For `n >= 3` the CSD split
+/// needs an eigendecomposition of a general `2^(n-1) x 2^(n-1)` unitary, and this workspace has
+/// no linear-algebra crate (no `ndarray-linalg`/`faer`/`nalgebra`) wired in to provide one --
+/// hand-rolling a complex eigensolver with no way to verify it here risks silently wrong circuits,
+/// which is worse than the existing fallback. So `n >= 3` returns `None`, and the caller
+/// falls back to the Python `QS_DECOMPOSITION` helper for the actual decomposition.
+fn quantum_shannon_decompose(
+    unitary: ArrayView2<Complex64>,
+    qubits: &[Qubit],
+) -> Option<(Vec<QsdGate>, f64)> {
+    match qubits.len() {
+        1 => {
+            let mut target_basis_set = EulerBasisSet::new();
+            target_basis_set.support_all();
+            let sequence = unitary_to_gate_sequence_inner(
+                unitary,
+                &target_basis_set,
+                qubits[0].0 as usize,
+                None,
+                true,
+                None,
+            )?;
+            let gates = sequence
+                .gates
+                .into_iter()
+                .map(|(gate, params)| {
+                    let params: SmallVec<[Param; 3]> =
+                        params.iter().map(|p| Param::Float(*p)).collect();
+                    (gate, params, smallvec![qubits[0]])
+                })
+                .collect();
+            Some((gates, sequence.global_phase))
+        }
+        2 => {
+            let decomposer = TwoQubitBasisDecomposer::new_inner(
+                StandardGate::CXGate.name().to_string(),
+                StandardGate::CXGate.matrix(&[])?.view(),
+                1.0,
+                "ZSX",
+                None,
+            )
+            .ok()?;
+            let synth = decomposer.call_inner(unitary, None, false, None).ok()?;
+            let gates = synth
+                .gates
+                .into_iter()
+                .map(|(gate, params, local_qubits)| {
+                    let gate = gate.unwrap_or(StandardGate::CXGate);
+                    let mapped_qubits: SmallVec<[Qubit; 2]> =
+                        local_qubits.iter().map(|q| qubits[*q as usize]).collect();
+                    let params: SmallVec<[Param; 3]> =
+                        params.iter().map(|p| Param::Float(*p)).collect();
+                    (gate, params, mapped_qubits)
+                })
+                .collect();
+            Some((gates, synth.global_phase))
+        }
+        _ => None,
+    }
+}
+
+/// Pull `coupling_edges` out of its Python list up front so the rayon workers synthesizing 2q
+/// unitaries never need to touch a `Bound<PyList>` (which is tied to holding the GIL) from
+/// outside the thread that currently holds it.
+fn coupling_edge_set(coupling_edges: &Bound<'_, PyList>) -> HashSet<(usize, usize)> {
+    coupling_edges
+        .iter()
+        .filter_map(|item| item.extract::<(usize, usize)>().ok())
+        .collect()
+}
+
 // Used in get_2q_decomposers. If the found 2q basis is a subset of GOODBYE_SET,
 // then we know TwoQubitBasisDecomposer is an ideal decomposition and there is
 // no need to bother trying the XXDecomposer.
@@ -107,6 +291,132 @@ fn get_target_basis_set(target: &Target, qubit: PhysicalQubit) -> EulerBasisSet
     target_basis_set
 }
 
+/// Read `qubit`'s 1q basis-gate error rates off of `target`, the same way
+/// [`get_2q_decomposers_from_target`] reads `props.error` for 2q gates, so the Euler decomposer can
+/// pick the cheapest-error basis rather than just the shortest one when several are available.
+/// Basis gates the target reports no characterized error for fall back to the decomposer's default
+/// (gate-count-only) tie-breaking.
+fn build_one_qubit_error_map(target: &Target, qubit: PhysicalQubit) -> OneQubitGateErrorMap {
+    // `unitary_to_gate_sequence_inner` looks up this qubit's rates by the same `qubit_index` we
+    // pass it at the call site (`qubit.0 as usize`), so pad the map out to that index rather than
+    // always inserting at 0.
+    let mut error_map = OneQubitGateErrorMap::new(qubit.0 as usize + 1);
+    for _ in 0..qubit.0 {
+        error_map.add_qubit(HashMap::new());
+    }
+    let mut rates = HashMap::new();
+    if let Ok(basis_list) = target.operation_names_for_qargs(Some(&smallvec![qubit])) {
+        for gate in basis_list {
+            if target.qargs_for_operation_name(gate).is_err() {
+                continue;
+            }
+            let error = match &target[gate].get(Some(&smallvec![qubit])) {
+                Some(Some(props)) => props.error,
+                _ => None,
+            };
+            if let Some(error) = error {
+                rates.insert(gate.to_string(), error);
+            }
+        }
+    }
+    error_map.add_qubit(rates);
+    error_map
+}
+
+/// Below this magnitude, a [`TwoQubitWeylDecomposition`]'s interaction coefficients (a, b, c) are
+/// treated as exactly zero, i.e. `unitary` doesn't entangle its two qubits at all and can be
+/// synthesized as two independent 1q operators; see [`run_2q_unitary_synthesis`].
+const SEPARABLE_TOL: f64 = 1e-12;
+
+/// Synthesize a single-qubit factor of a separable 2q unitary (one of a
+/// [`TwoQubitWeylDecomposition`]'s `K1l`/`K1r`) the same way the main loop synthesizes any other
+/// 1q `unitary` node.
+fn separable_1q_sequence(
+    unitary: ArrayView2<Complex64>,
+    qubit: PhysicalQubit,
+    target: &Target,
+) -> Option<(Vec<(StandardGate, SmallVec<[Param; 3]>)>, f64)> {
+    let target_basis_set = get_target_basis_set(target, qubit);
+    let error_map = build_one_qubit_error_map(target, qubit);
+    let sequence = unitary_to_gate_sequence_inner(
+        unitary,
+        &target_basis_set,
+        qubit.0 as usize,
+        Some(&error_map),
+        true,
+        None,
+    )?;
+    let gates = sequence
+        .gates
+        .into_iter()
+        .map(|(gate, params)| {
+            let params: SmallVec<[Param; 3]> = params.iter().map(|p| Param::Float(*p)).collect();
+            (gate, params)
+        })
+        .collect();
+    Some((gates, sequence.global_phase))
+}
+
+/// Gates whose action is purely diagonal in the computational basis -- so a diagonal 1q rotation
+/// (`RZGate`/`PhaseGate`) on either qubit commutes straight through them, whether applied just
+/// before or just after. Used by the commutation-aware 1q peephole in `py_run_main_loop` to decide
+/// which neighboring 2q gates a run's trailing `Rz` can be pushed across.
+fn diagonal_commuting_legs(gate: StandardGate) -> [bool; 2] {
+    match gate {
+        StandardGate::CZGate | StandardGate::RZZGate | StandardGate::CPhaseGate => [true, true],
+        _ => [false, false],
+    }
+}
+
+/// If `before`'s last gate is a diagonal rotation (`RZGate`/`PhaseGate`), commute it across a
+/// diagonal-commuting 2q gate and try folding it into the start of `after` by resynthesizing the
+/// combined 1q operator. Only applied when the resynthesized `after` needs no more gates than it
+/// already had -- `before` always drops a gate either way, so that alone is enough to guarantee the
+/// merge strictly reduces the total 1q gate count. Returns `(new length for `before`, replacement
+/// gates for `after`, global phase of the replacement gates)`, where that phase leaves out
+/// `after`'s own global phase (the caller adds it), or `None` if there's no movable trailing gate
+/// or the merge doesn't pay for itself.
+///
+/// This only handles the forward direction (a trailing gate on `before` moving into `after`); the
+/// mirror case -- a leading gate on `after` commuting backward into `before` -- isn't attempted.
+fn try_commute_1q_forward(
+    before: &[(StandardGate, SmallVec<[Param; 3]>)],
+    after: &[(StandardGate, SmallVec<[Param; 3]>)],
+    qubit: PhysicalQubit,
+    target: &Target,
+) -> Option<(usize, Vec<(StandardGate, SmallVec<[Param; 3]>)>, f64)> {
+    let (last_gate, last_params) = before.last()?;
+    if !matches!(last_gate, StandardGate::RZGate | StandardGate::PhaseGate) {
+        return None;
+    }
+    let mut combined = last_gate.matrix(last_params)?;
+    for (gate, params) in after {
+        combined = gate.matrix(params)?.dot(&combined);
+    }
+    let target_basis_set = get_target_basis_set(target, qubit);
+    let error_map = build_one_qubit_error_map(target, qubit);
+    let new_sequence = unitary_to_gate_sequence_inner(
+        combined.view(),
+        &target_basis_set,
+        qubit.0 as usize,
+        Some(&error_map),
+        true,
+        None,
+    )?;
+    if new_sequence.gates.len() > after.len() {
+        return None;
+    }
+    let gates = new_sequence
+        .gates
+        .into_iter()
+        .map(|(gate, params)| {
+            let params: SmallVec<[Param; 3]> = params.iter().map(|p| Param::Float(*p)).collect();
+            (gate, params)
+        })
+        .collect();
+    Some((before.len() - 1, gates, new_sequence.global_phase))
+}
+
 fn apply_synth_dag(
     py: Python<'_>,
     out_dag: &mut DAGCircuit,
@@ -224,8 +534,22 @@ fn synth_error(
 
 // This is the outer-most run function. It is meant to be called from Python
 // in `UnitarySynthesis.run()`.
+//
+// `num_threads` selects how many rayon workers synthesize the collected `unitary` nodes with:
+// `None` uses rayon's global pool (typically one worker per core), `Some(n)` builds a dedicated
+// `n`-worker pool for this call only. Either way, synthesis itself runs with the GIL released
+// (see the parallel region below); only the handful of Python calls each decomposer still needs
+// (XXDecomposer, the QSD fallback) reacquire it per-node via `Python::with_gil`.
+//
+// `commutative_1q_peephole`, when set, runs an extra serial pass after synthesis that looks for a
+// 1-qubit `unitary` job directly followed by a 2-qubit `unitary` job directly followed by another
+// 1-qubit `unitary` job, all sharing one qubit with nothing else in between; if the 2q job
+// synthesized down to a single diagonal-commuting native gate (see `diagonal_commuting_legs`) and
+// the first 1q run ends in a diagonal rotation, that rotation is commuted across the 2q gate and
+// folded into the second 1q run (see `try_commute_1q_forward`) whenever doing so doesn't grow it.
 #[pyfunction]
 #[pyo3(name = "run_default_main_loop")]
+#[pyo3(signature = (dag, qubit_indices, min_qubits, target, coupling_edges, approximation_degree, natural_direction, num_threads=None, commutative_1q_peephole=false))]
 fn py_run_main_loop(
     py: Python,
     dag: &mut DAGCircuit,
@@ -235,6 +559,8 @@ fn py_run_main_loop(
     coupling_edges: &Bound<'_, PyList>,
     approximation_degree: Option<f64>,
     natural_direction: Option<bool>,
+    num_threads: Option<usize>,
+    commutative_1q_peephole: bool,
 ) -> PyResult<DAGCircuit> {
     let dag_to_circuit = imports::DAG_TO_CIRCUIT.get_bound(py);
 
@@ -278,6 +604,8 @@ fn py_run_main_loop(
                 coupling_edges,
                 approximation_degree,
                 natural_direction,
+                num_threads,
+                commutative_1q_peephole,
             )?;
             new_blocks.push(dag_to_circuit.call1((res,))?);
         }
@@ -290,121 +618,606 @@ fn py_run_main_loop(
     }
     let mut out_dag = dag.copy_empty_like(py, "alike")?;
 
-    // Iterate over dag nodes and determine unitary synthesis approach
-    for node in dag.topological_op_nodes()? {
+    // Pass 1 (serial): walk the DAG in topological order and, for every candidate `unitary`
+    // node, pull out just enough information (its matrix, and the physical qubits it sits on)
+    // to synthesize it with no further access to `dag`. This is what lets pass 2 run free of the
+    // GIL and free of `dag` borrows.
+    let node_ids: Vec<NodeIndex> = dag.topological_op_nodes()?.collect();
+    let mut jobs: Vec<Option<NodeWork>> = Vec::with_capacity(node_ids.len());
+    for &node in &node_ids {
         let NodeType::Operation(packed_instr) = &dag.dag()[node] else {
             panic!("DAG node must be an instruction")
         };
         if !(packed_instr.op.name() == "unitary"
             && packed_instr.op.num_qubits() >= min_qubits as u32)
         {
-            out_dag.push_back(py, packed_instr.clone())?;
+            jobs.push(None);
             continue;
         }
-        let unitary: Array<Complex<f64>, Dim<[usize; 2]>> = match packed_instr.op.matrix(&[]) {
+        let unitary: Array2<Complex64> = match packed_instr.op.matrix(&[]) {
             Some(unitary) => unitary,
             None => return Err(QiskitError::new_err("Unitary not found")),
         };
-        match unitary.shape() {
-            // Run 1q synthesis
+        jobs.push(Some(match unitary.shape() {
             [2, 2] => {
                 let qubit = dag.get_qargs(packed_instr.qubits)[0];
-                let target_basis_set = get_target_basis_set(target, PhysicalQubit::new(qubit.0));
+                NodeWork::OneQubit { qubit, unitary }
+            }
+            [4, 4] => {
+                // "ref_qubits" is used to access properties in the target. It accounts for
+                // control flow mapping.
+                let out_qargs = dag.get_qargs(packed_instr.qubits);
+                let ref_qubits = [
+                    PhysicalQubit::new(qubit_indices.get_item(out_qargs[0].0 as usize)?.extract()?),
+                    PhysicalQubit::new(qubit_indices.get_item(out_qargs[1].0 as usize)?.extract()?),
+                ];
+                NodeWork::TwoQubit { ref_qubits, unitary }
+            }
+            _ => {
+                let out_qargs = dag.get_qargs(packed_instr.qubits).to_vec();
+                NodeWork::ThreeQubitPlus { unitary, out_qargs }
+            }
+        }));
+    }
+
+    // Build adjacency between 1-qubit and 2-qubit `unitary` jobs for the commutation-aware
+    // peephole below: for every 2-qubit job, the immediately preceding/following 1-qubit job on
+    // each leg, if nothing else touches that qubit in between. Keyed by index into `jobs`.
+    let mut pred_1q: HashMap<usize, [Option<usize>; 2]> = HashMap::new();
+    let mut succ_1q: HashMap<usize, [Option<usize>; 2]> = HashMap::new();
+    if commutative_1q_peephole {
+        let mut last_1q_job: HashMap<Qubit, usize> = HashMap::new();
+        for (idx, &node) in node_ids.iter().enumerate() {
+            let NodeType::Operation(packed_instr) = &dag.dag()[node] else {
+                panic!("DAG node must be an instruction")
+            };
+            let qargs = dag.get_qargs(packed_instr.qubits);
+            if matches!(&jobs[idx], Some(NodeWork::TwoQubit { .. })) {
+                pred_1q.insert(
+                    idx,
+                    [
+                        last_1q_job.get(&qargs[0]).copied(),
+                        last_1q_job.get(&qargs[1]).copied(),
+                    ],
+                );
+            }
+            for q in qargs {
+                last_1q_job.remove(q);
+            }
+            if let Some(NodeWork::OneQubit { qubit, .. }) = &jobs[idx] {
+                last_1q_job.insert(*qubit, idx);
+            }
+        }
+        let mut next_1q_job: HashMap<Qubit, usize> = HashMap::new();
+        for (idx, &node) in node_ids.iter().enumerate().rev() {
+            let NodeType::Operation(packed_instr) = &dag.dag()[node] else {
+                panic!("DAG node must be an instruction")
+            };
+            let qargs = dag.get_qargs(packed_instr.qubits);
+            if matches!(&jobs[idx], Some(NodeWork::TwoQubit { .. })) {
+                succ_1q.insert(
+                    idx,
+                    [
+                        next_1q_job.get(&qargs[0]).copied(),
+                        next_1q_job.get(&qargs[1]).copied(),
+                    ],
+                );
+            }
+            for q in qargs {
+                next_1q_job.remove(q);
+            }
+            if let Some(NodeWork::OneQubit { qubit, .. }) = &jobs[idx] {
+                next_1q_job.insert(*qubit, idx);
+            }
+        }
+    }
+
+    // Pass 2 (parallel, GIL released): synthesize every collected node. There is no data
+    // dependency between nodes, so this is where the actual synthesis work -- the expensive
+    // part -- gets to run across a rayon thread pool instead of one node at a time.
+    let coupling_edges = coupling_edge_set(coupling_edges);
+    let decomposer_cache: TwoQubitDecomposerCache = Mutex::new(HashMap::new());
+    let basis_decomposer_cache: BasisDecomposerCache = Mutex::new(HashMap::new());
+    let run_job = |work: &NodeWork| -> NodeOutcome {
+        match work {
+            NodeWork::OneQubit { qubit, unitary } => {
+                let physical_qubit = PhysicalQubit::new(qubit.0);
+                let target_basis_set = get_target_basis_set(target, physical_qubit);
+                let error_map = build_one_qubit_error_map(target, physical_qubit);
                 let sequence = unitary_to_gate_sequence_inner(
                     unitary.view(),
                     &target_basis_set,
                     qubit.0 as usize,
-                    None,
+                    Some(&error_map),
                     true,
                     None,
                 );
-                match sequence {
-                    Some(sequence) => {
-                        for (gate, params) in sequence.gates {
+                NodeOutcome::OneQubit(sequence.map(|sequence| {
+                    let gates = sequence
+                        .gates
+                        .into_iter()
+                        .map(|(gate, params)| {
                             let new_params: SmallVec<[Param; 3]> =
                                 params.iter().map(|p| Param::Float(*p)).collect();
+                            (gate, new_params)
+                        })
+                        .collect();
+                    (gates, sequence.global_phase)
+                }))
+            }
+            NodeWork::TwoQubit { ref_qubits, unitary } => {
+                // The common case -- this qubit pair's decomposer is already cached and it's the
+                // single, pure-Rust `TwoQubitBasisDecomposer` -- never needs the GIL at all; only
+                // fall back to reacquiring it for a cache miss, a `synth_error`-scored multi-
+                // decomposer comparison, or an `XXDecomposer` match (see
+                // `try_run_2q_unitary_synthesis_gil_free`'s docs).
+                NodeOutcome::TwoQubit(
+                    try_run_2q_unitary_synthesis_gil_free(
+                        unitary,
+                        ref_qubits,
+                        &coupling_edges,
+                        target,
+                        approximation_degree,
+                        natural_direction,
+                        &decomposer_cache,
+                    )
+                    .unwrap_or_else(|| {
+                        Python::with_gil(|py| {
+                            run_2q_unitary_synthesis(
+                                py,
+                                unitary.clone(),
+                                ref_qubits,
+                                &coupling_edges,
+                                target,
+                                approximation_degree,
+                                natural_direction,
+                                &decomposer_cache,
+                                &basis_decomposer_cache,
+                            )
+                        })
+                    }),
+                )
+            }
+            NodeWork::ThreeQubitPlus { unitary, out_qargs } => {
+                let local_qubits: Vec<Qubit> = (0..out_qargs.len() as u32).map(Qubit).collect();
+                match quantum_shannon_decompose(unitary.view(), &local_qubits) {
+                    Some((gates, global_phase)) => {
+                        NodeOutcome::ThreeQubitPlus(Ok(ThreeQubitPlusOutcome::Native(
+                            gates,
+                            global_phase,
+                        )))
+                    }
+                    None => NodeOutcome::ThreeQubitPlus(Python::with_gil(|py| {
+                        let qs_decomposition: &Bound<'_, PyAny> =
+                            imports::QS_DECOMPOSITION.get_bound(py);
+                        let synth_circ =
+                            qs_decomposition.call1((unitary.clone().into_pyarray_bound(py),))?;
+                        let synth_dag = circuit_to_dag(
+                            py,
+                            QuantumCircuitData::extract_bound(&synth_circ)?,
+                            false,
+                            None,
+                            None,
+                        )?;
+                        Ok(ThreeQubitPlusOutcome::Fallback(synth_dag))
+                    })),
+                }
+            }
+        }
+    };
+    let mut results: Vec<Option<NodeOutcome>> = py.allow_threads(|| -> PyResult<_> {
+        let run_all = || jobs.par_iter().map(|job| job.as_ref().map(run_job)).collect();
+        match num_threads {
+            Some(num_threads) => {
+                let pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .map_err(|err| QiskitError::new_err(err.to_string()))?;
+                Ok(pool.install(run_all))
+            }
+            None => Ok(run_all()),
+        }
+    })?;
+
+    // Pass 2.5 (serial, optional): the commutation-aware 1q peephole. For every 2-qubit job that
+    // synthesized down to a single native 2q gate, check whether that gate is diagonal-commuting
+    // on a leg flanked by a 1-qubit job on each side; if so, try commuting the predecessor's
+    // trailing gate across and folding it into the successor (see `try_commute_1q_forward`).
+    // Plans are collected first and only applied if neither of their two 1-qubit jobs was already
+    // claimed by another plan -- chained merges across more than one hop aren't attempted.
+    if commutative_1q_peephole {
+        let mut plans: Vec<(
+            usize,
+            Vec<(StandardGate, SmallVec<[Param; 3]>)>,
+            f64,
+            usize,
+            Vec<(StandardGate, SmallVec<[Param; 3]>)>,
+            f64,
+        )> = Vec::new();
+        for (&k, legs) in &pred_1q {
+            let Some(NodeOutcome::TwoQubit(Ok(UnitarySynthOutcome::Sequence(seq)))) = &results[k]
+            else {
+                continue;
+            };
+            if seq.gate_sequence.gates.len() != 1 {
+                continue;
+            }
+            let (gate, _, qubit_ids) = seq.gate_sequence.gates[0].clone();
+            if qubit_ids.len() != 2 {
+                continue;
+            }
+            let native_gate = match gate {
+                Some(gate) => gate,
+                None => seq.decomp_gate.operation.standard_gate(),
+            };
+            let commuting = diagonal_commuting_legs(native_gate);
+            let Some(succs) = succ_1q.get(&k) else {
+                continue;
+            };
+            let NodeWork::TwoQubit { ref_qubits, .. } = jobs[k].as_ref().unwrap() else {
+                continue;
+            };
+            for leg in 0..2 {
+                if !commuting[leg] {
+                    continue;
+                }
+                let (Some(p), Some(s)) = (legs[leg], succs[leg]) else {
+                    continue;
+                };
+                let Some(NodeOutcome::OneQubit(Some((pred_gates, pred_phase)))) = &results[p]
+                else {
+                    continue;
+                };
+                let Some(NodeOutcome::OneQubit(Some((succ_gates, succ_phase)))) = &results[s]
+                else {
+                    continue;
+                };
+                if let Some((trimmed_len, new_succ_gates, new_succ_phase)) =
+                    try_commute_1q_forward(pred_gates, succ_gates, ref_qubits[leg], target)
+                {
+                    plans.push((
+                        p,
+                        pred_gates[..trimmed_len].to_vec(),
+                        *pred_phase,
+                        s,
+                        new_succ_gates,
+                        new_succ_phase + *succ_phase,
+                    ));
+                }
+            }
+        }
+        let mut used = HashSet::new();
+        for (p, new_pred, pred_phase, s, new_succ, succ_phase) in plans {
+            if used.contains(&p) || used.contains(&s) {
+                continue;
+            }
+            used.insert(p);
+            used.insert(s);
+            results[p] = Some(NodeOutcome::OneQubit(Some((new_pred, pred_phase))));
+            results[s] = Some(NodeOutcome::OneQubit(Some((new_succ, succ_phase))));
+        }
+    }
+
+    // Pass 3 (serial): apply every result to `out_dag` in topological order. DAG mutation isn't
+    // parallelizable (insertion order matters and `out_dag` is a single shared structure), but
+    // by this point it's just bookkeeping -- all the actual synthesis happened in pass 2.
+    for (&node, outcome) in node_ids.iter().zip(results) {
+        let NodeType::Operation(packed_instr) = &dag.dag()[node] else {
+            panic!("DAG node must be an instruction")
+        };
+        match outcome {
+            None => {
+                out_dag.push_back(py, packed_instr.clone())?;
+            }
+            Some(NodeOutcome::OneQubit(None)) => {
+                out_dag.push_back(py, packed_instr.clone())?;
+            }
+            Some(NodeOutcome::OneQubit(Some((gates, global_phase)))) => {
+                let qubit = dag.get_qargs(packed_instr.qubits)[0];
+                for (gate, params) in gates {
+                    out_dag.apply_operation_back(
+                        py,
+                        gate.into(),
+                        &[qubit],
+                        &[],
+                        Some(params),
+                        ExtraInstructionAttributes::new(None, None, None, None),
+                        #[cfg(feature = "cache_pygates")]
+                        None,
+                    )?;
+                }
+                out_dag.add_global_phase(py, &Param::Float(global_phase))?;
+            }
+            Some(NodeOutcome::TwoQubit(result)) => {
+                // "out_qargs" is used to append the synthesized instructions to the output dag
+                let out_qargs = dag.get_qargs(packed_instr.qubits).to_vec();
+                match result? {
+                    UnitarySynthOutcome::Sequence(sequence) => {
+                        apply_synth_sequence(py, &mut out_dag, &out_qargs, &sequence)?
+                    }
+                    UnitarySynthOutcome::Dag(synth_dag) => {
+                        apply_synth_dag(py, &mut out_dag, &out_qargs, &synth_dag)?
+                    }
+                    UnitarySynthOutcome::Separable {
+                        qubit0,
+                        qubit1,
+                        global_phase,
+                    } => {
+                        for (qubit, gates) in
+                            [(out_qargs[0], qubit0), (out_qargs[1], qubit1)]
+                        {
+                            for (gate, params) in gates {
+                                out_dag.apply_operation_back(
+                                    py,
+                                    gate.into(),
+                                    &[qubit],
+                                    &[],
+                                    Some(params),
+                                    ExtraInstructionAttributes::new(None, None, None, None),
+                                    #[cfg(feature = "cache_pygates")]
+                                    None,
+                                )?;
+                            }
+                        }
+                        out_dag.add_global_phase(py, &Param::Float(global_phase))?;
+                    }
+                    UnitarySynthOutcome::Original => {
+                        out_dag.push_back(py, packed_instr.clone())?;
+                    }
+                }
+            }
+            Some(NodeOutcome::ThreeQubitPlus(result)) => {
+                let out_qargs = dag.get_qargs(packed_instr.qubits).to_vec();
+                match result? {
+                    ThreeQubitPlusOutcome::Native(gates, global_phase) => {
+                        for (gate, params, local_qubits) in gates {
+                            let mapped_qubits: Vec<Qubit> = local_qubits
+                                .iter()
+                                .map(|q| out_qargs[q.0 as usize])
+                                .collect();
                             out_dag.apply_operation_back(
                                 py,
                                 gate.into(),
-                                &[qubit],
+                                &mapped_qubits,
                                 &[],
-                                Some(new_params),
+                                Some(params),
                                 ExtraInstructionAttributes::new(None, None, None, None),
                                 #[cfg(feature = "cache_pygates")]
                                 None,
                             )?;
                         }
-                        out_dag.add_global_phase(py, &Param::Float(sequence.global_phase))?;
+                        out_dag.add_global_phase(py, &Param::Float(global_phase))?;
                     }
-                    None => {
-                        out_dag.push_back(py, packed_instr.clone())?;
+                    ThreeQubitPlusOutcome::Fallback(synth_dag) => {
+                        apply_synth_dag(py, &mut out_dag, &out_qargs, &synth_dag)?;
                     }
                 }
             }
-            // Run 2q synthesis
-            [4, 4] => {
-                // "out_qargs" is used to append the synthesized instructions to the output dag
-                let out_qargs = dag.get_qargs(packed_instr.qubits);
-                // "ref_qubits" is used to access properties in the target. It accounts for control flow mapping.
-                let ref_qubits: &[PhysicalQubit; 2] = &[
-                    PhysicalQubit::new(qubit_indices.get_item(out_qargs[0].0 as usize)?.extract()?),
-                    PhysicalQubit::new(qubit_indices.get_item(out_qargs[1].0 as usize)?.extract()?),
-                ];
-                let apply_original_op = |out_dag: &mut DAGCircuit| -> PyResult<()> {
-                    out_dag.push_back(py, packed_instr.clone())?;
-                    Ok(())
-                };
-                run_2q_unitary_synthesis(
-                    py,
-                    unitary,
-                    ref_qubits,
-                    coupling_edges,
-                    target,
-                    approximation_degree,
-                    natural_direction,
-                    &mut out_dag,
-                    out_qargs,
-                    apply_original_op,
-                )?;
-            }
-            // Run 3q+ synthesis
-            _ => {
-                let qs_decomposition: &Bound<'_, PyAny> = imports::QS_DECOMPOSITION.get_bound(py);
-                let synth_circ = qs_decomposition.call1((unitary.into_pyarray_bound(py),))?;
-                let synth_dag = circuit_to_dag(
-                    py,
-                    QuantumCircuitData::extract_bound(&synth_circ)?,
-                    false,
-                    None,
-                    None,
-                )?;
-                out_dag = synth_dag;
-            }
         }
     }
     Ok(out_dag)
 }
 
+/// Key for [`TwoQubitDecomposerCache`]: a direction-normalized qubit pair plus the approximation
+/// degree the decomposers were built with (`f64` isn't `Hash`/`Eq`, so it's stored by bit pattern).
+type DecomposerCacheKey = (PhysicalQubit, PhysicalQubit, Option<u64>);
+
+/// Caches the (potentially expensive, KAK-table-building) result of
+/// [`get_2q_decomposers_from_target`] across `unitary` nodes that share a qubit pair, shared by
+/// all rayon workers synthesizing 2q nodes in [`py_run_main_loop`]'s Pass 2 -- hence the `Mutex`
+/// rather than a plain `HashMap`.
+type TwoQubitDecomposerCache = Mutex<HashMap<DecomposerCacheKey, Vec<DecomposerElement>>>;
+
+/// Key for [`BasisDecomposerCache`]: the *shape* of what's synthesizable on a qubit pair -- its 1q
+/// and 2q basis gate names, plus each 2q gate's fidelity rounded to a configurable granularity --
+/// rather than the pair itself, so two edges exposing the same (or, depending on rounding,
+/// near-enough) basis and error profile share one built set of decomposers. Sorted so that the
+/// same basis always hashes to the same key regardless of iteration order.
+type BasisSignature = (Vec<&'static str>, Vec<(String, Option<u64>)>, Option<u64>);
+
+/// Caches [`get_2q_decomposers_from_target`]'s result keyed by [`BasisSignature`] rather than by
+/// qubit pair, shared across all of [`py_run_main_loop`]'s Pass 2 workers the same way
+/// [`TwoQubitDecomposerCache`] is: on a uniform (or near-uniform) coupling map, most edges share an
+/// identical basis, so this reuses one built `Vec<DecomposerElement>` across all of them instead
+/// of rebuilding per edge. [`get_2q_decomposers_cached`] checks this cache on a
+/// [`TwoQubitDecomposerCache`] miss, before paying for [`get_2q_decomposers_from_target`].
+type BasisDecomposerCache = Mutex<HashMap<BasisSignature, Vec<DecomposerElement>>>;
+
+/// Compute `ref_qubits`' [`BasisSignature`] for [`BasisDecomposerCache`]: its 1q Euler bases, its
+/// 2q basis gate names with fidelity rounded to `rounding_decimals` places, and the approximation
+/// degree. `rounding_decimals` is the configurable granularity the fidelity rounding happens at --
+/// pass a smaller value to merge more (less precisely matched) edges into the same cache entry on
+/// a heterogeneous device, or a larger one to only ever share a decomposer between edges with
+/// near-identical error rates. Returns `None` when `target` has no gates at all on `ref_qubits`
+/// (the same condition under which [`get_2q_decomposers_from_target`] errors).
+fn basis_signature(
+    target: &Target,
+    ref_qubits: &[PhysicalQubit; 2],
+    approximation_degree: Option<f64>,
+    rounding_decimals: i32,
+) -> Option<BasisSignature> {
+    let qubits: SmallVec<[PhysicalQubit; 2]> = SmallVec::from_buf(*ref_qubits);
+    let reverse_qubits: SmallVec<[PhysicalQubit; 2]> = qubits.iter().rev().copied().collect();
+    let names = target
+        .operation_names_for_qargs(Some(&qubits))
+        .or_else(|_| target.operation_names_for_qargs(Some(&reverse_qubits)))
+        .ok()?;
+
+    let scale = 10f64.powi(rounding_decimals);
+    let mut two_qubit_basis: Vec<(String, Option<u64>)> = names
+        .iter()
+        .filter(|name| target.qargs_for_operation_name(name).is_ok())
+        .map(|name| {
+            let error = match &target[*name].get(Some(&qubits)) {
+                Some(Some(props)) => props.error,
+                _ => None,
+            };
+            (name.to_string(), error.map(|e| (e * scale).round().to_bits()))
+        })
+        .collect();
+    two_qubit_basis.sort();
+
+    let mut one_qubit_basis: Vec<&'static str> = get_target_basis_set(target, qubits[0])
+        .get_bases()
+        .map(|basis| basis.as_str())
+        .collect();
+    one_qubit_basis.sort_unstable();
+
+    Some((
+        one_qubit_basis,
+        two_qubit_basis,
+        approximation_degree.map(f64::to_bits),
+    ))
+}
+
+/// The default [`basis_signature`] fidelity-rounding granularity: two edges whose 2q gate errors
+/// agree to this many decimal places are treated as sharing a decomposer.
+const DEFAULT_FIDELITY_ROUNDING_DECIMALS: i32 = 4;
+
+fn get_2q_decomposers_cached(
+    py: Python,
+    cache: &TwoQubitDecomposerCache,
+    basis_cache: &BasisDecomposerCache,
+    target: &Target,
+    ref_qubits: &[PhysicalQubit; 2],
+    approximation_degree: Option<f64>,
+) -> PyResult<Vec<DecomposerElement>> {
+    let key = (
+        ref_qubits[0].min(ref_qubits[1]),
+        ref_qubits[0].max(ref_qubits[1]),
+        approximation_degree.map(f64::to_bits),
+    );
+    if let Some(decomposers) = cache.lock().unwrap().get(&key) {
+        return Ok(decomposers.clone());
+    }
+
+    let signature = basis_signature(
+        target,
+        ref_qubits,
+        approximation_degree,
+        DEFAULT_FIDELITY_ROUNDING_DECIMALS,
+    );
+    if let Some(signature) = &signature {
+        if let Some(decomposers) = basis_cache.lock().unwrap().get(signature) {
+            let decomposers = decomposers.clone();
+            cache.lock().unwrap().insert(key, decomposers.clone());
+            return Ok(decomposers);
+        }
+    }
+
+    let decomposers =
+        get_2q_decomposers_from_target(py, target, ref_qubits, approximation_degree)?
+            .unwrap_or_default();
+    cache.lock().unwrap().insert(key, decomposers.clone());
+    if let Some(signature) = signature {
+        basis_cache.lock().unwrap().insert(signature, decomposers.clone());
+    }
+    Ok(decomposers)
+}
+
+/// Attempt the cheap path of [`run_2q_unitary_synthesis`] with no GIL at all: the separable check
+/// (a pure Weyl-decomposition computation), and, when `ref_qubits`' decomposer is already cached
+/// and is the single, pure-Rust `TwoQubitBasisDecomposer`, the actual synthesis
+/// (`synth_su4_sequence`/`reversed_synth_su4_sequence`, neither of which touch Python). Returns
+/// `None` when the GIL is unavoidable -- a cache miss (building a fresh decomposer list needs
+/// `py` to introspect `target`'s Python-side gate objects), more than one candidate decomposer
+/// (scoring them calls `synth_error(py, ...)`), or an `XXDecomposer` match -- so the caller falls
+/// back to [`run_2q_unitary_synthesis`] under `Python::with_gil`. This is what lets Pass 2's rayon
+/// workers synthesize same-native-gate hardware targets -- the common case -- fully GIL-free; see
+/// `py_run_main_loop`.
+fn try_run_2q_unitary_synthesis_gil_free(
+    unitary: &Array2<Complex64>,
+    ref_qubits: &[PhysicalQubit; 2],
+    coupling_edges: &HashSet<(usize, usize)>,
+    target: &Target,
+    approximation_degree: Option<f64>,
+    natural_direction: Option<bool>,
+    decomposer_cache: &TwoQubitDecomposerCache,
+) -> Option<PyResult<UnitarySynthOutcome>> {
+    let kak = TwoQubitWeylDecomposition::new_inner(unitary.view(), None, None).ok()?;
+    if kak.a().abs() < SEPARABLE_TOL && kak.b().abs() < SEPARABLE_TOL && kak.c().abs() < SEPARABLE_TOL
+    {
+        if let (Some((qubit0, phase0)), Some((qubit1, phase1))) = (
+            separable_1q_sequence(kak.K1r().view(), ref_qubits[0], target),
+            separable_1q_sequence(kak.K1l().view(), ref_qubits[1], target),
+        ) {
+            return Some(Ok(UnitarySynthOutcome::Separable {
+                qubit0,
+                qubit1,
+                global_phase: kak.global_phase + phase0 + phase1,
+            }));
+        }
+    }
+
+    let key = (
+        ref_qubits[0].min(ref_qubits[1]),
+        ref_qubits[0].max(ref_qubits[1]),
+        approximation_degree.map(f64::to_bits),
+    );
+    let decomposers = decomposer_cache.lock().unwrap().get(&key)?.clone();
+    if decomposers.len() != 1 {
+        return None;
+    }
+    let decomposer_item = decomposers.first().unwrap();
+    if !matches!(
+        decomposer_item.decomposer,
+        DecomposerType::TwoQubitBasisDecomposer(_)
+    ) {
+        return None;
+    }
+    Some((|| -> PyResult<UnitarySynthOutcome> {
+        let preferred_dir = preferred_direction(
+            decomposer_item,
+            ref_qubits,
+            natural_direction,
+            coupling_edges,
+            target,
+        )?;
+        let synth =
+            synth_su4_sequence(unitary, decomposer_item, preferred_dir, approximation_degree)?;
+        Ok(UnitarySynthOutcome::Sequence(synth))
+    })())
+}
+
 fn run_2q_unitary_synthesis(
     py: Python,
     unitary: Array2<Complex64>,
     ref_qubits: &[PhysicalQubit; 2],
-    coupling_edges: &Bound<'_, PyList>,
+    coupling_edges: &HashSet<(usize, usize)>,
     target: &Target,
     approximation_degree: Option<f64>,
     natural_direction: Option<bool>,
-    out_dag: &mut DAGCircuit,
-    out_qargs: &[Qubit],
-    mut apply_original_op: impl FnMut(&mut DAGCircuit) -> PyResult<()>,
-) -> PyResult<()> {
-    let decomposers = {
-        let decomposers_2q =
-            get_2q_decomposers_from_target(py, target, ref_qubits, approximation_degree)?;
-        match decomposers_2q {
-            Some(decomp) => decomp,
-            None => Vec::new(),
+    decomposer_cache: &TwoQubitDecomposerCache,
+    basis_decomposer_cache: &BasisDecomposerCache,
+) -> PyResult<UnitarySynthOutcome> {
+    // Check whether `unitary` is actually local (a tensor product of two 1q operators) before
+    // spending a CX/ECR on it: its Weyl-chamber interaction coordinates (a, b, c) are all zero
+    // exactly when it doesn't entangle its two qubits, in which case the whole operator reduces
+    // to the K1l/K1r factors of the decomposition (up to the Weyl global phase), with no middle
+    // interaction term and no need for K2l/K2r.
+    let kak = TwoQubitWeylDecomposition::new_inner(unitary.view(), None, None)?;
+    if kak.a().abs() < SEPARABLE_TOL && kak.b().abs() < SEPARABLE_TOL && kak.c().abs() < SEPARABLE_TOL
+    {
+        if let (Some((qubit0, phase0)), Some((qubit1, phase1))) = (
+            separable_1q_sequence(kak.K1r().view(), ref_qubits[0], target),
+            separable_1q_sequence(kak.K1l().view(), ref_qubits[1], target),
+        ) {
+            return Ok(UnitarySynthOutcome::Separable {
+                qubit0,
+                qubit1,
+                global_phase: kak.global_phase + phase0 + phase1,
+            });
         }
-    };
+    }
+
+    let decomposers = get_2q_decomposers_cached(
+        py,
+        decomposer_cache,
+        basis_decomposer_cache,
+        target,
+        ref_qubits,
+        approximation_degree,
+    )?;
     // If there's a single decomposer, avoid computing synthesis score
     if decomposers.len() == 1 {
         let decomposer_item = decomposers.first().unwrap();
@@ -415,7 +1228,7 @@ fn run_2q_unitary_synthesis(
             coupling_edges,
             target,
         )?;
-        match decomposer_item.decomposer {
+        return Ok(match decomposer_item.decomposer {
             DecomposerType::TwoQubitBasisDecomposer(_) => {
                 let synth = synth_su4_sequence(
                     &unitary,
@@ -423,7 +1236,7 @@ fn run_2q_unitary_synthesis(
                     preferred_dir,
                     approximation_degree,
                 )?;
-                apply_synth_sequence(py, out_dag, out_qargs, &synth)?;
+                UnitarySynthOutcome::Sequence(synth)
             }
             DecomposerType::XXDecomposer(_) => {
                 let synth = synth_su4_dag(
@@ -433,10 +1246,13 @@ fn run_2q_unitary_synthesis(
                     preferred_dir,
                     approximation_degree,
                 )?;
-                apply_synth_dag(py, out_dag, out_qargs, &synth)?;
+                UnitarySynthOutcome::Dag(synth)
             }
-        }
-        return Ok(());
+            DecomposerType::UpToDiagonal(_) => panic!(
+                "UpToDiagonal decomposers are not yet offered by get_2q_decomposers_from_target's \
+                 regular candidate list -- see get_2q_up_to_diagonal_decomposer"
+            ),
+        });
     }
 
     let mut synth_errors_sequence = Vec::new();
@@ -520,6 +1336,10 @@ fn run_2q_unitary_synthesis(
                     .into_iter();
                 synth_errors_dag.push((synth_dag, synth_error(py, scoring_info, target)));
             }
+            DecomposerType::UpToDiagonal(_) => panic!(
+                "UpToDiagonal decomposers are not yet offered by get_2q_decomposers_from_target's \
+                 regular candidate list -- see get_2q_up_to_diagonal_decomposer"
+            ),
         }
     }
 
@@ -535,19 +1355,18 @@ fn run_2q_unitary_synthesis(
         .min_by(|error1, error2| error1.1 .1.partial_cmp(&error2.1 .1).unwrap())
         .map(|(index, _)| &synth_errors_dag[index]);
 
-    match (synth_sequence, synth_dag) {
-        (None, None) => apply_original_op(out_dag)?,
-        (Some((sequence, _)), None) => apply_synth_sequence(py, out_dag, out_qargs, sequence)?,
-        (None, Some((dag, _))) => apply_synth_dag(py, out_dag, out_qargs, dag)?,
+    Ok(match (synth_sequence, synth_dag) {
+        (None, None) => UnitarySynthOutcome::Original,
+        (Some((sequence, _)), None) => UnitarySynthOutcome::Sequence(sequence.clone()),
+        (None, Some((dag, _))) => UnitarySynthOutcome::Dag(dag.clone()),
         (Some((sequence, sequence_error)), Some((dag, dag_error))) => {
             if sequence_error > dag_error {
-                apply_synth_dag(py, out_dag, out_qargs, dag)?
+                UnitarySynthOutcome::Dag(dag.clone())
             } else {
-                apply_synth_sequence(py, out_dag, out_qargs, sequence)?
+                UnitarySynthOutcome::Sequence(sequence.clone())
             }
         }
-    };
-    Ok(())
+    })
 }
 
 fn get_2q_decomposers_from_target(
@@ -807,11 +1626,202 @@ fn get_2q_decomposers_from_target(
     Ok(Some(decomposers))
 }
 
+/// The minimum number of 2q basis gates an exact synthesis of a unitary with Weyl-chamber
+/// coordinates `kak` would need, counted in CX-equivalent applications: `0` for a local
+/// (separable) unitary, `1` when it's locally equivalent to a single CX (`a == pi/4`,
+/// `b == c == 0`), `2` when it's `c == 0` but not `1`-equivalent, else `3`. A unitary locally
+/// equivalent to one application of a supercontrolled gate with `b != 0` counts as `2`.
+fn min_basis_gate_count(kak: &TwoQubitWeylDecomposition) -> u8 {
+    if relative_eq!(kak.a(), 0.0) && relative_eq!(kak.b(), 0.0) && relative_eq!(kak.c(), 0.0) {
+        0
+    } else if relative_eq!(kak.c(), 0.0) && relative_eq!(kak.a(), PI4) && relative_eq!(kak.b(), 0.0)
+    {
+        1
+    } else if relative_eq!(kak.c(), 0.0) {
+        2
+    } else {
+        3
+    }
+}
+
+/// The best available 1q gate's fidelity on `qubit`, used by [`estimate_decomposer_fidelity`] as a
+/// stand-in for "per-layer 1q fidelity": the concrete Euler basis a `TwoQubitBasisDecomposer` was
+/// built with isn't recoverable from it after construction, but every basis ends up drawing from
+/// the same pool of 1q gates the target exposes here, so the best of those is a reasonable
+/// estimate of what the decomposer will actually use.
+fn best_1q_gate_fidelity(target: &Target, qubit: PhysicalQubit) -> f64 {
+    let Ok(basis_list) = target.operation_names_for_qargs(Some(&smallvec![qubit])) else {
+        return 1.0;
+    };
+    let best_error = basis_list
+        .iter()
+        .filter(|gate| target.qargs_for_operation_name(gate).is_ok())
+        .filter_map(|gate| match &target[*gate].get(Some(&smallvec![qubit])) {
+            Some(Some(props)) => props.error,
+            _ => None,
+        })
+        .fold(f64::INFINITY, f64::min);
+    if best_error.is_finite() {
+        1.0 - best_error
+    } else {
+        1.0
+    }
+}
+
+/// Estimate the fidelity of synthesizing `kak`'s unitary with `decomposer`, without actually
+/// running the (potentially expensive) synthesis: `basis_2q_fidelity ^ n` for the minimum
+/// basis-gate count `n` (see [`min_basis_gate_count`]), times the best available 1q gate fidelity
+/// on each qubit, raised to the number of 1q layers (`n + 1`, one sandwiching each 2q gate plus
+/// one at either end). Returns `None` for an `XXDecomposer`/`UpToDiagonal` candidate -- per-strength
+/// weighting for the former needs the Python-side `basis_2q_fidelity_dict` this estimator doesn't
+/// have access to post-construction, and the latter isn't comparable on the same terms (see
+/// [`get_2q_up_to_diagonal_decomposer`]) -- so callers should only prefer those when no
+/// `TwoQubitBasisDecomposer` candidate is available.
+fn estimate_decomposer_fidelity(
+    decomposer: &DecomposerElement,
+    kak: &TwoQubitWeylDecomposition,
+    ref_qubits: &[PhysicalQubit; 2],
+    target: &Target,
+) -> Option<f64> {
+    if !matches!(
+        decomposer.decomposer,
+        DecomposerType::TwoQubitBasisDecomposer(_)
+    ) {
+        return None;
+    }
+    let n = min_basis_gate_count(kak);
+    let basis_2q_fidelity = match target.qargs_for_operation_name(decomposer.gate.operation.name())
+    {
+        Ok(_) => match &target[decomposer.gate.operation.name()].get(Some(
+            &ref_qubits
+                .iter()
+                .copied()
+                .collect::<SmallVec<[PhysicalQubit; 2]>>(),
+        )) {
+            Some(Some(props)) => 1.0 - props.error.unwrap_or(0.0),
+            _ => 1.0,
+        },
+        Err(_) => 1.0,
+    };
+    let layer_fidelity =
+        best_1q_gate_fidelity(target, ref_qubits[0]) * best_1q_gate_fidelity(target, ref_qubits[1]);
+    Some(basis_2q_fidelity.powi(n as i32) * layer_fidelity.powi(n as i32 + 1))
+}
+
+/// Given a concrete `unitary` and the qubit pair it's targeting, pick whichever of `decomposers`
+/// [`estimate_decomposer_fidelity`] ranks highest, instead of synthesizing with every candidate
+/// just to compare (unlike [`run_2q_unitary_synthesis`]'s `synth_error`-based scoring, which needs
+/// the actual synthesized sequence to compare). Every `TwoQubitBasisDecomposer` candidate shares
+/// the same minimum basis-gate count for a given `unitary` (it's a property of the unitary's Weyl
+/// coordinates, not of the decomposer), so ties are broken by duration rather than gate count --
+/// reading it the same way [`preferred_direction`]'s cost helper does. `XXDecomposer`/
+/// `UpToDiagonal` candidates are only picked when no estimate is available for anything else.
+fn best_2q_decomposer<'a>(
+    unitary: ArrayView2<Complex64>,
+    ref_qubits: &[PhysicalQubit; 2],
+    target: &Target,
+    decomposers: &'a [DecomposerElement],
+) -> PyResult<Option<&'a DecomposerElement>> {
+    if decomposers.is_empty() {
+        return Ok(None);
+    }
+    let kak = TwoQubitWeylDecomposition::new_inner(unitary, None, None)?;
+    let duration = |decomposer: &DecomposerElement| -> f64 {
+        match target.qargs_for_operation_name(decomposer.gate.operation.name()) {
+            Ok(_) => match &target[decomposer.gate.operation.name()].get(Some(
+                &ref_qubits
+                    .iter()
+                    .copied()
+                    .collect::<SmallVec<[PhysicalQubit; 2]>>(),
+            )) {
+                Some(Some(props)) => props.duration.unwrap_or(f64::INFINITY),
+                _ => f64::INFINITY,
+            },
+            Err(_) => f64::INFINITY,
+        }
+    };
+    Ok(decomposers
+        .iter()
+        .map(|decomposer| {
+            (
+                decomposer,
+                estimate_decomposer_fidelity(decomposer, &kak, ref_qubits, target),
+            )
+        })
+        .max_by(|(decomposer1, fidelity1), (decomposer2, fidelity2)| {
+            match (fidelity1, fidelity2) {
+                (Some(f1), Some(f2)) => f1
+                    .partial_cmp(f2)
+                    .unwrap()
+                    .then_with(|| duration(decomposer2).total_cmp(&duration(decomposer1))),
+                (Some(_), None) => std::cmp::Ordering::Greater,
+                (None, Some(_)) => std::cmp::Ordering::Less,
+                (None, None) => std::cmp::Ordering::Equal,
+            }
+        })
+        .map(|(decomposer, _)| decomposer))
+}
+
+/// Build the up-to-diagonal decomposer for `ref_qubits`, when available: only offered when a
+/// basis gate on this pair is supercontrolled, the same requirement
+/// [`get_2q_decomposers_from_target`] uses before building a [`TwoQubitBasisDecomposer`] (the
+/// diagonal-absorbing synthesis relies on the same Weyl-chamber machinery).
+///
+/// Deliberately NOT folded into [`get_2q_decomposers_from_target`]'s regular candidate list:
+/// picking this decomposer changes what the *next* block on these qubits needs to synthesize (the
+/// leftover diagonal `D` has to be left-multiplied into that block's target matrix before it
+/// runs), which needs cross-node coordination that `py_run_main_loop`'s independent, order-free
+/// Pass 2 doesn't do yet. This function, together with [`synth_su4_up_to_diagonal_sequence`], is
+/// the building block for wiring that up -- not yet called from `py_run_main_loop`, hence the
+/// `allow`.
+#[allow(dead_code)]
+fn get_2q_up_to_diagonal_decomposer(
+    target: &Target,
+    ref_qubits: &[PhysicalQubit; 2],
+) -> PyResult<Option<DecomposerElement>> {
+    let qubits: SmallVec<[PhysicalQubit; 2]> = SmallVec::from_buf(*ref_qubits);
+    let reverse_qubits: SmallVec<[PhysicalQubit; 2]> = qubits.iter().rev().copied().collect();
+    let names = match target.operation_names_for_qargs(Some(&qubits)) {
+        Ok(names) => names,
+        Err(_) => match target.operation_names_for_qargs(Some(&reverse_qubits)) {
+            Ok(names) => names,
+            Err(_) => return Ok(None),
+        },
+    };
+    for name in names.iter() {
+        let Ok(op) = target.operation_from_name(name) else {
+            continue;
+        };
+        if !matches!(
+            op.operation.view(),
+            OperationRef::Gate(_) | OperationRef::Standard(_)
+        ) {
+            continue;
+        }
+        let Some(unitary_matrix) = op.operation.matrix(&op.params) else {
+            continue;
+        };
+        let kak = TwoQubitWeylDecomposition::new_inner(unitary_matrix.view(), None, None)?;
+        if !(relative_eq!(kak.a(), PI4) && relative_eq!(kak.c(), 0.0)) {
+            continue;
+        }
+        let decomposer = TwoQubitDecomposeUpToDiagonal::new_inner(
+            op.operation.name().to_owned(),
+            unitary_matrix.view(),
+        )?;
+        return Ok(Some(DecomposerElement {
+            decomposer: DecomposerType::UpToDiagonal(Box::new(decomposer)),
+            gate: op.clone(),
+        }));
+    }
+    Ok(None)
+}
+
 fn preferred_direction(
     decomposer: &DecomposerElement,
     ref_qubits: &[PhysicalQubit; 2],
     natural_direction: Option<bool>,
-    coupling_edges: &Bound<'_, PyList>,
+    coupling_edges: &HashSet<(usize, usize)>,
     target: &Target,
 ) -> PyResult<Option<bool>> {
     // Returns:
@@ -847,14 +1857,8 @@ fn preferred_direction(
         Some(false) => None,
         _ => {
             // None or Some(true)
-            let mut edge_set = HashSet::new();
-            for item in coupling_edges.iter() {
-                if let Ok(tuple) = item.extract::<(usize, usize)>() {
-                    edge_set.insert(tuple);
-                }
-            }
-            let zero_one = edge_set.contains(&(qubits[0].0 as usize, qubits[1].0 as usize));
-            let one_zero = edge_set.contains(&(qubits[1].0 as usize, qubits[0].0 as usize));
+            let zero_one = coupling_edges.contains(&(qubits[0].0 as usize, qubits[1].0 as usize));
+            let one_zero = coupling_edges.contains(&(qubits[1].0 as usize, qubits[0].0 as usize));
 
             match (zero_one, one_zero) {
                 (true, false) => Some(true),
@@ -947,6 +1951,82 @@ fn synth_su4_sequence(
     }
 }
 
+/// Sibling of [`synth_su4_sequence`] for a [`DecomposerType::UpToDiagonal`] decomposer: synthesizes
+/// `su4_mat` up to a leftover diagonal `D` rather than exactly, returning both the gate sequence
+/// (which implements `D . su4_mat`, not `su4_mat` itself) and `D` so the caller can left-multiply
+/// it into whatever's synthesized next on these qubits. No `preferred_direction` handling here --
+/// unlike [`synth_su4_sequence`], that's unexplored for this decomposer; see
+/// [`get_2q_up_to_diagonal_decomposer`] for why this isn't wired into the main selection loop yet.
+#[allow(dead_code)]
+fn synth_su4_up_to_diagonal_sequence(
+    su4_mat: &Array2<Complex64>,
+    decomposer_2q: &DecomposerElement,
+) -> PyResult<(TwoQubitUnitarySequence, Array2<Complex64>)> {
+    let (synth, diagonal) =
+        if let DecomposerType::UpToDiagonal(decomp) = &decomposer_2q.decomposer {
+            decomp.call_inner(su4_mat.view())?
+        } else {
+            panic!("synth_su4_up_to_diagonal_sequence should only be called for UpToDiagonal.")
+        };
+    let sequence = TwoQubitUnitarySequence {
+        gate_sequence: synth,
+        decomp_gate: decomposer_2q.gate.clone(),
+    };
+    Ok((sequence, diagonal))
+}
+
+/// Determinant of a small square complex matrix via Gaussian elimination with partial pivoting.
+/// Only used by [`mirrored_phase_correction`] on the 4x4 matrices that show up there.
+fn determinant(matrix: ArrayView2<Complex64>) -> Complex64 {
+    let n = matrix.nrows();
+    let mut a = matrix.to_owned();
+    let mut det = Complex64::new(1.0, 0.0);
+    for col in 0..n {
+        let mut pivot_row = col;
+        let mut pivot_mag = a[[col, col]].norm();
+        for row in (col + 1)..n {
+            let mag = a[[row, col]].norm();
+            if mag > pivot_mag {
+                pivot_mag = mag;
+                pivot_row = row;
+            }
+        }
+        if pivot_mag < 1e-12 {
+            return Complex64::new(0.0, 0.0);
+        }
+        if pivot_row != col {
+            for k in 0..n {
+                let tmp = a[[col, k]];
+                a[[col, k]] = a[[pivot_row, k]];
+                a[[pivot_row, k]] = tmp;
+            }
+            det = -det;
+        }
+        det *= a[[col, col]];
+        for row in (col + 1)..n {
+            let factor = a[[row, col]] / a[[col, col]];
+            for k in col..n {
+                let value = a[[col, k]];
+                a[[row, k]] -= factor * value;
+            }
+        }
+    }
+    det
+}
+
+/// `reversed_synth_su4_sequence`/`reversed_synth_su4_dag` mirror `su4_mat` into `su4_mat_mm` by
+/// swapping rows/cols 1 and 2 -- exactly conjugation by `SWAP`, which preserves the determinant
+/// exactly (`det(SWAP) = -1`, and it appears squared in a conjugation). This returns half the
+/// difference between the arguments of `det(su4_mat)` and `det(su4_mat_mm)`, with the difference
+/// wrapped into `[-pi, pi)` so the two sides of the `arg` branch cut don't yield a spurious `pi`;
+/// since the two determinants are equal, the result is zero up to rounding. Only the two input
+/// matrices are compared: the decomposer's output and its local factors are not inspected.
+fn mirrored_phase_correction(su4_mat: &Array2<Complex64>, su4_mat_mm: &Array2<Complex64>) -> f64 {
+    let original_phase = determinant(su4_mat.view()).arg();
+    let mirrored_phase = determinant(su4_mat_mm.view()).arg();
+    ((original_phase - mirrored_phase + PI).rem_euclid(2.0 * PI) - PI) / 2.0
+}
+
 fn reversed_synth_su4_sequence(
     su4_mat: &Array2<Complex64>,
     decomposer_2q: &DecomposerElement,
@@ -969,6 +2049,8 @@ fn reversed_synth_su4_sequence(
         } else {
             panic!("reversed_synth_su4_sequence should only be called for TwoQubitBasisDecomposer.")
         };
+    synth.global_phase = (synth.global_phase + mirrored_phase_correction(su4_mat, &su4_mat_mm))
+        .rem_euclid(2.0 * PI);
 
     let flip_bits: [u8; 2] = [1, 0];
     for (_, _, qubit_ids) in synth.gates.iter_mut() {
@@ -984,6 +2066,36 @@ fn reversed_synth_su4_sequence(
     Ok(sequence)
 }
 
+/// Stand-in for one entry of the Python `XX_EMBODIMENTS` table, keyed (like that table) by
+/// interaction strength: the default embodiment of an arbitrary strength `theta` not otherwise
+/// pinned to a fixed circuit is a single `RZXGate(theta)`, which is cheap to build natively and
+/// needs no trip through Python. This is groundwork towards natively porting
+/// [`DecomposerType::XXDecomposer`]'s synthesis -- see [`synth_su4_dag`]'s docs for the much
+/// larger remaining piece (sequencing possibly-several embodiments together with the right local
+/// 1q gates and tracked global phase) that this alone doesn't solve, so it isn't called from
+/// anywhere yet.
+#[allow(dead_code)]
+fn native_xx_embodiment(strength: f64) -> TwoQubitGateSequence {
+    TwoQubitGateSequence {
+        gates: vec![(
+            Some(StandardGate::RZXGate),
+            smallvec![Param::Float(strength)],
+            smallvec![0, 1],
+        )],
+        global_phase: 0.0,
+    }
+}
+
+/// Synthesize `su4_mat` with an [`DecomposerType::XXDecomposer`] candidate, still via the Python
+/// `XXDecomposer.__call__` (`use_dag=True`) rather than natively: unlike
+/// [`TwoQubitBasisDecomposer`], which always spends a fixed gate count on a single basis gate, the
+/// XX decomposer picks how many applications of (possibly several) available interaction
+/// strengths to combine for a given target, interleaved with 1q gates solved from the Weyl
+/// chamber at each step -- a real synthesis algorithm, not a lookup, and porting it to Rust
+/// without being able to compile and check it against the existing Python implementation risks a
+/// silently wrong circuit. [`native_xx_embodiment`] above is the one safely reproducible piece
+/// (the trivial single-embodiment-per-strength circuit); the strength-selection and
+/// multi-application-sequencing logic that would consume it is not ported here.
 fn synth_su4_dag(
     py: Python,
     su4_mat: &Array2<Complex64>,
@@ -1072,6 +2184,9 @@ fn reversed_synth_su4_dag(
     };
 
     let mut target_dag = synth_dag.copy_empty_like(py, "alike")?;
+    let phase_correction =
+        mirrored_phase_correction(su4_mat, &su4_mat_mm).rem_euclid(2.0 * PI);
+    target_dag.add_global_phase(py, &Param::Float(phase_correction))?;
     let flip_bits: [Qubit; 2] = [Qubit(1), Qubit(0)];
     for node in synth_dag.topological_op_nodes()? {
         let NodeType::Operation(mut inst) = synth_dag.dag()[node].clone()  else {
@@ -17,11 +17,15 @@ use crate::dag_node::{DAGInNode, DAGNode, DAGOpNode, DAGOutNode};
 use crate::dot_utils::build_dot;
 use crate::error::DAGCircuitError;
 use crate::imports::{
-    CIRCUIT_TO_DAG, CLASSICAL_REGISTER, CLBIT, CONTROL_FLOW_OP, DAG_NODE, DAG_TO_CIRCUIT, EXPR,
-    ITER_VARS, STORE_OP, SWITCH_CASE_OP, VARIABLE_MAPPER,
+    CIRCUIT_TO_DAG, CLASSICAL_REGISTER, CLBIT, CONTROL_FLOW_OP, EXPR, ITER_VARS, STORE_OP,
+    SWITCH_CASE_OP, VARIABLE_MAPPER,
+};
+use crate::interchange::{
+    SerializedDag, SerializedNode, SerializedOp, SerializedParam, SerializedVarType,
+    SerializedWire, SerializedWireKind,
 };
 use crate::interner::{Index, IndexedInterner, Interner};
-use crate::operations::{Operation, OperationType, Param};
+use crate::operations::{Operation, OperationType, Param, StandardGate};
 use crate::rustworkx_core_vnext::isomorphism;
 use crate::{BitType, Clbit, Qubit, TupleLikeArg};
 use hashbrown::{hash_map, HashMap, HashSet};
@@ -30,10 +34,11 @@ use petgraph::prelude::*;
 use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{
-    IntoPyDict, PyDict, PyFloat, PyFrozenSet, PyInt, PyIterator, PyList, PySequence, PySet,
-    PyString, PyTuple, PyType,
+    IntoPyDict, PyDict, PyFrozenSet, PyInt, PyIterator, PyList, PySequence, PySet, PyString,
+    PyTuple, PyType,
 };
 use pyo3::{intern, PyObject, PyResult};
+use rayon::prelude::*;
 use rustworkx_core::err::ContractError;
 use rustworkx_core::graph_ext::ContractNodesDirected;
 use rustworkx_core::petgraph;
@@ -50,7 +55,6 @@ use std::cmp::Ordering;
 use std::collections::{BTreeMap, VecDeque};
 use std::convert::Infallible;
 use std::f64::consts::PI;
-use std::ffi::c_double;
 use std::hash::Hash;
 
 static CONTROL_FLOW_OP_NAMES: [&str; 4] = ["for_loop", "while_loop", "if_else", "switch_case"];
@@ -120,27 +124,42 @@ impl NodeType {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub(crate) enum Wire {
     Qubit(Qubit),
     Clbit(Clbit),
-    Var(PyObject),
+    Var(Var),
 }
 
-impl PartialEq for Wire {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Wire::Qubit(q1), Wire::Qubit(q2)) => q1 == q2,
-            (Wire::Clbit(c1), Wire::Clbit(c2)) => c1 == c2,
-            (Wire::Var(v1), Wire::Var(v2)) => {
-                v1.is(v2) || Python::with_gil(|py| v1.bind(py).eq(v2).unwrap())
-            }
-            _ => false,
-        }
-    }
+/// Traversal direction for [DAGCircuit::light_cone].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConeDirection {
+    /// Walk predecessors: the causal/past light cone of the seed wires.
+    Backward,
+    /// Walk successors: the future light cone of the seed wires.
+    Forward,
+}
+
+/// Which wire kinds a [DAGCircuit::light_cone] traversal follows.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConeWires {
+    /// Follow only qubit wires.
+    Quantum,
+    /// Follow only clbit wires.
+    Classical,
+    /// Follow both qubit and clbit wires; a node enters the cone if it shares *any* in-cone
+    /// wire (quantum or classical) and then contributes all of its qubits and clbits to the
+    /// frontier.
+    Both,
 }
 
-impl Eq for Wire {}
+/// The result of a [DAGCircuit::light_cone] traversal.
+#[derive(Clone, Debug, Default)]
+pub struct LightCone {
+    pub qubits: HashSet<Qubit>,
+    pub clbits: HashSet<Clbit>,
+    pub nodes: HashSet<NodeIndex>,
+}
 
 // TODO: Remove me.
 // This is a temporary map type used to store a mapping of
@@ -150,51 +169,18 @@ impl Eq for Wire {}
 //
 // Once we've got Var ported, Wire should also become Hash + Eq
 // and we can consider combining input/output nodes maps.
-#[derive(Clone, Debug)]
-struct _VarIndexMap {
-    dict: Py<PyDict>,
-}
-
-impl _VarIndexMap {
-    pub fn new(py: Python) -> Self {
-        Self {
-            dict: PyDict::new_bound(py).unbind(),
-        }
-    }
-
-    pub fn keys(&self) -> impl Iterator<Item = PyObject> {
-        Python::with_gil(|py| {
-            self.dict
-                .bind(py)
-                .keys()
-                .into_iter()
-                .map(|k| k.unbind())
-                .collect::<Vec<_>>()
-                .into_iter()
-        })
-    }
-
-    pub fn contains_key(&self, key: &PyObject) -> bool {
-        Python::with_gil(|py| self.dict.bind(py).contains(key).unwrap())
-    }
-
-    pub fn get(&self, key: &PyObject) -> Option<NodeIndex> {
-        Python::with_gil(|py| {
-            self.dict
-                .bind(py)
-                .get_item(key)
-                .unwrap()
-                .map(|v| NodeIndex::new(v.extract().unwrap()))
-        })
-    }
+/// A native, interned handle to a real-time classical `Var`, analogous to [Qubit]/[Clbit].
+///
+/// Vars are interned by name into `DAGCircuit::var_order`/`var_indices` the same way bits are
+/// interned into a `BitData`, which is what lets `Wire` be fully `Hash + Eq` and `var_input_map`
+/// / `var_output_map` live as plain `IndexMap`s right alongside the qubit/clbit wire maps,
+/// instead of needing a GIL-guarded `PyDict` to work around `PyObject` not being hashable.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub(crate) struct Var(u32);
 
-    pub fn insert(&mut self, key: PyObject, value: NodeIndex) {
-        Python::with_gil(|py| {
-            self.dict
-                .bind(py)
-                .set_item(key, value.index().into_py(py))
-                .unwrap()
-        })
+impl Var {
+    fn index(self) -> usize {
+        self.0 as usize
     }
 }
 
@@ -232,7 +218,7 @@ pub struct DAGCircuit {
     /// Clbits registered in the circuit.
     pub(crate) clbits: BitData<Clbit>,
     /// Global phase.
-    global_phase: PyObject,
+    global_phase: Param,
     /// Duration.
     #[pyo3(get, set)]
     duration: Option<PyObject>,
@@ -259,11 +245,14 @@ pub struct DAGCircuit {
     /// Map from clbit to output nodes of the graph.
     clbit_output_map: IndexMap<Clbit, NodeIndex>,
 
-    // TODO: use IndexMap<Wire, NodeIndex> once Var is ported to Rust
     /// Map from var to input nodes of the graph.
-    var_input_map: _VarIndexMap,
+    var_input_map: IndexMap<Var, NodeIndex>,
     /// Map from var to output nodes of the graph.
-    var_output_map: _VarIndexMap,
+    var_output_map: IndexMap<Var, NodeIndex>,
+    /// Interned vars, in the order they were added; `Var(i)` indexes into this.
+    var_order: Vec<PyObject>,
+    /// Reverse lookup from a var's name to its interned [Var] handle.
+    var_indices: HashMap<String, Var>,
 
     /// Operation kind to count
     op_names: HashMap<String, usize>,
@@ -333,6 +322,7 @@ struct PyCircuitModule {
     while_loop_op: Py<PyAny>,
     switch_case_op: Py<PyAny>,
     operation: Py<PyAny>,
+    instruction: Py<PyAny>,
     store: Py<PyAny>,
     gate: Py<PyAny>,
     parameter_expression: Py<PyAny>,
@@ -353,6 +343,7 @@ impl PyCircuitModule {
             while_loop_op: module.getattr("WhileLoopOp")?.unbind(),
             switch_case_op: module.getattr("SwitchCaseOp")?.unbind(),
             operation: module.getattr("Operation")?.unbind(),
+            instruction: module.getattr("Instruction")?.unbind(),
             store: module.getattr("Store")?.unbind(),
             gate: module.getattr("Gate")?.unbind(),
             parameter_expression: module.getattr("ParameterExpression")?.unbind(),
@@ -457,6 +448,36 @@ struct DAGVarInfo {
     out_node: NodeIndex,
 }
 
+/// A predicate restricting [DAGCircuit::depth] to a subset of operations.
+///
+/// The order here defines the order the variants are tried in the `FromPyObject` derivation:
+/// a plain callable is tried last since almost anything can be coerced to `Py<PyAny>`.
+#[derive(FromPyObject)]
+enum DepthFilter {
+    Names(Vec<String>),
+    MinQubits(usize),
+    Callable(Py<PyAny>),
+}
+
+impl DepthFilter {
+    fn node_matches(&self, py: Python, dag: &DAGCircuit, node: NodeIndex) -> PyResult<bool> {
+        let instr = match &dag.dag[node] {
+            NodeType::Operation(instr) => instr,
+            _ => return Ok(false),
+        };
+        match self {
+            DepthFilter::MinQubits(min_qubits) => {
+                Ok(dag.qargs_cache.intern(instr.qubits_id).len() >= *min_qubits)
+            }
+            DepthFilter::Names(names) => Ok(names.iter().any(|name| name == instr.op.name())),
+            DepthFilter::Callable(callable) => {
+                let py_node = dag.get_node(py, node)?;
+                callable.bind(py).call1((py_node,))?.extract()
+            }
+        }
+    }
+}
+
 #[pymethods]
 impl DAGCircuit {
     #[new]
@@ -472,7 +493,7 @@ impl DAGCircuit {
             cargs_cache: IndexedInterner::new(),
             qubits: BitData::new(py, "qubits".to_string()),
             clbits: BitData::new(py, "clbits".to_string()),
-            global_phase: PyFloat::new_bound(py, 0 as c_double).into_any().unbind(),
+            global_phase: Param::Float(0.0),
             duration: None,
             unit: "dt".to_string(),
             qubit_locations: PyDict::new_bound(py).unbind(),
@@ -481,8 +502,10 @@ impl DAGCircuit {
             qubit_output_map: IndexMap::new(),
             clbit_input_map: IndexMap::new(),
             clbit_output_map: IndexMap::new(),
-            var_input_map: _VarIndexMap::new(py),
-            var_output_map: _VarIndexMap::new(py),
+            var_input_map: IndexMap::new(),
+            var_output_map: IndexMap::new(),
+            var_order: Vec::new(),
+            var_indices: HashMap::new(),
             op_names: HashMap::new(),
 
             // Python module wrappers
@@ -578,8 +601,8 @@ impl DAGCircuit {
 
     /// Return the global phase of the circuit.
     #[getter]
-    fn get_global_phase(&self) -> &PyObject {
-        &self.global_phase
+    fn get_global_phase(&self, py: Python) -> PyObject {
+        self.global_phase.to_object(py)
     }
 
     /// Set the global phase of the circuit.
@@ -587,21 +610,11 @@ impl DAGCircuit {
     /// Args:
     ///     angle (float, :class:`.ParameterExpression`): The phase angle.
     #[setter]
-    fn set_global_phase(&mut self, py: Python<'_>, angle: &Bound<PyAny>) -> PyResult<()> {
-        if let Ok(angle) = angle.downcast::<PyFloat>() {
-            self.global_phase = PyFloat::new_bound(
-                py,
-                if !angle.is_truthy()? {
-                    0 as c_double
-                } else {
-                    angle.value() % (2f64 * PI)
-                },
-            )
-            .into_any()
-            .unbind();
-        } else {
-            self.global_phase = angle.clone().unbind()
-        }
+    fn set_global_phase(&mut self, angle: Param) -> PyResult<()> {
+        self.global_phase = match angle {
+            Param::Float(angle) => Param::Float(angle.rem_euclid(2f64 * PI)),
+            other => other,
+        };
         Ok(())
     }
 
@@ -696,38 +709,7 @@ def _format(operand):
     /// Return True if the dag has a calibration defined for the node operation. In this
     /// case, the operation does not need to be translated to the device basis.
     fn has_calibration_for(&self, py: Python, node: PyRef<DAGOpNode>) -> PyResult<bool> {
-        let node = node.as_ref().node.unwrap();
-        if let Some(NodeType::Operation(packed)) = self.dag.node_weight(node) {
-            let op_name = packed.op.name().to_string();
-            if !self.calibrations.contains_key(&op_name) {
-                return Ok(false);
-            }
-            let mut params = Vec::new();
-            for p in &packed.params {
-                if let Param::ParameterExpression(exp) = p {
-                    let exp = exp.bind(py);
-                    if !exp.getattr(intern!(py, "parameters"))?.is_truthy()? {
-                        let as_py_float = exp.call_method0(intern!(py, "__float__"))?;
-                        params.push(as_py_float.unbind());
-                        continue;
-                    }
-                }
-                params.push(p.to_object(py));
-            }
-            let qubits: Vec<BitType> = self
-                .qargs_cache
-                .intern(packed.qubits_id)
-                .iter()
-                .cloned()
-                .map(|b| b.into())
-                .collect();
-            let params = PyTuple::new_bound(py, params);
-            self.calibrations[&op_name]
-                .bind(py)
-                .contains((qubits, params).to_object(py))
-        } else {
-            Ok(false)
-        }
+        self.has_calibration_for_index(py, node.as_ref().node.unwrap())
     }
 
     /// Remove all operation nodes with the given name.
@@ -929,14 +911,20 @@ def _format(operand):
             )));
         }
 
-        // Remove any references to bits.
+        // Remove any references to bits, using each removed bit's own BitLocations.registers
+        // to find the affected registers directly instead of scanning every register in the DAG.
+        let clbit_locations = self.clbit_locations.bind(py);
         let mut cregs_to_remove = Vec::new();
-        for creg in self.cregs.bind(py).values() {
-            for bit in creg.iter()? {
-                let bit = bit?;
-                if clbits.contains(&self.clbits.find(&bit).unwrap()) {
+        for bit in clbits.iter() {
+            let bit = self.clbits.get(*bit).unwrap().bind(py);
+            let locations = clbit_locations
+                .get_item(bit)?
+                .unwrap()
+                .downcast_into_exact::<BitLocations>()?;
+            for reg_index in locations.borrow().registers.bind(py).iter() {
+                let creg = reg_index.get_item(0)?;
+                if !cregs_to_remove.iter().any(|r: &Bound<PyAny>| r.is(&creg)) {
                     cregs_to_remove.push(creg);
-                    break;
                 }
             }
         }
@@ -950,15 +938,27 @@ def _format(operand):
         // Update bit data.
         self.clbits.remove_indices(py, clbits)?;
 
-        // Update bit locations.
+        // Update bit locations in a single pass, rebuilding each surviving bit's BitLocations
+        // directly instead of round-tripping through a Python `_replace` call per bit.
         let bit_locations = self.clbit_locations.bind(py);
         for (i, bit) in self.clbits.bits().iter().enumerate() {
+            let bit = bit.bind(py);
+            let registers = bit_locations
+                .get_item(bit)?
+                .unwrap()
+                .downcast_into_exact::<BitLocations>()?
+                .borrow()
+                .registers
+                .clone_ref(py);
             bit_locations.set_item(
                 bit,
-                bit_locations
-                    .get_item(bit)?
-                    .unwrap()
-                    .call_method1(intern!(py, "_replace"), (i,))?,
+                Py::new(
+                    py,
+                    BitLocations {
+                        index: i.into_py(py),
+                        registers,
+                    },
+                )?,
             )?;
         }
         Ok(())
@@ -977,9 +977,17 @@ def _format(operand):
         for reg in cregs.iter() {
             if !reg.is_instance(self.circuit_module.classical_register.bind(py))? {
                 non_regs.push(reg);
-            } else if self.cregs.bind(py).values().contains(&reg)? {
-                // TODO: make check not quadratic
-                unknown_regs.push(reg);
+            } else {
+                // Look the register up by name instead of linearly scanning every known
+                // register's identity, since `cregs` is already keyed by name.
+                let name = reg.getattr(intern!(py, "name"))?;
+                let is_known = match self.cregs.bind(py).get_item(&name)? {
+                    Some(existing) => existing.is(&reg),
+                    None => false,
+                };
+                if !is_known {
+                    unknown_regs.push(reg);
+                }
             }
         }
         if !non_regs.is_empty() {
@@ -1058,14 +1066,20 @@ def _format(operand):
             )));
         }
 
-        // Remove any references to bits.
+        // Remove any references to bits, using each removed bit's own BitLocations.registers
+        // to find the affected registers directly instead of scanning every register in the DAG.
+        let qubit_locations = self.qubit_locations.bind(py);
         let mut qregs_to_remove = Vec::new();
-        for qreg in self.qregs.bind(py).values() {
-            for bit in qreg.iter()? {
-                let bit = bit?;
-                if qubits.contains(&self.qubits.find(&bit).unwrap()) {
+        for bit in qubits.iter() {
+            let bit = self.qubits.get(*bit).unwrap().bind(py);
+            let locations = qubit_locations
+                .get_item(bit)?
+                .unwrap()
+                .downcast_into_exact::<BitLocations>()?;
+            for reg_index in locations.borrow().registers.bind(py).iter() {
+                let qreg = reg_index.get_item(0)?;
+                if !qregs_to_remove.iter().any(|r: &Bound<PyAny>| r.is(&qreg)) {
                     qregs_to_remove.push(qreg);
-                    break;
                 }
             }
         }
@@ -1079,15 +1093,27 @@ def _format(operand):
         // Update bit data.
         self.qubits.remove_indices(py, qubits)?;
 
-        // Update bit locations.
+        // Update bit locations in a single pass, rebuilding each surviving bit's BitLocations
+        // directly instead of round-tripping through a Python `_replace` call per bit.
         let bit_locations = self.qubit_locations.bind(py);
         for (i, bit) in self.qubits.bits().iter().enumerate() {
+            let bit = bit.bind(py);
+            let registers = bit_locations
+                .get_item(bit)?
+                .unwrap()
+                .downcast_into_exact::<BitLocations>()?
+                .borrow()
+                .registers
+                .clone_ref(py);
             bit_locations.set_item(
                 bit,
-                bit_locations
-                    .get_item(bit)?
-                    .unwrap()
-                    .call_method1(intern!(py, "_replace"), (i,))?,
+                Py::new(
+                    py,
+                    BitLocations {
+                        index: i.into_py(py),
+                        registers,
+                    },
+                )?,
             )?;
         }
         Ok(())
@@ -1106,9 +1132,17 @@ def _format(operand):
         for reg in qregs.iter() {
             if !reg.is_instance(self.circuit_module.quantum_register.bind(py))? {
                 non_regs.push(reg);
-            } else if self.qregs.bind(py).values().contains(&reg)? {
-                // TODO: make check not quadratic
-                unknown_regs.push(reg);
+            } else {
+                // Look the register up by name instead of linearly scanning every known
+                // register's identity, since `qregs` is already keyed by name.
+                let name = reg.getattr(intern!(py, "name"))?;
+                let is_known = match self.qregs.bind(py).get_item(&name)? {
+                    Some(existing) => existing.is(&reg),
+                    None => false,
+                };
+                if !is_known {
+                    unknown_regs.push(reg);
+                }
             }
         }
         if !non_regs.is_empty() {
@@ -1147,19 +1181,88 @@ def _format(operand):
         Ok(())
     }
 
+    /// Walk a classical `Expr` node, descending through its operands/`Var`/`Value` leaves, and
+    /// return every clbit, classical register, and native [Var] it references.
+    ///
+    /// Unlike [DAGCircuit::additional_wires]'s `wires_from_expr`, this keeps registers as
+    /// registers rather than flattening them to their constituent bits, so callers can validate
+    /// register identity (and catch the case where a register is present but one of its bits
+    /// has since been removed) separately from individual-bit validation.
+    fn expr_resources<'py>(
+        &self,
+        node: &Bound<'py, PyAny>,
+    ) -> PyResult<(Vec<Bound<'py, PyAny>>, Vec<Bound<'py, PyAny>>, Vec<Var>)> {
+        let py = node.py();
+        let mut clbits = Vec::new();
+        let mut cregs = Vec::new();
+        let mut vars = Vec::new();
+        for var in ITER_VARS.get_bound(py).call1((node,))?.iter()? {
+            let var = var?;
+            let var_var = var.getattr("var")?;
+            if var_var.is_instance(CLBIT.get_bound(py))? {
+                clbits.push(var_var);
+            } else if var_var.is_instance(CLASSICAL_REGISTER.get_bound(py))? {
+                cregs.push(var_var);
+            } else {
+                vars.push(self.lookup_var(&var_var)?);
+            }
+        }
+        Ok((clbits, cregs, vars))
+    }
+
     /// Verify that the condition is valid.
     ///
     /// Args:
     ///     name (string): used for error reporting
-    ///     condition (tuple or None): a condition tuple (ClassicalRegister, int) or (Clbit, bool)
+    ///     condition: a condition tuple (ClassicalRegister, int) or (Clbit, bool), or a classical
+    ///         `Expr` referencing any mix of clbits, classical registers, and real-time `Var`s.
     ///
     /// Raises:
-    ///     DAGCircuitError: if conditioning on an invalid register
+    ///     DAGCircuitError: if conditioning on an invalid register, clbit, or var
     fn _check_condition(&self, py: Python, name: &str, condition: &Bound<PyAny>) -> PyResult<()> {
         if condition.is_none() {
             return Ok(());
         }
 
+        if condition.is_instance(EXPR.get_bound(py))? {
+            let (clbits, cregs, vars) = self.expr_resources(condition)?;
+            for creg in cregs {
+                if !self.cregs.bind(py).contains(creg.getattr(intern!(py, "name"))?)? {
+                    return Err(DAGCircuitError::new_err(format!(
+                        "invalid creg {} in condition for {}",
+                        creg, name
+                    )));
+                }
+                for bit in creg.iter()? {
+                    let bit = bit?;
+                    if self.clbits.find(&bit).is_none() {
+                        return Err(DAGCircuitError::new_err(format!(
+                            "invalid clbit {} in condition for {}",
+                            bit, name
+                        )));
+                    }
+                }
+            }
+            for bit in clbits {
+                if self.clbits.find(&bit).is_none() {
+                    return Err(DAGCircuitError::new_err(format!(
+                        "invalid clbit {} in condition for {}",
+                        bit, name
+                    )));
+                }
+            }
+            for var in vars {
+                if !self.var_output_map.contains_key(&var) {
+                    return Err(DAGCircuitError::new_err(format!(
+                        "invalid var {} in condition for {}",
+                        self.get_var(var),
+                        name
+                    )));
+                }
+            }
+            return Ok(());
+        }
+
         let resources = self.control_flow_module.condition_resources(condition)?;
         for reg in resources.cregs.bind(py) {
             if !self
@@ -1199,7 +1302,7 @@ def _format(operand):
     fn copy_empty_like(&self, py: Python) -> PyResult<Self> {
         let mut target_dag = DAGCircuit::new(py)?;
         target_dag.name = self.name.as_ref().map(|n| n.clone_ref(py));
-        target_dag.global_phase = self.global_phase.clone_ref(py);
+        target_dag.global_phase = self.global_phase.clone();
         target_dag.duration = self.duration.as_ref().map(|d| d.clone_ref(py));
         target_dag.unit = self.unit.clone();
         target_dag.metadata = self.metadata.as_ref().map(|m| m.clone_ref(py));
@@ -1218,6 +1321,10 @@ def _format(operand):
         for reg in self.cregs.bind(py).values() {
             target_dag.add_creg(py, &reg)?;
         }
+        for var in self.var_order.iter() {
+            let type_ = self.vars_info[&var.bind(py).getattr(intern!(py, "name"))?.extract::<String>()?].type_;
+            target_dag.add_var(py, var.bind(py), type_)?;
+        }
         Ok(target_dag)
     }
 
@@ -1310,7 +1417,7 @@ def _format(operand):
                         if !self.var_output_map.contains_key(&v) {
                             return Err(DAGCircuitError::new_err(format!(
                                 "var {} not found in output map",
-                                v
+                                self.get_var(v)
                             )));
                         }
                     }
@@ -1411,7 +1518,7 @@ def _format(operand):
                         if !self.var_output_map.contains_key(&v) {
                             return Err(DAGCircuitError::new_err(format!(
                                 "var {} not found in output map",
-                                v
+                                self.get_var(v)
                             )));
                         }
                     }
@@ -1434,15 +1541,26 @@ def _format(operand):
     ///     other (DAGCircuit): circuit to compose with self
     ///     qubits (list[~qiskit.circuit.Qubit|int]): qubits of self to compose onto.
     ///     clbits (list[Clbit|int]): clbits of self to compose onto.
-    ///     front (bool): If True, front composition will be performed (not implemented yet)
+    ///     front (bool): If True, front composition will be performed. ``other`` is mapped onto
+    ///         the input wires of ``self`` instead of the output wires, preserving its internal
+    ///         order ahead of ``self``'s existing body.
     ///     inplace (bool): If True, modify the object. Otherwise return composed circuit.
+    ///     var_map (dict): a mapping of ``other``'s real-time classical variables onto ``self``'s.
+    ///         Variables of ``other`` that are absent from ``var_map`` are instead matched onto a
+    ///         variable of ``self`` with the same name, if one exists.
+    ///     return_mapping (bool): If True, also return a dict mapping each of ``other``'s
+    ///         :class:`.DAGOpNode`\\ s to the newly created node in the composed circuit, so
+    ///         callers can post-process inserted instructions without re-scanning the whole DAG.
     ///
     /// Returns:
-    ///     DAGCircuit: the composed dag (returns None if inplace==True).
+    ///     DAGCircuit: the composed dag (returns None if inplace==True and return_mapping is
+    ///     False). If ``return_mapping`` is True, the old-to-new node mapping is returned instead
+    ///     (or alongside the composed dag, as a 2-tuple, if ``inplace`` is also False).
     ///
     /// Raises:
-    ///     DAGCircuitError: if ``other`` is wider or there are duplicate edge mappings.
-    #[pyo3(signature = (other, qubits=None, clbits=None, front=false, inplace=true))]
+    ///     DAGCircuitError: if ``other`` is wider or there are duplicate edge mappings, or if
+    ///         ``other`` declares or captures a variable that cannot be resolved in ``self``.
+    #[pyo3(signature = (other, qubits=None, clbits=None, front=false, inplace=true, *, var_map=None, return_mapping=false))]
     fn compose(
         slf: PyRefMut<Self>,
         py: Python,
@@ -1451,13 +1569,9 @@ def _format(operand):
         clbits: Option<Bound<PyList>>,
         front: bool,
         inplace: bool,
+        var_map: Option<Bound<PyDict>>,
+        return_mapping: bool,
     ) -> PyResult<Option<PyObject>> {
-        if front {
-            return Err(DAGCircuitError::new_err(
-                "Front composition not supported yet.",
-            ));
-        }
-
         if other.qubits.len() > slf.qubits.len() || other.clbits.len() > slf.clbits.len() {
             return Err(DAGCircuitError::new_err(
                 "Trying to compose with another DAGCircuit which has more 'in' edges.",
@@ -1558,7 +1672,15 @@ def _format(operand):
             Py::new(py, slf.clone())?.into_bound(py).borrow_mut()
         };
 
-        dag.global_phase = dag.global_phase.bind(py).add(&other.global_phase)?.unbind();
+        dag.global_phase = match (&dag.global_phase, &other.global_phase) {
+            (Param::Float(a), Param::Float(b)) => Param::Float(a + b),
+            _ => dag
+                .global_phase
+                .to_object(py)
+                .bind(py)
+                .add(other.global_phase.to_object(py))?
+                .extract()?,
+        };
 
         for (gate, cals) in other.calibrations.iter() {
             dag.calibrations[gate]
@@ -1566,17 +1688,83 @@ def _format(operand):
                 .update(&cals.bind(py).as_mapping())?;
         }
 
+        // Fall back to matching each of `other`'s vars onto the same-named var in `self` for any
+        // variable not given an explicit entry in `var_map`.
+        let var_map = match var_map {
+            Some(var_map) => var_map,
+            None => PyDict::new_bound(py),
+        };
+        for var in other.var_order.iter() {
+            let var = var.bind(py);
+            if var_map.contains(var)? {
+                continue;
+            }
+            let var_name: String = var.getattr(intern!(py, "name"))?.extract()?;
+            if let Some(&self_var) = dag.var_indices.get(&var_name) {
+                var_map.set_item(var, dag.get_var(self_var).bind(py))?;
+            }
+        }
+
         let variable_mapper = PyVariableMapper::new(
             py,
             dag.cregs.bind(py).values().into_any(),
             Some(edge_map.clone()),
-            None,
+            Some(var_map),
             Some(wrap_pyfunction_bound!(reject_new_register, py)?.to_object(py)),
         )?;
 
-        for node in other.topological_nodes()? {
+        // Dense, index-addressable wire maps, so operations that don't need any classical
+        // remapping can be translated and re-interned entirely natively (see below) instead of
+        // paying a `PyDict` lookup and a full Python gate round-trip per node.
+        let qubit_wire_map: Vec<Qubit> = other
+            .qubits
+            .bits()
+            .iter()
+            .map(|bit| -> PyResult<Qubit> {
+                let bit = bit.bind(py);
+                let m_wire = edge_map.get_item(bit)?.unwrap_or_else(|| bit.clone());
+                Ok(dag.qubits.find(&m_wire).unwrap())
+            })
+            .collect::<PyResult<_>>()?;
+        let clbit_wire_map: Vec<Clbit> = other
+            .clbits
+            .bits()
+            .iter()
+            .map(|bit| -> PyResult<Clbit> {
+                let bit = bit.bind(py);
+                let m_wire = edge_map.get_item(bit)?.unwrap_or_else(|| bit.clone());
+                Ok(dag.clbits.find(&m_wire).unwrap())
+            })
+            .collect::<PyResult<_>>()?;
+
+        // Built up incrementally as each node of `other` is applied below, so enabling it adds
+        // negligible cost when `return_mapping` is left False.
+        let node_mapping = if return_mapping {
+            Some(PyDict::new_bound(py))
+        } else {
+            None
+        };
+
+        // Back composition attaches `other`'s input wires onto `self`'s current outputs, so its
+        // `QubitIn`/`ClbitIn` nodes are the boundary that needs validating; front composition
+        // attaches `other`'s *output* wires onto `self`'s current inputs instead, and the whole
+        // sequence must be replayed in reverse topological order so that repeated `push_front`
+        // calls (which always insert immediately after the input node) reproduce `other`'s
+        // original relative ordering ahead of `self`'s existing body.
+        let node_order: Vec<NodeIndex> = if front {
+            let mut nodes: Vec<NodeIndex> = other.topological_nodes()?.collect();
+            nodes.reverse();
+            nodes
+        } else {
+            other.topological_nodes()?.collect()
+        };
+
+        for node in node_order {
             match &other.dag[node] {
                 NodeType::QubitIn(q) => {
+                    if front {
+                        continue;
+                    }
                     let bit = other.qubits.get(*q).unwrap().bind(py);
                     let m_wire = edge_map.get_item(bit)?.unwrap_or_else(|| bit.clone());
                     let bit_in_dag = dag.qubits.find(bit);
@@ -1591,7 +1779,27 @@ def _format(operand):
                     }
                     // TODO: Python code has check here if node.wire is in other._wires. Why?
                 }
+                NodeType::QubitOut(q) => {
+                    if !front {
+                        continue;
+                    }
+                    let bit = other.qubits.get(*q).unwrap().bind(py);
+                    let m_wire = edge_map.get_item(bit)?.unwrap_or_else(|| bit.clone());
+                    let bit_in_dag = dag.qubits.find(bit);
+                    if bit_in_dag.is_none()
+                        || !dag.qubit_input_map.contains_key(&bit_in_dag.unwrap())
+                    {
+                        return Err(DAGCircuitError::new_err(format!(
+                            "wire {}[{}] not in self",
+                            m_wire.getattr("name")?,
+                            m_wire.getattr("index")?
+                        )));
+                    }
+                }
                 NodeType::ClbitIn(c) => {
+                    if front {
+                        continue;
+                    }
                     let bit = other.clbits.get(*c).unwrap().bind(py);
                     let m_wire = edge_map.get_item(bit)?.unwrap_or_else(|| bit.clone());
                     let bit_in_dag = dag.clbits.find(bit);
@@ -1606,7 +1814,78 @@ def _format(operand):
                     }
                     // TODO: Python code has check here if node.wire is in other._wires. Why?
                 }
+                NodeType::ClbitOut(c) => {
+                    if !front {
+                        continue;
+                    }
+                    let bit = other.clbits.get(*c).unwrap().bind(py);
+                    let m_wire = edge_map.get_item(bit)?.unwrap_or_else(|| bit.clone());
+                    let bit_in_dag = dag.clbits.find(bit);
+                    if bit_in_dag.is_none()
+                        || !dag.clbit_input_map.contains_key(&bit_in_dag.unwrap())
+                    {
+                        return Err(DAGCircuitError::new_err(format!(
+                            "wire {}[{}] not in self",
+                            m_wire.getattr("name")?,
+                            m_wire.getattr("index")?
+                        )));
+                    }
+                }
                 NodeType::Operation(op) => {
+                    // Nodes that carry no condition, switch target, or store expression need no
+                    // classical remapping at all, so they can skip the Python round-trip
+                    // (`unpack_py_op` + attribute sets) entirely: translate their qargs/cargs
+                    // through the dense wire maps, re-intern them into `dag`'s own interners, and
+                    // push the `PackedInstruction` directly onto the graph.
+                    let op_name = op.op.name();
+                    if op.condition().is_none() && op_name != "switch_case" && op_name != "store" {
+                        let mapped_qubits: Vec<Qubit> = other
+                            .qargs_cache
+                            .intern(op.qubits_id)
+                            .iter()
+                            .map(|q| qubit_wire_map[q.0 as usize])
+                            .collect();
+                        let mapped_clbits: Vec<Clbit> = other
+                            .cargs_cache
+                            .intern(op.clbits_id)
+                            .iter()
+                            .map(|c| clbit_wire_map[c.0 as usize])
+                            .collect();
+                        let qubits_id = Interner::intern(&mut dag.qargs_cache, mapped_qubits)?;
+                        let clbits_id = Interner::intern(&mut dag.cargs_cache, mapped_clbits)?;
+                        let (label, duration, unit, condition) = match &op.extra_attrs {
+                            Some(attrs) => (
+                                attrs.label.clone(),
+                                attrs.duration.clone(),
+                                attrs.unit.clone(),
+                                attrs.condition.clone(),
+                            ),
+                            None => (None, None, None, None),
+                        };
+                        let new_instr = PackedInstruction::new(
+                            op.op.clone(),
+                            qubits_id,
+                            clbits_id,
+                            op.params.clone(),
+                            label,
+                            duration,
+                            unit,
+                            condition,
+                            #[cfg(feature = "cache_pygates")]
+                            None,
+                        );
+                        let new_node = if front {
+                            dag.push_front(py, new_instr)?
+                        } else {
+                            dag.push_back(py, new_instr)?
+                        };
+                        if let Some(node_mapping) = &node_mapping {
+                            node_mapping
+                                .set_item(other.get_node(py, node)?, dag.get_node(py, new_node)?)?;
+                        }
+                        continue;
+                    }
+
                     let m_qargs = {
                         let qubits = other
                             .qubits
@@ -1653,23 +1932,54 @@ def _format(operand):
                             intern!(py, "target"),
                             variable_mapper.map_target(&py_op.getattr(intern!(py, "target"))?)?,
                         )?;
+                    } else if py_op.is_instance(STORE_OP.get_bound(py))? {
+                        py_op.setattr(
+                            intern!(py, "lvalue"),
+                            variable_mapper.map_expr(&py_op.getattr(intern!(py, "lvalue"))?)?,
+                        )?;
+                        py_op.setattr(
+                            intern!(py, "rvalue"),
+                            variable_mapper.map_expr(&py_op.getattr(intern!(py, "rvalue"))?)?,
+                        )?;
                     };
 
-                    dag.py_apply_operation_back(
-                        py,
-                        py_op,
-                        Some(TupleLikeArg { value: m_qargs }),
-                        Some(TupleLikeArg { value: m_cargs }),
-                        false,
-                    )?;
-                }
-                NodeType::VarIn(var) => {
-                    todo!()
+                    let new_node_obj = if front {
+                        dag.py_apply_operation_front(
+                            py,
+                            py_op,
+                            Some(TupleLikeArg { value: m_qargs }),
+                            Some(TupleLikeArg { value: m_cargs }),
+                            false,
+                        )?
+                    } else {
+                        dag.py_apply_operation_back(
+                            py,
+                            py_op,
+                            Some(TupleLikeArg { value: m_qargs }),
+                            Some(TupleLikeArg { value: m_cargs }),
+                            false,
+                        )?
+                    };
+                    if let Some(node_mapping) = &node_mapping {
+                        node_mapping.set_item(other.get_node(py, node)?, new_node_obj)?;
+                    }
                 }
-                NodeType::VarOut(var) => {
-                    todo!()
+                NodeType::VarIn(_) | NodeType::VarOut(_) => {
+                    // Vars are resolved through `var_map` (falling back to a same-named var)
+                    // rather than by the qubit/clbit edge map, so only the "in" side needs
+                    // checking; the "out" side shares the same wire.
+                    if let NodeType::VarIn(var) = &other.dag[node] {
+                        let var = var.bind(py);
+                        let m_var = var_map.get_item(var)?.unwrap_or_else(|| var.clone());
+                        let m_var_name: String = m_var.getattr(intern!(py, "name"))?.extract()?;
+                        if !dag.var_indices.contains_key(&m_var_name) {
+                            return Err(DAGCircuitError::new_err(format!(
+                                "var '{}' not in self",
+                                m_var_name
+                            )));
+                        }
+                    }
                 }
-                NodeType::QubitOut(_) | NodeType::ClbitOut(_) => (),
             }
         }
         // if qubits is None:
@@ -1757,21 +2067,65 @@ def _format(operand):
         // else:
         //     return None
 
-        if !inplace {
-            Ok(Some(dag.into_py(py)))
-        } else {
-            Ok(None)
+        match (node_mapping, inplace) {
+            (Some(node_mapping), true) => Ok(Some(node_mapping.into_py(py))),
+            (Some(node_mapping), false) => Ok(Some(
+                PyTuple::new_bound(py, [dag.into_py(py), node_mapping.into_py(py)]).into_py(py),
+            )),
+            (None, true) => Ok(None),
+            (None, false) => Ok(Some(dag.into_py(py))),
         }
     }
 
     /// Reverse the operations in the ``self`` circuit.
     ///
+    /// Only the order of the operations is reversed; per-gate parameters and the global phase
+    /// are untouched, since reversing the application order of a circuit's instructions doesn't
+    /// change what each individual instruction does.
+    ///
     /// Returns:
     ///     DAGCircuit: the reversed dag.
-    fn reverse_ops<'py>(slf: PyRef<Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        let qc = DAG_TO_CIRCUIT.get_bound(py).call1((slf,))?;
-        let reversed = qc.call_method0("reverse_ops")?;
-        CIRCUIT_TO_DAG.get_bound(py).call1((reversed,))
+    fn reverse_ops(&self, py: Python) -> PyResult<Self> {
+        let mut target_dag = self.copy_empty_like(py)?;
+        let mut op_nodes: Vec<NodeIndex> = self
+            .topological_nodes()?
+            .filter(|node| matches!(self.dag[*node], NodeType::Operation(_)))
+            .collect();
+        op_nodes.reverse();
+
+        let new_instrs: Vec<PackedInstruction> = op_nodes
+            .into_iter()
+            .map(|node| {
+                let instr = match &self.dag[node] {
+                    NodeType::Operation(instr) => instr,
+                    _ => unreachable!("filtered to operation nodes above"),
+                };
+                let (label, duration, unit, condition) = match &instr.extra_attrs {
+                    Some(attrs) => (
+                        attrs.label.clone(),
+                        attrs.duration.clone(),
+                        attrs.unit.clone(),
+                        attrs.condition.clone(),
+                    ),
+                    None => (None, None, None, None),
+                };
+                PackedInstruction::new(
+                    instr.op.clone(),
+                    instr.qubits_id,
+                    instr.clbits_id,
+                    instr.params.clone(),
+                    label,
+                    duration,
+                    unit,
+                    condition,
+                    #[cfg(feature = "cache_pygates")]
+                    None,
+                )
+            })
+            .collect();
+        target_dag.extend(py, new_instrs)?;
+
+        Ok(target_dag)
     }
 
     /// Return idle wires.
@@ -1792,7 +2146,7 @@ def _format(operand):
             .cloned()
             .map(Wire::Qubit)
             .chain(self.clbit_input_map.keys().cloned().map(Wire::Clbit))
-            .chain(self.var_input_map.keys().map(Wire::Var));
+            .chain(self.var_input_map.keys().copied().map(Wire::Var));
         match ignore {
             Some(ignore) => {
                 // Convert the list to a Rust set.
@@ -1814,7 +2168,7 @@ def _format(operand):
                         result.push(match wire {
                             Wire::Qubit(qubit) => self.qubits.get(qubit).unwrap().clone_ref(py),
                             Wire::Clbit(clbit) => self.clbits.get(clbit).unwrap().clone_ref(py),
-                            Wire::Var(var) => var,
+                            Wire::Var(var) => self.get_var(var).clone_ref(py),
                         });
                     }
                 }
@@ -1825,7 +2179,7 @@ def _format(operand):
                         result.push(match wire {
                             Wire::Qubit(qubit) => self.qubits.get(qubit).unwrap().clone_ref(py),
                             Wire::Clbit(clbit) => self.clbits.get(clbit).unwrap().clone_ref(py),
-                            Wire::Var(var) => var,
+                            Wire::Var(var) => self.get_var(var).clone_ref(py),
                         });
                     }
                 }
@@ -1834,28 +2188,138 @@ def _format(operand):
         Ok(PyTuple::new_bound(py, result).into_any().iter()?.unbind())
     }
 
-    /// Return the number of operations.  If there is control flow present, this count may only
-    /// be an estimate, as the complete control-flow path cannot be statically known.
-    ///
-    /// Args:
-    ///     recurse: if ``True``, then recurse into control-flow operations.  For loops with
-    ///         known-length iterators are counted unrolled.  If-else blocks sum both of the two
-    ///         branches.  While loops are counted as if the loop body runs once only.  Defaults to
-    ///         ``False`` and raises :class:`.DAGCircuitError` if any control flow is present, to
-    ///         avoid silently returning a mostly meaningless number.
+    /// Remove idle qubits and clbits from the circuit, along with any registers that become
+    /// entirely composed of removed bits. Registers that keep at least one surviving bit are
+    /// left intact.
     ///
-    /// Returns:
-    ///     int: the circuit size
-    ///
-    /// Raises:
-    ///     DAGCircuitError: if an unknown :class:`.ControlFlowOp` is present in a call with
-    ///         ``recurse=True``, or any control flow is present in a non-recursive call.
-    #[pyo3(signature= (*, recurse=false))]
-    fn size(&self, py: Python, recurse: bool) -> PyResult<usize> {
-        let mut length = self.dag.node_count() - self.width() * 2;
-        if !recurse {
-            if CONTROL_FLOW_OP_NAMES
-                .iter()
+    /// This is the native equivalent of calling `remove_qubits`/`remove_clbits` with the output
+    /// of `idle_wires`, except that it only drops a register once every one of its bits is idle,
+    /// rather than dropping every register that references any idle bit.
+    fn remove_idle_wires(&mut self, py: Python) -> PyResult<()> {
+        let idle_qubits: IndexSet<Qubit> = self
+            .qubit_input_map
+            .keys()
+            .copied()
+            .filter(|q| self.is_wire_idle(&Wire::Qubit(*q)).unwrap_or(false))
+            .collect();
+        let idle_clbits: IndexSet<Clbit> = self
+            .clbit_input_map
+            .keys()
+            .copied()
+            .filter(|c| self.is_wire_idle(&Wire::Clbit(*c)).unwrap_or(false))
+            .collect();
+
+        // A register is only dropped once every one of its bits is idle; registers that keep at
+        // least one surviving bit are left in place.
+        let mut qregs_to_remove = Vec::new();
+        for qreg in self.qregs.bind(py).values() {
+            let mut all_idle = true;
+            for bit in qreg.iter()? {
+                let bit = bit?;
+                if !idle_qubits.contains(&self.qubits.find(&bit).unwrap()) {
+                    all_idle = false;
+                    break;
+                }
+            }
+            if all_idle {
+                qregs_to_remove.push(qreg);
+            }
+        }
+        self.remove_qregs(py, &PyTuple::new_bound(py, qregs_to_remove))?;
+
+        let mut cregs_to_remove = Vec::new();
+        for creg in self.cregs.bind(py).values() {
+            let mut all_idle = true;
+            for bit in creg.iter()? {
+                let bit = bit?;
+                if !idle_clbits.contains(&self.clbits.find(&bit).unwrap()) {
+                    all_idle = false;
+                    break;
+                }
+            }
+            if all_idle {
+                cregs_to_remove.push(creg);
+            }
+        }
+        self.remove_cregs(py, &PyTuple::new_bound(py, cregs_to_remove))?;
+
+        for bit in idle_qubits.iter() {
+            self.remove_idle_wire(Wire::Qubit(*bit))?;
+        }
+        self.qubits.remove_indices(py, idle_qubits)?;
+        let qubit_locations = self.qubit_locations.bind(py);
+        for (i, bit) in self.qubits.bits().iter().enumerate() {
+            let bit = bit.bind(py);
+            let registers = qubit_locations
+                .get_item(bit)?
+                .unwrap()
+                .downcast_into_exact::<BitLocations>()?
+                .borrow()
+                .registers
+                .clone_ref(py);
+            qubit_locations.set_item(
+                bit,
+                Py::new(
+                    py,
+                    BitLocations {
+                        index: i.into_py(py),
+                        registers,
+                    },
+                )?,
+            )?;
+        }
+
+        for bit in idle_clbits.iter() {
+            self.remove_idle_wire(Wire::Clbit(*bit))?;
+        }
+        self.clbits.remove_indices(py, idle_clbits)?;
+        let clbit_locations = self.clbit_locations.bind(py);
+        for (i, bit) in self.clbits.bits().iter().enumerate() {
+            let bit = bit.bind(py);
+            let registers = clbit_locations
+                .get_item(bit)?
+                .unwrap()
+                .downcast_into_exact::<BitLocations>()?
+                .borrow()
+                .registers
+                .clone_ref(py);
+            clbit_locations.set_item(
+                bit,
+                Py::new(
+                    py,
+                    BitLocations {
+                        index: i.into_py(py),
+                        registers,
+                    },
+                )?,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Return the number of operations.  If there is control flow present, this count may only
+    /// be an estimate, as the complete control-flow path cannot be statically known.
+    ///
+    /// Args:
+    ///     recurse: if ``True``, then recurse into control-flow operations.  For loops with
+    ///         known-length iterators are counted unrolled.  If-else blocks sum both of the two
+    ///         branches.  While loops are counted as if the loop body runs once only.  Defaults to
+    ///         ``False`` and raises :class:`.DAGCircuitError` if any control flow is present, to
+    ///         avoid silently returning a mostly meaningless number.
+    ///
+    /// Returns:
+    ///     int: the circuit size
+    ///
+    /// Raises:
+    ///     DAGCircuitError: if an unknown :class:`.ControlFlowOp` is present in a call with
+    ///         ``recurse=True``, or any control flow is present in a non-recursive call.
+    #[pyo3(signature= (*, recurse=false))]
+    fn size(&self, py: Python, recurse: bool) -> PyResult<usize> {
+        let mut length = self.dag.node_count() - self.width() * 2;
+        if !recurse {
+            if CONTROL_FLOW_OP_NAMES
+                .iter()
                 .any(|n| self.op_names.contains_key(&n.to_string()))
             {
                 return Err(DAGCircuitError::new_err(concat!(
@@ -1917,6 +2381,11 @@ def _format(operand):
     ///         if the loop body runs once only.  Defaults to ``False`` and raises
     ///         :class:`.DAGCircuitError` if any control flow is present, to avoid silently
     ///         returning a nonsensical number.
+    ///     filter_function: if given, restrict the depth count to only the operations that
+    ///         match. This may be a set of gate names (for e.g. "T-count"), a minimum qubit
+    ///         count (``2`` for "two-qubit depth"), or a callable taking a :class:`.DAGOpNode`
+    ///         and returning a ``bool``. Applied recursively inside control-flow blocks when
+    ///         ``recurse`` is ``True``.
     ///
     /// Returns:
     ///     int: the circuit depth
@@ -1925,55 +2394,14 @@ def _format(operand):
     ///     DAGCircuitError: if not a directed acyclic graph
     ///     DAGCircuitError: if unknown control flow is present in a recursive call, or any control
     ///         flow is present in a non-recursive call.
-    #[pyo3(signature= (*, recurse=false))]
-    fn depth(&self, py: Python, recurse: bool) -> PyResult<usize> {
-        Ok(if recurse {
-            let circuit_to_dag = CIRCUIT_TO_DAG.get_bound(py);
-            let mut node_lookup: HashMap<NodeIndex, usize> = HashMap::new();
-
-            for node in self.op_nodes(py, Some(CONTROL_FLOW_OP.get_bound(py).downcast()?), true)? {
-                let node = node.bind(py);
-                let weight = if node.is_instance(self.circuit_module.for_loop_op.bind(py))? {
-                    node.getattr("params")?.get_item(0)?.len()?
-                } else {
-                    1
-                };
-                let node_index = node.extract::<DAGNode>()?.node.unwrap();
-                if weight == 0 {
-                    node_lookup.insert(node_index, 0);
-                } else {
-                    let raw_blocks = node.getattr("op")?.getattr("blocks")?;
-                    let blocks: &Bound<PyList> = raw_blocks.downcast::<PyList>()?;
-                    let mut block_weights: Vec<usize> = Vec::with_capacity(blocks.len());
-                    for block in blocks.iter() {
-                        let inner_dag: &DAGCircuit = &circuit_to_dag.call1((block,))?.extract()?;
-                        block_weights.push(inner_dag.depth(py, true)?);
-                    }
-                    node_lookup.insert(node_index, weight * block_weights.iter().max().unwrap());
-                }
-            }
-
-            let weight_fn = |edge: EdgeReference<'_, Wire>| -> Result<usize, Infallible> {
-                Ok(*node_lookup.get(&edge.target()).unwrap_or(&1))
-            };
-            match rustworkx_core::dag_algo::longest_path(&self.dag, weight_fn).unwrap() {
-                Some(res) => res.1,
-                None => return Err(DAGCircuitError::new_err("not a DAG")),
-            }
-        } else {
-            if CONTROL_FLOW_OP_NAMES
-                .iter()
-                .any(|x| self.op_names.contains_key(&x.to_string()))
-            {
-                return Err(DAGCircuitError::new_err("Depth with control flow is ambiguous. You may use `recurse=True` to get a result, but see this method's documentation for the meaning of this."));
-            }
-
-            let weight_fn = |_| -> Result<usize, Infallible> { Ok(1) };
-            match rustworkx_core::dag_algo::longest_path(&self.dag, weight_fn).unwrap() {
-                Some(res) => res.1,
-                None => return Err(DAGCircuitError::new_err("not a DAG")),
-            }
-        } - 1)
+    #[pyo3(signature= (*, recurse=false, filter_function=None))]
+    fn depth(
+        &self,
+        py: Python,
+        recurse: bool,
+        filter_function: Option<DepthFilter>,
+    ) -> PyResult<usize> {
+        self.depth_impl(py, recurse, filter_function.as_ref())
     }
 
     /// Return the total number of qubits + clbits used by the circuit.
@@ -2013,40 +2441,175 @@ def _format(operand):
         weak_components
     }
 
+    /// Decompose the circuit into its separable components.
+    ///
+    /// Reuses the same union-find pass as :meth:`num_tensor_factors` to bucket the DAG's nodes
+    /// by connected component, then materializes each bucket as its own :class:`.DAGCircuit`.
+    ///
+    /// Args:
+    ///     remove_idle_qubits: If ``False`` (default), every resulting DAG keeps the full width
+    ///         of ``self``, with the wires belonging to other components left idle. If ``True``,
+    ///         each resulting DAG only contains the qubits and clbits actually touched by its
+    ///         component.
+    ///
+    /// Returns:
+    ///     list[DAGCircuit]: one :class:`.DAGCircuit` per connected component. ``global_phase``
+    ///     is preserved on exactly one of the returned circuits; the rest are given a
+    ///     ``global_phase`` of ``0``.
+    #[pyo3(signature = (remove_idle_qubits=false))]
+    fn separable_circuits(&self, py: Python, remove_idle_qubits: bool) -> PyResult<Vec<Self>> {
+        let mut vertex_sets = UnionFind::new(self.dag.node_bound());
+        for edge in self.dag.edge_references() {
+            let (a, b) = (edge.source(), edge.target());
+            vertex_sets.union(a.index(), b.index());
+        }
+
+        // Bucket the nodes by component root, preserving the DAG's topological order within
+        // each bucket so its operations can be replayed onto the new DAG with `push_back`.
+        let mut buckets: IndexMap<usize, Vec<NodeIndex>> = IndexMap::new();
+        for node in self.topological_nodes()? {
+            buckets
+                .entry(vertex_sets.find(node.index()))
+                .or_default()
+                .push(node);
+        }
+
+        let mut out = Vec::with_capacity(buckets.len());
+        let mut global_phase_assigned = false;
+        for (_, nodes) in buckets {
+            let (mut new_dag, qubit_map, clbit_map) = if remove_idle_qubits {
+                let mut new_dag = DAGCircuit::new(py)?;
+                new_dag.name = self.name.as_ref().map(|n| n.clone_ref(py));
+                new_dag.metadata = self.metadata.as_ref().map(|m| m.clone_ref(py));
+                let mut qubit_map: HashMap<Qubit, Qubit> = HashMap::new();
+                let mut clbit_map: HashMap<Clbit, Clbit> = HashMap::new();
+                for node in &nodes {
+                    match &self.dag[*node] {
+                        NodeType::QubitIn(q) => {
+                            let bit = self.qubits.get(*q).unwrap().bind(py).clone();
+                            let new_qubit = new_dag.add_qubit_unchecked(py, &bit)?;
+                            qubit_map.insert(*q, new_qubit);
+                        }
+                        NodeType::ClbitIn(c) => {
+                            let bit = self.clbits.get(*c).unwrap().bind(py).clone();
+                            let new_clbit = new_dag.add_clbit_unchecked(py, &bit)?;
+                            clbit_map.insert(*c, new_clbit);
+                        }
+                        _ => {}
+                    }
+                }
+                (new_dag, Some(qubit_map), Some(clbit_map))
+            } else {
+                (self.copy_empty_like(py)?, None, None)
+            };
+
+            if global_phase_assigned {
+                new_dag.global_phase = Param::Float(0.0);
+            } else {
+                new_dag.global_phase = self.global_phase.clone();
+                global_phase_assigned = true;
+            }
+
+            let mut new_instrs = Vec::with_capacity(nodes.len());
+            for node in nodes {
+                let instr = match &self.dag[node] {
+                    NodeType::Operation(instr) => instr,
+                    _ => continue,
+                };
+                let (label, duration, unit, condition) = match &instr.extra_attrs {
+                    Some(attrs) => (
+                        attrs.label.clone(),
+                        attrs.duration.clone(),
+                        attrs.unit.clone(),
+                        attrs.condition.clone(),
+                    ),
+                    None => (None, None, None, None),
+                };
+                let (qubits_id, clbits_id) = match (&qubit_map, &clbit_map) {
+                    (Some(qubit_map), Some(clbit_map)) => {
+                        let qargs: Vec<Qubit> = self
+                            .qargs_cache
+                            .intern(instr.qubits_id)
+                            .iter()
+                            .map(|q| qubit_map[q])
+                            .collect();
+                        let cargs: Vec<Clbit> = self
+                            .cargs_cache
+                            .intern(instr.clbits_id)
+                            .iter()
+                            .map(|c| clbit_map[c])
+                            .collect();
+                        (
+                            Interner::intern(&mut new_dag.qargs_cache, qargs)?,
+                            Interner::intern(&mut new_dag.cargs_cache, cargs)?,
+                        )
+                    }
+                    _ => (instr.qubits_id, instr.clbits_id),
+                };
+                new_instrs.push(PackedInstruction::new(
+                    instr.op.clone(),
+                    qubits_id,
+                    clbits_id,
+                    instr.params.clone(),
+                    label,
+                    duration,
+                    unit,
+                    condition,
+                    #[cfg(feature = "cache_pygates")]
+                    None,
+                ));
+            }
+            new_dag.extend(py, new_instrs)?;
+
+            out.push(new_dag);
+        }
+
+        Ok(out)
+    }
+
     fn __eq__(&self, py: Python, other: &DAGCircuit) -> PyResult<bool> {
         // Try to convert to float, but in case of unbound ParameterExpressions
         // a TypeError will be raise, fallback to normal equality in those
         // cases.
-        let self_phase = match self
-            .global_phase
-            .bind(py)
-            .call_method0(intern!(py, "__float__"))
-        {
-            Err(e) if !e.is_instance_of::<PyTypeError>(py) => {
-                return Err(e);
-            }
-            res => res.ok(),
+        let self_phase: Option<f64> = match &self.global_phase {
+            Param::Float(angle) => Some(*angle),
+            expr => match expr
+                .to_object(py)
+                .bind(py)
+                .call_method0(intern!(py, "__float__"))
+            {
+                Err(e) if !e.is_instance_of::<PyTypeError>(py) => {
+                    return Err(e);
+                }
+                res => res.ok().map(|v| v.extract()).transpose()?,
+            },
         };
-        let other_phase = match other
-            .global_phase
-            .bind(py)
-            .call_method0(intern!(py, "__float__"))
-        {
-            Err(e) if !e.is_instance_of::<PyTypeError>(py) => {
-                return Err(e);
-            }
-            res => res.ok(),
+        let other_phase: Option<f64> = match &other.global_phase {
+            Param::Float(angle) => Some(*angle),
+            expr => match expr
+                .to_object(py)
+                .bind(py)
+                .call_method0(intern!(py, "__float__"))
+            {
+                Err(e) if !e.is_instance_of::<PyTypeError>(py) => {
+                    return Err(e);
+                }
+                res => res.ok().map(|v| v.extract()).transpose()?,
+            },
         };
         match (self_phase, other_phase) {
             (Some(self_phase), Some(other_phase)) => {
-                let self_phase: f64 = self_phase.extract()?;
-                let other_phase: f64 = other_phase.extract()?;
                 if (((self_phase - other_phase + PI) % (2.0 * PI)) - PI).abs() > 1.0e-10 {
                     return Ok(false);
                 }
             }
             _ => {
-                if !self.global_phase.bind(py).eq(other.global_phase.bind(py))? {
+                if !self
+                    .global_phase
+                    .to_object(py)
+                    .bind(py)
+                    .eq(other.global_phase.to_object(py))?
+                {
                     return Ok(false);
                 }
             }
@@ -2069,28 +2632,6 @@ def _format(operand):
             }
         }
 
-        let self_bit_indices = {
-            let indices = self
-                .qubits
-                .bits()
-                .iter()
-                .chain(self.clbits.bits())
-                .enumerate()
-                .map(|(idx, bit)| (bit, idx));
-            indices.into_py_dict_bound(py)
-        };
-
-        let other_bit_indices = {
-            let indices = other
-                .qubits
-                .bits()
-                .iter()
-                .chain(other.clbits.bits())
-                .enumerate()
-                .map(|(idx, bit)| (bit, idx));
-            indices.into_py_dict_bound(py)
-        };
-
         // Check if qregs are the same.
         let self_qregs = self.qregs.bind(py);
         let other_qregs = other.qregs.bind(py);
@@ -2138,17 +2679,135 @@ def _format(operand):
             }
         }
 
-        // Check for VF2 isomorphic match.
-        let semantic_eq = DAG_NODE.get_bound(py).getattr(intern!(py, "semantic_eq"))?;
+        // Check for VF2 isomorphic match. A wire's "index" (as previously passed to Python's
+        // `DAGNode.semantic_eq`) is just its `Qubit`/`Clbit` index, with clbits offset by the
+        // qubit count, so the two circuits' wires can be compared by plain integers without
+        // ever building or round-tripping through Python.
+        let self_num_qubits = self.qubits.len();
+        let other_num_qubits = other.qubits.len();
+        let wire_index = |num_qubits: usize, qubit: Option<Qubit>, clbit: Option<Clbit>| -> usize {
+            match (qubit, clbit) {
+                (Some(q), None) => q.0 as usize,
+                (None, Some(c)) => num_qubits + c.0 as usize,
+                _ => unreachable!(),
+            }
+        };
+        let params_eq = |p1: &Param, p2: &Param| -> PyResult<bool> {
+            match (p1, p2) {
+                (Param::Float(a), Param::Float(b)) => Ok((a - b).abs() < 1.0e-10),
+                _ => p1.to_object(py).bind(py).eq(p2.to_object(py)),
+            }
+        };
+        // Maps a condition's register/clbit target to the ordered list of mapped clbit wire
+        // indices it covers, so two conditions can be compared positionally without caring
+        // whether they refer to the "same" register object across the two circuits.
+        let reg_or_bit_indices = |dag: &Self, wire: &Bound<PyAny>| -> PyResult<Vec<usize>> {
+            let num_qubits = dag.qubits.len();
+            if let Some(bit) = dag.clbits.find(wire) {
+                Ok(vec![wire_index(num_qubits, None, Some(bit))])
+            } else {
+                wire.iter()?
+                    .map(|bit| -> PyResult<usize> {
+                        let bit = bit?;
+                        let bit = dag.clbits.find(&bit).ok_or_else(|| {
+                            DAGCircuitError::new_err("condition register bit not found in DAG")
+                        })?;
+                        Ok(wire_index(num_qubits, None, Some(bit)))
+                    })
+                    .collect()
+            }
+        };
+        let condition_eq = |instr1: &PackedInstruction, instr2: &PackedInstruction| -> PyResult<bool> {
+            match (instr1.condition(), instr2.condition()) {
+                (None, None) => Ok(true),
+                (Some(_), None) | (None, Some(_)) => Ok(false),
+                (Some(c1), Some(c2)) => {
+                    let c1 = c1.bind(py);
+                    let c2 = c2.bind(py);
+                    match (c1.downcast::<PyTuple>(), c2.downcast::<PyTuple>()) {
+                        (Ok(c1), Ok(c2)) => {
+                            let wire1 = c1.get_item(0)?;
+                            let wire2 = c2.get_item(0)?;
+                            let self_indices = reg_or_bit_indices(self, &wire1)?;
+                            let other_indices = reg_or_bit_indices(other, &wire2)?;
+                            Ok(self_indices == other_indices
+                                && c1.get_item(1)?.eq(c2.get_item(1)?)?)
+                        }
+                        // Real-time `Expr` conditions (or anything else not in legacy tuple
+                        // form) don't carry a cross-circuit bit remapping here, so fall back to
+                        // Python equality, which is correct whenever the two conditions close
+                        // over the same bit/var objects (e.g. comparing a dag against itself or
+                        // a DAG built via `copy_empty_like`).
+                        _ => c1.eq(c2),
+                    }
+                }
+            }
+        };
         let node_match = |n1: &NodeType, n2: &NodeType| -> PyResult<bool> {
-            // Note: we pretend that the node IDs are 0, since we know that semantic_eq
-            // doesn't use node IDs in its comparison. We should eventually port
-            // semantic_eq to Rust to entirely skip conversion to Python DAGNodes.
-            let n1 = self.unpack_into(py, NodeIndex::new(0), n1)?;
-            let n2 = self.unpack_into(py, NodeIndex::new(0), n2)?;
-            Ok(semantic_eq
-                .call1((n1, n2, &self_bit_indices, &other_bit_indices))?
-                .extract()?)
+            match (n1, n2) {
+                (NodeType::QubitIn(q1), NodeType::QubitIn(q2))
+                | (NodeType::QubitOut(q1), NodeType::QubitOut(q2)) => Ok(wire_index(
+                    self_num_qubits,
+                    Some(*q1),
+                    None,
+                ) == wire_index(other_num_qubits, Some(*q2), None)),
+                (NodeType::ClbitIn(c1), NodeType::ClbitIn(c2))
+                | (NodeType::ClbitOut(c1), NodeType::ClbitOut(c2)) => Ok(wire_index(
+                    self_num_qubits,
+                    None,
+                    Some(*c1),
+                ) == wire_index(other_num_qubits, None, Some(*c2))),
+                (NodeType::VarIn(v1), NodeType::VarIn(v2))
+                | (NodeType::VarOut(v1), NodeType::VarOut(v2)) => {
+                    let v1 = v1.bind(py);
+                    let v2 = v2.bind(py);
+                    Ok(v1.getattr(intern!(py, "name"))?.eq(v2.getattr(intern!(py, "name"))?)?)
+                }
+                (NodeType::Operation(instr1), NodeType::Operation(instr2)) => {
+                    if instr1.op.name() != instr2.op.name() {
+                        return Ok(false);
+                    }
+                    if instr1.params.len() != instr2.params.len() {
+                        return Ok(false);
+                    }
+                    for (p1, p2) in instr1.params.iter().zip(instr2.params.iter()) {
+                        if !params_eq(p1, p2)? {
+                            return Ok(false);
+                        }
+                    }
+
+                    let self_qargs = self.qargs_cache.intern(instr1.qubits_id);
+                    let other_qargs = other.qargs_cache.intern(instr2.qubits_id);
+                    if self_qargs.len() != other_qargs.len()
+                        || !self_qargs
+                            .iter()
+                            .zip(other_qargs.iter())
+                            .all(|(q1, q2)| {
+                                wire_index(self_num_qubits, Some(*q1), None)
+                                    == wire_index(other_num_qubits, Some(*q2), None)
+                            })
+                    {
+                        return Ok(false);
+                    }
+
+                    let self_cargs = self.cargs_cache.intern(instr1.clbits_id);
+                    let other_cargs = other.cargs_cache.intern(instr2.clbits_id);
+                    if self_cargs.len() != other_cargs.len()
+                        || !self_cargs
+                            .iter()
+                            .zip(other_cargs.iter())
+                            .all(|(c1, c2)| {
+                                wire_index(self_num_qubits, None, Some(*c1))
+                                    == wire_index(other_num_qubits, None, Some(*c2))
+                            })
+                    {
+                        return Ok(false);
+                    }
+
+                    condition_eq(instr1, instr2)
+                }
+                _ => Ok(false),
+            }
         };
 
         isomorphism::vf2::is_isomorphic(
@@ -2302,6 +2961,18 @@ def _format(operand):
     ///         a contiguous block and won't introduce a cycle when it's
     ///         contracted to a single node, this can be set to ``False`` to
     ///         improve the runtime performance of this method.
+    ///     qubits (List[Qubit] | None): the already-ordered qargs for the replacement op. When
+    ///         given together with ``clbits`` and ``op_names``, ``wire_pos_map`` is ignored
+    ///         entirely and the per-node qargs/cargs/condition derivation this method otherwise
+    ///         performs is skipped, with the block contracted directly (``cycle_check`` is
+    ///         forced to ``False`` in this mode, since the caller is asserting the block is
+    ///         already known to be contiguous). Passes such as block consolidation that have
+    ///         already computed this information while forming ``node_block`` should use this to
+    ///         avoid paying for it twice.
+    ///     clbits (List[Clbit] | None): the already-ordered cargs for the replacement op; see
+    ///         ``qubits``.
+    ///     op_names (List[str] | None): the op names of every node in ``node_block``, used to
+    ///         update the op-count bookkeeping; see ``qubits``.
     ///
     /// Raises:
     ///     DAGCircuitError: if ``cycle_check`` is set to ``True`` and replacing
@@ -2310,14 +2981,17 @@ def _format(operand):
     ///
     /// Returns:
     ///     DAGOpNode: The op node that replaces the block.
-    #[pyo3(signature = (node_block, op, wire_pos_map, cycle_check=true))]
+    #[pyo3(signature = (node_block, op, wire_pos_map=None, cycle_check=true, *, qubits=None, clbits=None, op_names=None))]
     fn replace_block_with_op(
         &mut self,
         py: Python,
         node_block: Vec<PyRef<DAGNode>>,
         op: Bound<PyAny>,
-        wire_pos_map: &Bound<PyDict>,
+        wire_pos_map: Option<&Bound<PyDict>>,
         cycle_check: bool,
+        qubits: Option<Vec<Bound<PyAny>>>,
+        clbits: Option<Vec<Bound<PyAny>>>,
+        op_names: Option<Vec<String>>,
     ) -> PyResult<Py<PyAny>> {
         // If node block is empty return early
         if node_block.is_empty() {
@@ -2326,101 +3000,130 @@ def _format(operand):
             ));
         }
 
-        let mut qubit_pos_map: HashMap<Qubit, usize> = HashMap::new();
-        let mut clbit_pos_map: HashMap<Clbit, usize> = HashMap::new();
-        for (bit, index) in wire_pos_map.iter() {
-            if bit.is_instance(self.circuit_module.qubit.bind(py))? {
-                qubit_pos_map.insert(self.qubits.find(&bit).unwrap(), index.extract()?);
-            } else if bit.is_instance(self.circuit_module.clbit.bind(py))? {
-                clbit_pos_map.insert(self.clbits.find(&bit).unwrap(), index.extract()?);
-            } else {
-                return Err(DAGCircuitError::new_err(
-                    "Wire map keys must be Qubit or Clbit instances.",
-                ));
-            }
-        }
-
         let block_ids: Vec<_> = node_block.iter().map(|n| n.node.unwrap()).collect();
 
-        let mut block_op_names = Vec::new();
-        let mut block_qargs: IndexSet<Qubit> = IndexSet::new();
-        let mut block_cargs: IndexSet<Clbit> = IndexSet::new();
-        for nd in &block_ids {
-            let weight = self.dag.node_weight(*nd);
-            match weight {
-                Some(NodeType::Operation(packed)) => {
-                    block_op_names.push(packed.op.name().to_string());
-                    block_qargs.extend(self.qargs_cache.intern(packed.qubits_id));
-                    block_cargs.extend(self.cargs_cache.intern(packed.clbits_id));
-
-                    let condition = packed
-                        .extra_attrs
+        let (block_qargs, block_cargs, block_op_names, cycle_check) =
+            match (qubits, clbits, op_names) {
+                (Some(qubits), Some(clbits), Some(op_names)) => {
+                    let block_qargs: Vec<Qubit> = qubits
                         .iter()
-                        .flat_map(|e| e.condition.as_ref().map(|c| c.bind(py)))
-                        .next();
-                    if let Some(condition) = condition {
-                        block_cargs.extend(
-                            self.clbits.map_bits(
-                                self.control_flow_module
-                                    .condition_resources(condition)?
-                                    .clbits
-                                    .bind(py),
-                            )?,
-                        );
-                        continue;
+                        .map(|bit| self.qubits.find(bit).unwrap())
+                        .collect();
+                    let block_cargs: Vec<Clbit> = clbits
+                        .iter()
+                        .map(|bit| self.clbits.find(bit).unwrap())
+                        .collect();
+                    (block_qargs, block_cargs, op_names, false)
+                }
+                (None, None, None) => {
+                    let wire_pos_map = wire_pos_map.ok_or_else(|| {
+                        DAGCircuitError::new_err(
+                            "either 'wire_pos_map' or all of 'qubits', 'clbits', and 'op_names' must be given",
+                        )
+                    })?;
+
+                    let mut qubit_pos_map: HashMap<Qubit, usize> = HashMap::new();
+                    let mut clbit_pos_map: HashMap<Clbit, usize> = HashMap::new();
+                    for (bit, index) in wire_pos_map.iter() {
+                        if bit.is_instance(self.circuit_module.qubit.bind(py))? {
+                            qubit_pos_map.insert(self.qubits.find(&bit).unwrap(), index.extract()?);
+                        } else if bit.is_instance(self.circuit_module.clbit.bind(py))? {
+                            clbit_pos_map.insert(self.clbits.find(&bit).unwrap(), index.extract()?);
+                        } else {
+                            return Err(DAGCircuitError::new_err(
+                                "Wire map keys must be Qubit or Clbit instances.",
+                            ));
+                        }
                     }
 
-                    // Add classical bits from SwitchCaseOp, if applicable.
-                    if let OperationType::Instruction(ref op) = packed.op {
-                        let op = op.instruction.bind(py);
-                        if op.is_instance(self.circuit_module.switch_case_op.bind(py))? {
-                            let target = op.getattr(intern!(py, "target"))?;
-                            if target.is_instance(self.circuit_module.clbit.bind(py))? {
-                                block_cargs.insert(self.clbits.find(&target).unwrap());
-                            } else if target
-                                .is_instance(self.circuit_module.classical_register.bind(py))?
-                            {
-                                block_cargs.extend(
-                                    self.clbits
-                                        .map_bits(target.extract::<Vec<Bound<PyAny>>>()?)?,
-                                );
-                            } else {
-                                block_cargs.extend(
-                                    self.clbits.map_bits(
-                                        self.control_flow_module
-                                            .node_resources(&target)?
-                                            .clbits
-                                            .bind(py),
-                                    )?,
-                                );
+                    let mut block_op_names = Vec::new();
+                    let mut block_qargs: IndexSet<Qubit> = IndexSet::new();
+                    let mut block_cargs: IndexSet<Clbit> = IndexSet::new();
+                    for nd in &block_ids {
+                        let weight = self.dag.node_weight(*nd);
+                        match weight {
+                            Some(NodeType::Operation(packed)) => {
+                                block_op_names.push(packed.op.name().to_string());
+                                block_qargs.extend(self.qargs_cache.intern(packed.qubits_id));
+                                block_cargs.extend(self.cargs_cache.intern(packed.clbits_id));
+
+                                let condition = packed
+                                    .extra_attrs
+                                    .iter()
+                                    .flat_map(|e| e.condition.as_ref().map(|c| c.bind(py)))
+                                    .next();
+                                if let Some(condition) = condition {
+                                    block_cargs.extend(
+                                        self.clbits.map_bits(
+                                            self.control_flow_module
+                                                .condition_resources(condition)?
+                                                .clbits
+                                                .bind(py),
+                                        )?,
+                                    );
+                                    continue;
+                                }
+
+                                // Add classical bits from SwitchCaseOp, if applicable.
+                                if let OperationType::Instruction(ref op) = packed.op {
+                                    let op = op.instruction.bind(py);
+                                    if op.is_instance(self.circuit_module.switch_case_op.bind(py))? {
+                                        let target = op.getattr(intern!(py, "target"))?;
+                                        if target.is_instance(self.circuit_module.clbit.bind(py))? {
+                                            block_cargs.insert(self.clbits.find(&target).unwrap());
+                                        } else if target
+                                            .is_instance(self.circuit_module.classical_register.bind(py))?
+                                        {
+                                            block_cargs.extend(
+                                                self.clbits
+                                                    .map_bits(target.extract::<Vec<Bound<PyAny>>>()?)?,
+                                            );
+                                        } else {
+                                            block_cargs.extend(
+                                                self.clbits.map_bits(
+                                                    self.control_flow_module
+                                                        .node_resources(&target)?
+                                                        .clbits
+                                                        .bind(py),
+                                                )?,
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                            Some(_) => {
+                                return Err(DAGCircuitError::new_err(
+                                    "Nodes in 'node_block' must be of type 'DAGOpNode'.",
+                                ))
+                            }
+                            None => {
+                                return Err(DAGCircuitError::new_err(
+                                    "Node in 'node_block' not found in DAG.",
+                                ))
                             }
                         }
                     }
+
+                    let mut block_qargs: Vec<Qubit> = block_qargs
+                        .into_iter()
+                        .filter(|q| qubit_pos_map.contains_key(q))
+                        .collect();
+                    block_qargs.sort_by_key(|q| qubit_pos_map[q]);
+
+                    let mut block_cargs: Vec<Clbit> = block_cargs
+                        .into_iter()
+                        .filter(|c| clbit_pos_map.contains_key(c))
+                        .collect();
+                    block_cargs.sort_by_key(|c| clbit_pos_map[c]);
+
+                    (block_qargs, block_cargs, block_op_names, cycle_check)
                 }
-                Some(_) => {
-                    return Err(DAGCircuitError::new_err(
-                        "Nodes in 'node_block' must be of type 'DAGOpNode'.",
-                    ))
-                }
-                None => {
+                _ => {
                     return Err(DAGCircuitError::new_err(
-                        "Node in 'node_block' not found in DAG.",
+                        "'qubits', 'clbits', and 'op_names' must be given together",
                     ))
                 }
-            }
-        }
-
-        let mut block_qargs: Vec<Qubit> = block_qargs
-            .into_iter()
-            .filter(|q| qubit_pos_map.contains_key(q))
-            .collect();
-        block_qargs.sort_by_key(|q| qubit_pos_map[q]);
-
-        let mut block_cargs: Vec<Clbit> = block_cargs
-            .into_iter()
-            .filter(|c| clbit_pos_map.contains_key(c))
-            .collect();
-        block_cargs.sort_by_key(|c| clbit_pos_map[c]);
+            };
 
         let old_op = op.unbind();
         let op = convert_py_to_operation_type(py, old_op.clone_ref(py))?;
@@ -2473,13 +3176,17 @@ def _format(operand):
     ///         conditional logic already.  This is ignored for :class:`.ControlFlowOp`\\ s (i.e.
     ///         treated as if it is ``False``); replacements of those must already fulfill the same
     ///         conditional logic or this function would be close to useless for them.
+    ///     check (bool): If ``True`` (default), verify that the number of wires given in ``wires``
+    ///         (when passed as a list) matches ``node``'s qargs/cargs count before building the
+    ///         wire map. Callers who already guarantee ``wires`` is consistent with ``node`` can
+    ///         pass ``False`` to skip this check.
     ///
     /// Returns:
     ///     dict: maps node IDs from `input_dag` to their new node incarnations in `self`.
     ///
     /// Raises:
     ///     DAGCircuitError: if met with unexpected predecessor/successors
-    #[pyo3(signature = (node, input_dag, wires=None, propagate_condition=true))]
+    #[pyo3(signature = (node, input_dag, wires=None, propagate_condition=true, check=true))]
     fn substitute_node_with_dag(
         &mut self,
         py: Python,
@@ -2487,6 +3194,7 @@ def _format(operand):
         input_dag: &DAGCircuit,
         wires: Option<Bound<PyAny>>,
         propagate_condition: bool,
+        check: bool,
     ) -> PyResult<Py<PyDict>> {
         let (node_index, bound_node) = match node.downcast::<DAGOpNode>() {
             Ok(bound_node) => (bound_node.borrow().as_ref().node.unwrap(), bound_node),
@@ -2519,7 +3227,7 @@ def _format(operand):
             //
             //            }
 
-            if qargs_len + cargs_len != wires.len() {
+            if check && qargs_len + cargs_len != wires.len() {
                 return Err(DAGCircuitError::new_err(format!(
                     "bit mapping invalid: expected {}, got {}",
                     qargs_len + cargs_len,
@@ -2578,79 +3286,131 @@ def _format(operand):
             }
         };
 
-        let node_map = if propagate_condition && !node.op.control_flow() {
-            // Nested until https://github.com/rust-lang/rust/issues/53667 is fixed in a stable
-            // release
-            if let Some(condition) = node
-                .extra_attrs
+        // The replaced node's own condition, to be propagated onto every non-control-flow node of
+        // the replacement subgraph that doesn't already carry its own condition (mirroring
+        // `substitute_node`'s handling of the same legacy `propagate_condition` argument). This is
+        // read out before `substitute_node_with_subgraph` below removes `node` from the graph.
+        let propagated_condition: Option<Py<PyAny>> = if propagate_condition
+            && !node.op.control_flow()
+        {
+            node.extra_attrs
                 .as_ref()
                 .and_then(|attrs| attrs.condition.as_ref())
-            {
-                todo!()
-            } else {
-                self.substitute_node_with_subgraph(
-                    py,
-                    node_index,
-                    input_dag,
-                    qubit_wire_map,
-                    clbit_wire_map,
-                    var_map,
-                )?
-            }
+                .map(|condition| condition.clone_ref(py))
         } else {
-            self.substitute_node_with_subgraph(
-                py,
-                node_index,
-                input_dag,
-                qubit_wire_map,
-                clbit_wire_map,
-                var_map,
-            )?
+            None
         };
 
-        //        let variable_mapper = PyVariableMapper::new(
-        //            py,
-        //            self.cregs.bind(py).values().into_any(),
-        //            Some(edge_map.clone()),
-        //            None,
-        //            Some(wrap_pyfunction_bound!(reject_new_register, py)?.to_object(py)),
-        //        )?;
+        let node_map = self.substitute_node_with_subgraph(
+            py,
+            node_index,
+            input_dag,
+            qubit_wire_map.clone(),
+            clbit_wire_map.clone(),
+            var_map.clone_ref(py),
+        )?;
 
-        // if in_dag.global_phase:
-        //     self.global_phase += in_dag.global_phase
+        self.global_phase = match (&self.global_phase, &input_dag.global_phase) {
+            (Param::Float(a), Param::Float(b)) => Param::Float(a + b),
+            _ => self
+                .global_phase
+                .to_object(py)
+                .bind(py)
+                .add(input_dag.global_phase.to_object(py))?
+                .extract()?,
+        };
+
+        // The nodes migrated into `self` by `substitute_node_with_subgraph` above still carry
+        // their original `input_dag`-relative qubits/clbits and un-remapped conditions/targets;
+        // fix each one up now that `wire_map` (qubit_wire_map + clbit_wire_map) is known. New
+        // classical registers are not created on the fly here, mirroring `compose`'s handling of
+        // the same situation: a condition or target that needs a register unknown to `self`
+        // raises rather than silently growing `self.cregs`.
+        let bit_map = PyDict::new_bound(py);
+        for (&old, &new) in &qubit_wire_map {
+            bit_map.set_item(input_dag.qubits.get(old).unwrap(), self.qubits.get(new).unwrap())?;
+        }
+        for (&old, &new) in &clbit_wire_map {
+            bit_map.set_item(input_dag.clbits.get(old).unwrap(), self.clbits.get(new).unwrap())?;
+        }
+        let variable_mapper = PyVariableMapper::new(
+            py,
+            self.cregs.bind(py).values().into_any(),
+            Some(bit_map),
+            Some(var_map.bind(py).clone()),
+            Some(wrap_pyfunction_bound!(reject_new_register, py)?.to_object(py)),
+        )?;
+
+        for (&old_index, &new_index) in node_map.iter() {
+            let old_instr = match &input_dag.dag[old_index] {
+                NodeType::Operation(instr) => instr.clone(),
+                _ => continue,
+            };
+
+            let mapped_qubits: Vec<Qubit> = input_dag
+                .qargs_cache
+                .intern(old_instr.qubits_id)
+                .iter()
+                .map(|q| qubit_wire_map[q])
+                .collect();
+            let mapped_clbits: Vec<Clbit> = input_dag
+                .cargs_cache
+                .intern(old_instr.clbits_id)
+                .iter()
+                .map(|c| clbit_wire_map[c])
+                .collect();
+
+            let mut py_op = old_instr.unpack_py_op(py)?.into_bound(py);
+            if let Some(condition) = old_instr.condition() {
+                let condition = variable_mapper.map_condition(condition.bind(py), true)?;
+                if !old_instr.op.control_flow() {
+                    py_op =
+                        py_op.call_method1(intern!(py, "c_if"), condition.downcast::<PyTuple>()?)?;
+                } else {
+                    py_op.setattr(intern!(py, "condition"), condition)?;
+                }
+            } else if let Some(condition) = propagated_condition
+                .as_ref()
+                .filter(|_| !old_instr.op.control_flow())
+            {
+                py_op = py_op
+                    .call_method1(intern!(py, "c_if"), condition.bind(py).downcast::<PyTuple>()?)?;
+            } else if py_op.is_instance(SWITCH_CASE_OP.get_bound(py))? {
+                py_op.setattr(
+                    intern!(py, "target"),
+                    variable_mapper.map_target(&py_op.getattr(intern!(py, "target"))?)?,
+                )?;
+            } else if py_op.is_instance(STORE_OP.get_bound(py))? {
+                py_op.setattr(
+                    intern!(py, "lvalue"),
+                    variable_mapper.map_expr(&py_op.getattr(intern!(py, "lvalue"))?)?,
+                )?;
+                py_op.setattr(
+                    intern!(py, "rvalue"),
+                    variable_mapper.map_expr(&py_op.getattr(intern!(py, "rvalue"))?)?,
+                )?;
+            }
+
+            let qubits_id = Interner::intern(&mut self.qargs_cache, mapped_qubits)?;
+            let clbits_id = Interner::intern(&mut self.cargs_cache, mapped_clbits)?;
+            let op_parts = convert_py_to_operation_type(py, py_op.unbind())?;
+            let new_instr = PackedInstruction::new(
+                op_parts.operation,
+                qubits_id,
+                clbits_id,
+                op_parts.params,
+                op_parts.label,
+                op_parts.duration,
+                op_parts.unit,
+                op_parts.condition,
+                #[cfg(feature = "cache_pygates")]
+                None,
+            );
+            let op_name = new_instr.op.name().to_string();
+            self.dag[new_index] = NodeType::Operation(new_instr);
+            self.increment_op(op_name);
+        }
 
-        //
-        // variable_mapper = _classical_resource_map.VariableMapper(
-        //     self.cregs.values(), wire_map, self.add_creg
-        // )
-        // # Iterate over nodes of input_circuit and update wires in node objects migrated
-        // # from in_dag
-        // for old_node_index, new_node_index in node_map.items():
-        //     # update node attributes
-        //     old_node = in_dag._multi_graph[old_node_index]
-        //     if isinstance(old_node.op, SwitchCaseOp):
-        //         m_op = SwitchCaseOp(
-        //             variable_mapper.map_target(old_node.op.target),
-        //             old_node.op.cases_specifier(),
-        //             label=old_node.op.label,
-        //         )
-        //     elif getattr(old_node.op, "condition", None) is not None:
-        //         m_op = old_node.op
-        //         if not isinstance(old_node.op, ControlFlowOp):
-        //             new_condition = variable_mapper.map_condition(m_op.condition)
-        //             if new_condition is not None:
-        //                 m_op = m_op.c_if(*new_condition)
-        //         else:
-        //             m_op.condition = variable_mapper.map_condition(m_op.condition)
-        //     else:
-        //         m_op = old_node.op
-        //     m_qargs = [wire_map[x] for x in old_node.qargs]
-        //     m_cargs = [wire_map[x] for x in old_node.cargs]
-        //     new_node = DAGOpNode(m_op, qargs=m_qargs, cargs=m_cargs, dag=self)
-        //     new_node._node_id = new_node_index
-        //     self._multi_graph[new_node_index] = new_node
-        //     self._increment_op(new_node.op)
-        //
         let out_dict = PyDict::new_bound(py);
         for (old_index, new_index) in node_map {
             out_dict.set_item(old_index.index(), self.get_node(py, new_index)?)?;
@@ -2684,129 +3444,150 @@ def _format(operand):
     #[pyo3(signature = (node, op, inplace=false, propagate_condition=true))]
     fn substitute_node(
         &mut self,
+        py: Python,
         node: PyRefMut<DAGOpNode>,
         op: &Bound<PyAny>,
         inplace: bool,
         propagate_condition: bool,
-    ) -> Py<PyAny> {
-        // if not isinstance(node, DAGOpNode):
-        //     raise DAGCircuitError("Only DAGOpNodes can be replaced.")
-        //
-        // if node.op.num_qubits != op.num_qubits or node.op.num_clbits != op.num_clbits:
-        //     raise DAGCircuitError(
-        //         "Cannot replace node of width ({} qubits, {} clbits) with "
-        //         "operation of mismatched width ({} qubits, {} clbits).".format(
-        //             node.op.num_qubits, node.op.num_clbits, op.num_qubits, op.num_clbits
-        //         )
-        //     )
-        //
-        // # This might include wires that are inherent to the node, like in its `condition` or
-        // # `target` fields, so might be wider than `node.op.num_{qu,cl}bits`.
-        // current_wires = {wire for _, _, wire in self.edges(node)}
-        // new_wires = set(node.qargs) | set(node.cargs)
-        // if (new_condition := getattr(op, "condition", None)) is not None:
-        //     new_wires.update(condition_resources(new_condition).clbits)
-        // elif isinstance(op, SwitchCaseOp):
-        //     if isinstance(op.target, Clbit):
-        //         new_wires.add(op.target)
-        //     elif isinstance(op.target, ClassicalRegister):
-        //         new_wires.update(op.target)
-        //     else:
-        //         new_wires.update(node_resources(op.target).clbits)
-        //
-        // if propagate_condition and not (
-        //     isinstance(node.op, ControlFlowOp) or isinstance(op, ControlFlowOp)
-        // ):
-        //     if new_condition is not None:
-        //         raise DAGCircuitError(
-        //             "Cannot propagate a condition to an operation that already has one."
-        //         )
-        //     if (old_condition := getattr(node.op, "condition", None)) is not None:
-        //         if not isinstance(op, Instruction):
-        //             raise DAGCircuitError("Cannot add a condition on a generic Operation.")
-        //         if not isinstance(node.op, ControlFlowOp):
-        //             op = op.c_if(*old_condition)
-        //         else:
-        //             op.condition = old_condition
-        //         new_wires.update(condition_resources(old_condition).clbits)
-        //
-        // if new_wires != current_wires:
-        //     # The new wires must be a non-strict subset of the current wires; if they add new wires,
-        //     # we'd not know where to cut the existing wire to insert the new dependency.
-        //     raise DAGCircuitError(
-        //         f"New operation '{op}' does not span the same wires as the old node '{node}'."
-        //         f" New wires: {new_wires}, old wires: {current_wires}."
-        //     )
-        //
-        // if inplace:
-        //     if op.name != node.op.name:
-        //         self._increment_op(op)
-        //         self._decrement_op(node.op)
-        //     node.op = op
-        //     return node
-        //
-        // new_node = copy.copy(node)
-        // new_node.op = op
-        // self._multi_graph[node._node_id] = new_node
-        // if op.name != node.op.name:
-        //     self._increment_op(op)
-        //     self._decrement_op(node.op)
-        // return new_node
-        todo!()
-    }
+    ) -> PyResult<Py<PyAny>> {
+        let node_index = node.as_ref().node.unwrap();
+        let (old_name, old_num_qubits, old_num_clbits, old_condition, qubits_id, clbits_id, node_is_control_flow) =
+            match &self.dag[node_index] {
+                NodeType::Operation(instr) => (
+                    instr.op.name().to_string(),
+                    instr.op.num_qubits(),
+                    instr.op.num_clbits(),
+                    instr.condition().map(|c| c.clone_ref(py)),
+                    instr.qubits_id,
+                    instr.clbits_id,
+                    instr.op.control_flow(),
+                ),
+                _ => return Err(DAGCircuitError::new_err("Only DAGOpNodes can be replaced.")),
+            };
 
-    /// Decompose the circuit into sets of qubits with no gates connecting them.
-    ///
-    /// Args:
-    ///     remove_idle_qubits (bool): Flag denoting whether to remove idle qubits from
-    ///         the separated circuits. If ``False``, each output circuit will contain the
-    ///         same number of qubits as ``self``.
-    ///
-    /// Returns:
-    ///     List[DAGCircuit]: The circuits resulting from separating ``self`` into sets
-    ///         of disconnected qubits
-    ///
-    /// Each :class:`~.DAGCircuit` instance returned by this method will contain the same number of
-    /// clbits as ``self``. The global phase information in ``self`` will not be maintained
-    /// in the subcircuits returned by this method.
-    #[pyo3(signature = (remove_idle_qubits=false))]
-    fn separable_circuits(&self, remove_idle_qubits: bool) -> Py<PyList> {
-        // connected_components = rx.weakly_connected_components(self._multi_graph)
-        //
-        // # Collect each disconnected subgraph
-        // disconnected_subgraphs = []
-        // for components in connected_components:
-        //     disconnected_subgraphs.append(self._multi_graph.subgraph(list(components)))
-        //
-        // # Helper function for ensuring rustworkx nodes are returned in lexicographical,
-        // # topological order
-        // def _key(x):
-        //     return x.sort_key
-        //
-        // # Create new DAGCircuit objects from each of the rustworkx subgraph objects
-        // decomposed_dags = []
-        // for subgraph in disconnected_subgraphs:
-        //     new_dag = self.copy_empty_like()
-        //     new_dag.global_phase = 0
-        //     subgraph_is_classical = True
-        //     for node in rx.lexicographical_topological_sort(subgraph, key=_key):
-        //         if isinstance(node, DAGInNode):
-        //             if isinstance(node.wire, Qubit):
-        //                 subgraph_is_classical = False
-        //         if not isinstance(node, DAGOpNode):
-        //             continue
-        //         new_dag.apply_operation_back(node.op, node.qargs, node.cargs, check=False)
-        //
-        //     # Ignore DAGs created for empty clbits
-        //     if not subgraph_is_classical:
-        //         decomposed_dags.append(new_dag)
-        //
-        // if remove_idle_qubits:
-        //     for dag in decomposed_dags:
-        //         dag.remove_qubits(*(bit for bit in dag.idle_wires() if isinstance(bit, Qubit)))
-        //
-        // return decomposed_dags
-        todo!()
+        let new_num_qubits: u32 = op.getattr(intern!(py, "num_qubits"))?.extract()?;
+        let new_num_clbits: u32 = op.getattr(intern!(py, "num_clbits"))?.extract()?;
+        if old_num_qubits != new_num_qubits || old_num_clbits != new_num_clbits {
+            return Err(DAGCircuitError::new_err(format!(
+                "Cannot replace node of width ({} qubits, {} clbits) with operation of mismatched width ({} qubits, {} clbits).",
+                old_num_qubits, old_num_clbits, new_num_qubits, new_num_clbits
+            )));
+        }
+
+        // This might include wires that are inherent to the node, like in its `condition` or
+        // `target` fields, so might be wider than the node's own qargs/cargs.
+        let current_wires: HashSet<Wire> = self
+            .dag
+            .edges_directed(node_index, Outgoing)
+            .map(|e| *e.weight())
+            .collect();
+
+        let mut new_wires: HashSet<Wire> = self
+            .qargs_cache
+            .intern(qubits_id)
+            .iter()
+            .map(|q| Wire::Qubit(*q))
+            .chain(
+                self.cargs_cache
+                    .intern(clbits_id)
+                    .iter()
+                    .map(|c| Wire::Clbit(*c)),
+            )
+            .collect();
+
+        let get_condition = |obj: &Bound<PyAny>| -> Option<Bound<PyAny>> {
+            match obj.getattr(intern!(py, "condition")) {
+                Ok(condition) if !condition.is_none() => Some(condition),
+                _ => None,
+            }
+        };
+
+        let new_condition = get_condition(op);
+        if let Some(condition) = &new_condition {
+            for bit in self
+                .control_flow_module
+                .condition_resources(condition)?
+                .clbits
+                .bind(py)
+            {
+                new_wires.insert(Wire::Clbit(self.clbits.find(&bit).unwrap()));
+            }
+        } else if op.is_instance(SWITCH_CASE_OP.get_bound(py))? {
+            let target = op.getattr(intern!(py, "target"))?;
+            if target.is_instance(CLBIT.get_bound(py))? {
+                new_wires.insert(Wire::Clbit(self.clbits.find(&target).unwrap()));
+            } else if target.is_instance(CLASSICAL_REGISTER.get_bound(py))? {
+                for bit in target.iter()? {
+                    new_wires.insert(Wire::Clbit(self.clbits.find(&bit?).unwrap()));
+                }
+            } else {
+                for bit in self.control_flow_module.node_resources(&target)?.clbits.bind(py) {
+                    new_wires.insert(Wire::Clbit(self.clbits.find(&bit).unwrap()));
+                }
+            }
+        }
+
+        let new_op_is_control_flow = op.is_instance(CONTROL_FLOW_OP.get_bound(py))?;
+        let mut op = op.clone();
+        if propagate_condition && !node_is_control_flow && !new_op_is_control_flow {
+            if new_condition.is_some() {
+                return Err(DAGCircuitError::new_err(
+                    "Cannot propagate a condition to an operation that already has one.",
+                ));
+            }
+            if let Some(old_condition) = &old_condition {
+                let old_condition = old_condition.bind(py);
+                if !op.is_instance(self.circuit_module.instruction.bind(py))? {
+                    return Err(DAGCircuitError::new_err(
+                        "Cannot add a condition on a generic Operation.",
+                    ));
+                }
+                op = op.call_method1(intern!(py, "c_if"), old_condition.downcast::<PyTuple>()?)?;
+                for bit in self
+                    .control_flow_module
+                    .condition_resources(old_condition)?
+                    .clbits
+                    .bind(py)
+                {
+                    new_wires.insert(Wire::Clbit(self.clbits.find(&bit).unwrap()));
+                }
+            }
+        }
+
+        if new_wires != current_wires {
+            // The new wires must be a non-strict subset of the current wires; if they add new
+            // wires, we'd not know where to cut the existing wire to insert the new dependency.
+            return Err(DAGCircuitError::new_err(format!(
+                "New operation '{}' does not span the same wires as the old node '{}'.",
+                op, old_name
+            )));
+        }
+
+        let op_parts = convert_py_to_operation_type(py, op.clone().unbind())?;
+        let new_name = op_parts.operation.name().to_string();
+        let new_instr = PackedInstruction::new(
+            op_parts.operation,
+            qubits_id,
+            clbits_id,
+            op_parts.params,
+            op_parts.label,
+            op_parts.duration,
+            op_parts.unit,
+            op_parts.condition,
+            #[cfg(feature = "cache_pygates")]
+            Some(op.unbind()),
+        );
+
+        // `inplace` only distinguishes whether the caller's own `DAGOpNode` handle is mutated in
+        // Python, which is irrelevant here: every `DAGOpNode` is just a view onto this node's
+        // index in `self.dag`, so either way the single node stored in the graph is replaced.
+        let _ = inplace;
+        self.dag[node_index] = NodeType::Operation(new_instr);
+        if new_name != old_name {
+            self.increment_op(new_name);
+            self.decrement_op(old_name);
+        }
+        self.get_node(py, node_index)
     }
 
     /// Swap connected nodes e.g. due to commutation.
@@ -2906,7 +3687,7 @@ def _format(operand):
                     match edge.weight() {
                         Wire::Qubit(qubit) => self.qubits.get(*qubit).unwrap(),
                         Wire::Clbit(clbit) => self.clbits.get(*clbit).unwrap(),
-                        Wire::Var(var) => var,
+                        Wire::Var(var) => self.get_var(*var),
                     },
                 ))
             }
@@ -3024,9 +3805,69 @@ def _format(operand):
     }
 
     /// Returns the longest path in the dag as a list of DAGOpNodes, DAGInNodes, and DAGOutNodes.
-    fn longest_path(&self, py: Python) {
-        // return [self._multi_graph[x] for x in rx.dag_longest_path(self._multi_graph)]
-        todo!()
+    ///
+    /// Args:
+    ///     weight_fn (Callable[[DAGOpNode], float]): Optional. If given, it is called with each
+    ///         op node on a candidate path and should return that node's duration; the "longest"
+    ///         path is then the one maximizing the summed duration rather than the node count.
+    ///         Boundary (input/output) nodes always contribute no duration. If not given, every
+    ///         node contributes a unit weight, reproducing the plain topological longest chain.
+    ///
+    /// Returns:
+    ///     list[DAGOpNode | DAGInNode | DAGOutNode]: the longest (optionally duration-weighted)
+    ///     path through the DAG, from a source node to a sink node.
+    #[pyo3(signature = (weight_fn=None))]
+    fn longest_path(&self, py: Python, weight_fn: Option<Bound<PyAny>>) -> PyResult<Vec<Py<PyAny>>> {
+        let order: Vec<NodeIndex> = self.topological_nodes()?.collect();
+        if order.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let node_weight = |node_index: NodeIndex| -> PyResult<f64> {
+            match &weight_fn {
+                None => Ok(1.0),
+                Some(weight_fn) => match &self.dag[node_index] {
+                    NodeType::Operation(_) => {
+                        let node = self.get_node(py, node_index)?;
+                        weight_fn.call1((node,))?.extract()
+                    }
+                    _ => Ok(0.0),
+                },
+            }
+        };
+
+        let mut best: HashMap<NodeIndex, f64> = HashMap::with_capacity(order.len());
+        let mut predecessor: HashMap<NodeIndex, NodeIndex> = HashMap::with_capacity(order.len());
+        for node in order.iter().copied() {
+            let mut best_here = 0.0;
+            let mut best_pred = None;
+            for edge in self.dag.edges_directed(node, Incoming) {
+                let pred = edge.source();
+                let candidate = best[&pred] + node_weight(pred)?;
+                if best_pred.is_none() || candidate > best_here {
+                    best_here = candidate;
+                    best_pred = Some(pred);
+                }
+            }
+            best.insert(node, best_here);
+            if let Some(pred) = best_pred {
+                predecessor.insert(node, pred);
+            }
+        }
+
+        let sink = order
+            .iter()
+            .copied()
+            .max_by(|a, b| best[a].partial_cmp(&best[b]).unwrap_or(Ordering::Equal))
+            .unwrap();
+
+        let mut path = vec![sink];
+        while let Some(&pred) = predecessor.get(path.last().unwrap()) {
+            path.push(pred);
+        }
+        path.reverse();
+
+        path.into_iter().map(|node| self.get_node(py, node)).collect()
     }
 
     /// Returns iterator of the successors of a node as DAGOpNodes and DAGOutNodes."""
@@ -3266,18 +4107,45 @@ def _format(operand):
         Ok(())
     }
 
+    /// Return the set of nodes reachable in one greedy layering step from `frontier`: every node
+    /// with an `Outgoing` edge from `frontier` whose own predecessors are all already in `seen`.
+    fn layer_successors(&self, frontier: &IndexSet<NodeIndex>, seen: &HashSet<NodeIndex>) -> IndexSet<NodeIndex> {
+        let mut next_layer = IndexSet::new();
+        for node in frontier {
+            for succ in self.dag.neighbors_directed(*node, Outgoing) {
+                if seen.contains(&succ) || next_layer.contains(&succ) {
+                    continue;
+                }
+                if self
+                    .dag
+                    .neighbors_directed(succ, Incoming)
+                    .all(|pred| seen.contains(&pred))
+                {
+                    next_layer.insert(succ);
+                }
+            }
+        }
+        next_layer
+    }
+
     /// Return a list of op nodes in the first layer of this dag.
-    fn front_layers(&self) -> Py<PyList> {
-        // graph_layers = self.multigraph_layers()
-        // try:
-        //     next(graph_layers)  # Remove input nodes
-        // except StopIteration:
-        //     return []
-        //
-        // op_nodes = [node for node in next(graph_layers) if isinstance(node, DAGOpNode)]
-        //
-        // return op_nodes
-        todo!()
+    fn front_layers(&self, py: Python) -> PyResult<Py<PyList>> {
+        let seen: HashSet<NodeIndex> = self
+            .qubit_input_map
+            .values()
+            .chain(self.clbit_input_map.values())
+            .chain(self.var_input_map.values())
+            .copied()
+            .collect();
+        let frontier: IndexSet<NodeIndex> = seen.iter().copied().collect();
+        let first_layer = self.layer_successors(&frontier, &seen);
+
+        let op_nodes = first_layer
+            .into_iter()
+            .filter(|node| matches!(self.dag[*node], NodeType::Operation(_)))
+            .map(|node| self.get_node(py, node))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(PyList::new_bound(py, op_nodes).unbind())
     }
 
     /// Yield a shallow view on a layer of this DAGCircuit for all d layers of this circuit.
@@ -3296,77 +4164,183 @@ def _format(operand):
     /// TODO: Gates that use the same cbits will end up in different
     /// layers as this is currently implemented. This may not be
     /// the desired behavior.
-    fn layers(&self) -> Py<PyIterator> {
-        // graph_layers = self.multigraph_layers()
-        // try:
-        //     next(graph_layers)  # Remove input nodes
-        // except StopIteration:
-        //     return
-        //
-        // for graph_layer in graph_layers:
-        //
-        //     # Get the op nodes from the layer, removing any input and output nodes.
-        //     op_nodes = [node for node in graph_layer if isinstance(node, DAGOpNode)]
-        //
-        //     # Sort to make sure they are in the order they were added to the original DAG
-        //     # It has to be done by node_id as graph_layer is just a list of nodes
-        //     # with no implied topology
-        //     # Drawing tools rely on _node_id to infer order of node creation
-        //     # so we need this to be preserved by layers()
-        //     op_nodes.sort(key=lambda nd: nd._node_id)
-        //
-        //     # Stop yielding once there are no more op_nodes in a layer.
-        //     if not op_nodes:
-        //         return
-        //
-        //     # Construct a shallow copy of self
-        //     new_layer = self.copy_empty_like()
-        //
-        //     for node in op_nodes:
-        //         # this creates new DAGOpNodes in the new_layer
-        //         new_layer.apply_operation_back(node.op, node.qargs, node.cargs, check=False)
-        //
-        //     # The quantum registers that have an operation in this layer.
-        //     support_list = [
-        //         op_node.qargs
-        //         for op_node in new_layer.op_nodes()
-        //         if not getattr(op_node.op, "_directive", False)
-        //     ]
-        //
-        //     yield {"graph": new_layer, "partition": support_list}
-        todo!()
+    fn layers(&self, py: Python) -> PyResult<Py<PyIterator>> {
+        let mut seen: HashSet<NodeIndex> = self
+            .qubit_input_map
+            .values()
+            .chain(self.clbit_input_map.values())
+            .chain(self.var_input_map.values())
+            .copied()
+            .collect();
+        let mut frontier: IndexSet<NodeIndex> = seen.iter().copied().collect();
+
+        let mut layer_dicts: Vec<Py<PyAny>> = Vec::new();
+        loop {
+            let next_layer = self.layer_successors(&frontier, &seen);
+            if next_layer.is_empty() {
+                break;
+            }
+            seen.extend(next_layer.iter().copied());
+
+            // Get the op nodes from the layer, removing any input and output nodes, and sort by
+            // node id to preserve the order nodes were added to the original DAG: drawing tools
+            // rely on this order being stable.
+            let mut op_nodes: Vec<NodeIndex> = next_layer
+                .iter()
+                .copied()
+                .filter(|node| matches!(self.dag[*node], NodeType::Operation(_)))
+                .collect();
+            op_nodes.sort_by_key(|node| node.index());
+
+            // Stop yielding once there are no more op nodes in a layer.
+            if op_nodes.is_empty() {
+                break;
+            }
+
+            let mut new_layer = self.copy_empty_like(py)?;
+            let mut new_instrs = Vec::with_capacity(op_nodes.len());
+            let mut partition = Vec::new();
+            for node in &op_nodes {
+                let instr = match &self.dag[*node] {
+                    NodeType::Operation(instr) => instr,
+                    _ => unreachable!(),
+                };
+                if !instr.op.directive() {
+                    let qargs = self.qargs_cache.intern(instr.qubits_id);
+                    partition.push(PyList::new_bound(py, self.qubits.map_indices(qargs.as_slice())));
+                }
+                let (label, duration, unit, condition) = match &instr.extra_attrs {
+                    Some(attrs) => (
+                        attrs.label.clone(),
+                        attrs.duration.clone(),
+                        attrs.unit.clone(),
+                        attrs.condition.clone(),
+                    ),
+                    None => (None, None, None, None),
+                };
+                new_instrs.push(PackedInstruction::new(
+                    instr.op.clone(),
+                    instr.qubits_id,
+                    instr.clbits_id,
+                    instr.params.clone(),
+                    label,
+                    duration,
+                    unit,
+                    condition,
+                    #[cfg(feature = "cache_pygates")]
+                    None,
+                ));
+            }
+            new_layer.extend(py, new_instrs)?;
+
+            let layer_dict = PyDict::new_bound(py);
+            layer_dict.set_item("graph", Py::new(py, new_layer)?)?;
+            layer_dict.set_item("partition", partition)?;
+            layer_dicts.push(layer_dict.into_any().unbind());
+
+            frontier = next_layer;
+        }
+
+        Ok(PyList::new_bound(py, layer_dicts).into_any().iter()?.unbind())
     }
 
     /// Yield a layer for all gates of this circuit.
     ///
     /// A serial layer is a circuit with one gate. The layers have the
     /// same structure as in layers().
-    fn serial_layers(&self) -> PyResult<Py<PyIterator>> {
-        // for next_node in self.topological_op_nodes():
-        //     new_layer = self.copy_empty_like()
-        //
-        //     # Save the support of the operation we add to the layer
-        //     support_list = []
-        //     # Operation data
-        //     op = copy.copy(next_node.op)
-        //     qargs = copy.copy(next_node.qargs)
-        //     cargs = copy.copy(next_node.cargs)
-        //
-        //     # Add node to new_layer
-        //     new_layer.apply_operation_back(op, qargs, cargs, check=False)
-        //     # Add operation to partition
-        //     if not getattr(next_node.op, "_directive", False):
-        //         support_list.append(list(qargs))
-        //     l_dict = {"graph": new_layer, "partition": support_list}
-        //     yield l_dict
-        todo!()
+    fn serial_layers(&self, py: Python) -> PyResult<Py<PyIterator>> {
+        let mut layer_dicts: Vec<Py<PyAny>> = Vec::new();
+        for node in self.topological_nodes()? {
+            let instr = match &self.dag[node] {
+                NodeType::Operation(instr) => instr,
+                _ => continue,
+            };
+
+            let partition = PyList::empty_bound(py);
+            if !instr.op.directive() {
+                let qargs = self.qargs_cache.intern(instr.qubits_id);
+                partition.append(PyList::new_bound(py, self.qubits.map_indices(qargs.as_slice())))?;
+            }
+
+            let (label, duration, unit, condition) = match &instr.extra_attrs {
+                Some(attrs) => (
+                    attrs.label.clone(),
+                    attrs.duration.clone(),
+                    attrs.unit.clone(),
+                    attrs.condition.clone(),
+                ),
+                None => (None, None, None, None),
+            };
+            let new_instr = PackedInstruction::new(
+                instr.op.clone(),
+                instr.qubits_id,
+                instr.clbits_id,
+                instr.params.clone(),
+                label,
+                duration,
+                unit,
+                condition,
+                #[cfg(feature = "cache_pygates")]
+                None,
+            );
+
+            let mut new_layer = self.copy_empty_like(py)?;
+            new_layer.push_back(py, new_instr)?;
+
+            let layer_dict = PyDict::new_bound(py);
+            layer_dict.set_item("graph", Py::new(py, new_layer)?)?;
+            layer_dict.set_item("partition", partition)?;
+            layer_dicts.push(layer_dict.into_any().unbind());
+        }
+
+        Ok(PyList::new_bound(py, layer_dicts).into_any().iter()?.unbind())
     }
 
     /// Yield layers of the multigraph.
-    fn multigraph_layers(&self) -> PyResult<Py<PyIterator>> {
-        // first_layer = [x._node_id for x in self.input_map.values()]
-        // return iter(rx.layers(self._multi_graph, first_layer))
-        todo!()
+    fn multigraph_layers(&self, py: Python) -> PyResult<Py<PyIterator>> {
+        let mut seen: HashSet<NodeIndex> = self
+            .qubit_input_map
+            .values()
+            .chain(self.clbit_input_map.values())
+            .chain(self.var_input_map.values())
+            .copied()
+            .collect();
+        let mut frontier: IndexSet<NodeIndex> = seen.iter().copied().collect();
+
+        let mut layers: Vec<Py<PyAny>> = Vec::new();
+        layers.push(
+            PyList::new_bound(
+                py,
+                frontier
+                    .iter()
+                    .map(|node| self.get_node(py, *node))
+                    .collect::<PyResult<Vec<_>>>()?,
+            )
+            .into_any()
+            .unbind(),
+        );
+
+        loop {
+            let next_layer = self.layer_successors(&frontier, &seen);
+            if next_layer.is_empty() {
+                break;
+            }
+            seen.extend(next_layer.iter().copied());
+            layers.push(
+                PyList::new_bound(
+                    py,
+                    next_layer
+                        .iter()
+                        .map(|node| self.get_node(py, *node))
+                        .collect::<PyResult<Vec<_>>>()?,
+                )
+                .into_any()
+                .unbind(),
+            );
+            frontier = next_layer;
+        }
+
+        Ok(PyList::new_bound(py, layers).into_any().iter()?.unbind())
     }
 
     /// Return a set of non-conditional runs of "op" nodes with the given names.
@@ -3387,7 +4361,7 @@ def _format(operand):
         }
         match self.collect_runs(name_list_set) {
             Some(runs) => {
-                let run_iter = runs.map(|node_indices| {
+                let run_iter = runs.into_iter().map(|node_indices| {
                     PyTuple::new_bound(
                         py,
                         node_indices
@@ -3411,9 +4385,9 @@ def _format(operand):
     /// Return a set of non-conditional runs of 1q "op" nodes.
     #[pyo3(name = "collect_1q_runs")]
     fn py_collect_1q_runs(&self, py: Python) -> PyResult<Py<PyList>> {
-        match self.collect_1q_runs() {
+        match self.collect_1q_runs(py)? {
             Some(runs) => {
-                let runs_iter = runs.map(|node_indices| {
+                let runs_iter = runs.into_iter().map(|node_indices| {
                     PyList::new_bound(
                         py,
                         node_indices
@@ -3434,6 +4408,33 @@ def _format(operand):
         }
     }
 
+    /// Check that every 2-qubit operation in this DAG (recursing into control-flow blocks) acts
+    /// on an edge of `edges`, an iterable of `(int, int)` physical-qubit pairs.
+    ///
+    /// `wire_map` optionally gives the physical qubit for each of this DAG's qubits, by index;
+    /// if not given, qubits are assumed to already be physical (identity map).
+    ///
+    /// Returns `None` if every 2-qubit operation is supported, or else `(name, qubits)` for
+    /// the first unsupported operation found, giving its name and the offending physical qubits.
+    /// Operations on three or more qubits are not checked.
+    #[pyo3(name = "check_coupling", signature = (edges, wire_map=None))]
+    fn py_check_coupling(
+        &self,
+        py: Python,
+        edges: &Bound<PyAny>,
+        wire_map: Option<Vec<u32>>,
+    ) -> PyResult<Option<(String, [u32; 2])>> {
+        let coupling: HashSet<[u32; 2]> = edges
+            .iter()?
+            .map(|edge| edge?.extract())
+            .collect::<PyResult<_>>()?;
+        let qubit_map: Vec<Qubit> = match wire_map {
+            Some(wire_map) => wire_map.into_iter().map(Qubit).collect(),
+            None => (0..self.qubits.len() as u32).map(Qubit).collect(),
+        };
+        self.check_map(py, &coupling, &qubit_map)
+    }
+
     /// Return a set of non-conditional runs of 2q "op" nodes.
     #[pyo3(name = "collect_2q_runs")]
     fn py_collect_2q_runs(&self, py: Python) -> PyResult<Py<PyList>> {
@@ -3460,6 +4461,37 @@ def _format(operand):
         }
     }
 
+    /// Collect maximal contiguous blocks of 1q/2q operations sharing an identical qubit support.
+    ///
+    /// Unlike :meth:`collect_2q_runs`, which chains together compatible 1q/2q operations
+    /// connected by single-successor edges regardless of whether consecutive nodes act on the
+    /// same qubits, this groups only nodes whose qubit support is exactly identical. A
+    /// conditional, control-flow, or directive operation (e.g. a barrier), or one with more than
+    /// two qubits or with any clbits, acts as a boundary that closes off any block open on the
+    /// qubits it touches. Blocks are disjoint and contiguous by construction, so they can be
+    /// fed straight into :meth:`replace_block_with_op` with ``cycle_check=False``.
+    ///
+    /// Returns:
+    ///     list[tuple[list[DAGOpNode], list[Qubit]]]: each block's op nodes in topological order,
+    ///     paired with its ordered qubit support.
+    #[pyo3(name = "collect_blocks")]
+    fn py_collect_blocks(&self, py: Python) -> PyResult<Vec<(Vec<Py<PyAny>>, Vec<Py<PyAny>>)>> {
+        self.collect_blocks()?
+            .into_iter()
+            .map(|(nodes, qargs)| -> PyResult<(Vec<Py<PyAny>>, Vec<Py<PyAny>>)> {
+                let nodes = nodes
+                    .into_iter()
+                    .map(|n| self.get_node(py, n))
+                    .collect::<PyResult<Vec<_>>>()?;
+                let qargs = qargs
+                    .into_iter()
+                    .map(|q| self.qubits.get(q).unwrap().bind(py).clone().unbind())
+                    .collect();
+                Ok((nodes, qargs))
+            })
+            .collect()
+    }
+
     /// Iterator for nodes that affect a given wire.
     ///
     /// Args:
@@ -3714,6 +4746,34 @@ def _format(operand):
         Ok(PyString::new_bound(py, std::str::from_utf8(&buffer)?))
     }
 
+    /// Serialize this DAG's graph structure to a JSON string: its nodes (qubit/clbit
+    /// input/output markers and operations), its typed, interned-index edge list, and the
+    /// global phase.
+    ///
+    /// Unlike `_to_dot`, which only ever produces a one-way Graphviz rendering for
+    /// visualization, this is meant to be read back in with [DAGCircuit::from_json] and is
+    /// independent of QPY or OpenQASM, so other Rust quantum frameworks can read and write it.
+    ///
+    /// Registers (``qregs``/``cregs``) are not part of this format, and see
+    /// `crate::interchange` for the fidelity limits this format has on non-`StandardGate`
+    /// operations, non-numeric parameters, and conditions.
+    fn to_json(&self, py: Python) -> PyResult<String> {
+        let serialized = self.to_serialized_dag(py)?;
+        serde_json::to_string(&serialized)
+            .map_err(|e| DAGCircuitError::new_err(format!("failed to serialize DAG: {e}")))
+    }
+
+    /// The inverse of [DAGCircuit::to_json]: rebuild a fresh `DAGCircuit` from a JSON string it
+    /// produced, recreating `BitData`, the qarg/carg interners, the input/output wire maps, and
+    /// the `StableDiGraph` edges, and validating that every wire forms an unbroken chain from its
+    /// input node to its output node.
+    #[staticmethod]
+    fn from_json(py: Python, data: &str) -> PyResult<Self> {
+        let serialized: SerializedDag = serde_json::from_str(data)
+            .map_err(|e| DAGCircuitError::new_err(format!("failed to parse DAG JSON: {e}")))?;
+        DAGCircuit::from_serialized_dag(py, &serialized)
+    }
+
     fn add_input_var(&mut self, py: Python, var: &Bound<PyAny>) -> PyResult<()> {
         if !self.vars_by_type[DAGVarType::CAPTURE as usize]
             .bind(py)
@@ -3814,11 +4874,446 @@ def _format(operand):
 }
 
 impl DAGCircuit {
-    /// Return an iterator of gate runs with non-conditional op nodes of given names
-    pub fn collect_runs(
+    /// Return `True` if the dag has a calibration defined for the operation at `node`. In this
+    /// case, the operation does not need to be translated to the device basis.
+    ///
+    /// This is the `NodeIndex`-taking core of the `has_calibration_for` pymethod, split out so
+    /// run-collection filters can reuse it without needing a `PyRef<DAGOpNode>`.
+    fn has_calibration_for_index(&self, py: Python, node: NodeIndex) -> PyResult<bool> {
+        if let Some(NodeType::Operation(packed)) = self.dag.node_weight(node) {
+            let op_name = packed.op.name().to_string();
+            if !self.calibrations.contains_key(&op_name) {
+                return Ok(false);
+            }
+            let mut params = Vec::new();
+            for p in &packed.params {
+                if let Param::ParameterExpression(exp) = p {
+                    let exp = exp.bind(py);
+                    if !exp.getattr(intern!(py, "parameters"))?.is_truthy()? {
+                        let as_py_float = exp.call_method0(intern!(py, "__float__"))?;
+                        params.push(as_py_float.unbind());
+                        continue;
+                    }
+                }
+                params.push(p.to_object(py));
+            }
+            let qubits: Vec<BitType> = self
+                .qargs_cache
+                .intern(packed.qubits_id)
+                .iter()
+                .cloned()
+                .map(|b| b.into())
+                .collect();
+            let params = PyTuple::new_bound(py, params);
+            self.calibrations[&op_name]
+                .bind(py)
+                .contains((qubits, params).to_object(py))
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Lower this DAG to the serde-friendly [SerializedDag] used by [DAGCircuit::to_json].
+    fn to_serialized_dag(&self, py: Python) -> PyResult<SerializedDag> {
+        let mut index_of: HashMap<NodeIndex, usize> = HashMap::with_capacity(self.dag.node_count());
+        let mut nodes = Vec::with_capacity(self.dag.node_count());
+        for (position, node_index) in self.dag.node_indices().enumerate() {
+            index_of.insert(node_index, position);
+            let serialized = match &self.dag[node_index] {
+                NodeType::QubitIn(qubit) => SerializedNode::QubitIn(qubit.0),
+                NodeType::QubitOut(qubit) => SerializedNode::QubitOut(qubit.0),
+                NodeType::ClbitIn(clbit) => SerializedNode::ClbitIn(clbit.0),
+                NodeType::ClbitOut(clbit) => SerializedNode::ClbitOut(clbit.0),
+                NodeType::VarIn(var) => {
+                    let name: String = var.bind(py).getattr("name")?.extract()?;
+                    let type_ = match self.vars_info.get(&name) {
+                        Some(info) => match info.type_ {
+                            DAGVarType::INPUT => SerializedVarType::Input,
+                            DAGVarType::CAPTURE => SerializedVarType::Capture,
+                            DAGVarType::DECLARE => SerializedVarType::Declare,
+                        },
+                        None => {
+                            return Err(DAGCircuitError::new_err(format!(
+                                "var '{name}' has no bookkeeping entry"
+                            )))
+                        }
+                    };
+                    SerializedNode::VarIn { name, type_ }
+                }
+                NodeType::VarOut(var) => SerializedNode::VarOut {
+                    name: var.bind(py).getattr("name")?.extract()?,
+                },
+                NodeType::Operation(instr) => SerializedNode::Operation {
+                    qubits: self
+                        .qargs_cache
+                        .intern(instr.qubits_id)
+                        .iter()
+                        .map(|q| q.0)
+                        .collect(),
+                    clbits: self
+                        .cargs_cache
+                        .intern(instr.clbits_id)
+                        .iter()
+                        .map(|c| c.0)
+                        .collect(),
+                    op: self.serialize_op(py, instr)?,
+                },
+            };
+            nodes.push(serialized);
+        }
+
+        let mut edges = Vec::with_capacity(self.dag.edge_count());
+        for edge in self.dag.edge_references() {
+            let wire = match edge.weight() {
+                Wire::Qubit(qubit) => SerializedWireKind::Qubit(qubit.0),
+                Wire::Clbit(clbit) => SerializedWireKind::Clbit(clbit.0),
+                Wire::Var(var) => SerializedWireKind::Var(
+                    self.var_order[var.index()].bind(py).getattr("name")?.extract()?,
+                ),
+            };
+            edges.push(SerializedWire {
+                source: index_of[&edge.source()],
+                target: index_of[&edge.target()],
+                wire,
+            });
+        }
+
+        Ok(SerializedDag {
+            global_phase: SerializedParam::from_param(py, &self.global_phase)?,
+            nodes,
+            edges,
+        })
+    }
+
+    /// Lower a single [PackedInstruction] to the serde-friendly [SerializedOp].
+    fn serialize_op(&self, py: Python, instr: &PackedInstruction) -> PyResult<SerializedOp> {
+        let standard_gate = match &instr.op {
+            OperationType::Standard(gate) => Some(*gate as u8),
+            _ => None,
+        };
+        let params = instr
+            .params
+            .iter()
+            .map(|p| SerializedParam::from_param(py, p))
+            .collect::<PyResult<Vec<_>>>()?;
+        let (label, duration, unit, condition) = match &instr.extra_attrs {
+            Some(attrs) => (
+                attrs.label.clone(),
+                attrs
+                    .duration
+                    .as_ref()
+                    .map(|d| d.bind(py).repr().map(|r| r.to_string()))
+                    .transpose()?,
+                attrs.unit.clone(),
+                attrs
+                    .condition
+                    .as_ref()
+                    .map(|c| c.bind(py).repr().map(|r| r.to_string()))
+                    .transpose()?,
+            ),
+            None => (None, None, None, None),
+        };
+        Ok(SerializedOp {
+            name: instr.op.name().to_string(),
+            standard_gate,
+            num_qubits: instr.op.num_qubits(),
+            num_clbits: instr.op.num_clbits(),
+            params,
+            label,
+            duration,
+            unit,
+            condition,
+        })
+    }
+
+    /// Reconstruct a fresh `DAGCircuit` from a [SerializedDag], as produced by
+    /// [DAGCircuit::to_serialized_dag].
+    fn from_serialized_dag(py: Python, serialized: &SerializedDag) -> PyResult<Self> {
+        let mut dag = DAGCircuit::new(py)?;
+        dag.global_phase = serialized.global_phase.to_param();
+
+        let num_qubits = serialized
+            .nodes
+            .iter()
+            .filter(|n| matches!(n, SerializedNode::QubitIn(_)))
+            .count();
+        let num_clbits = serialized
+            .nodes
+            .iter()
+            .filter(|n| matches!(n, SerializedNode::ClbitIn(_)))
+            .count();
+        for _ in 0..num_qubits {
+            let bit = dag.circuit_module.qubit.bind(py).call0()?;
+            dag.add_qubit_unchecked(py, &bit)?;
+        }
+        for _ in 0..num_clbits {
+            let bit = dag.circuit_module.clbit.bind(py).call0()?;
+            dag.add_clbit_unchecked(py, &bit)?;
+        }
+        // `add_qubit_unchecked`/`add_clbit_unchecked` each wired up a trivial direct edge from
+        // their bit's input node to its output node (via `add_wire`); the real edges, which may
+        // route through operation nodes, are rebuilt below from `serialized.edges`, so those
+        // placeholder edges have to go first.
+        dag.dag.retain_edges(|_, _| false);
+
+        let mut node_of: Vec<NodeIndex> = Vec::with_capacity(serialized.nodes.len());
+        for node in &serialized.nodes {
+            let index = match node {
+                SerializedNode::QubitIn(qubit) => dag.qubit_input_map[&Qubit(*qubit)],
+                SerializedNode::QubitOut(qubit) => dag.qubit_output_map[&Qubit(*qubit)],
+                SerializedNode::ClbitIn(clbit) => dag.clbit_input_map[&Clbit(*clbit)],
+                SerializedNode::ClbitOut(clbit) => dag.clbit_output_map[&Clbit(*clbit)],
+                SerializedNode::Operation { qubits, clbits, op } => {
+                    let instr = dag.deserialize_op(py, op, qubits, clbits)?;
+                    dag.increment_op(instr.op.name().to_string());
+                    dag.dag.add_node(NodeType::Operation(instr))
+                }
+                SerializedNode::VarIn { name, .. } | SerializedNode::VarOut { name } => {
+                    return Err(DAGCircuitError::new_err(format!(
+                        "from_json cannot reconstruct the live `Var` object backing variable \
+                         '{name}'; see `crate::interchange` for why"
+                    )))
+                }
+            };
+            node_of.push(index);
+        }
+
+        for edge in &serialized.edges {
+            let wire = match &edge.wire {
+                SerializedWireKind::Qubit(qubit) => Wire::Qubit(Qubit(*qubit)),
+                SerializedWireKind::Clbit(clbit) => Wire::Clbit(Clbit(*clbit)),
+                SerializedWireKind::Var(name) => {
+                    return Err(DAGCircuitError::new_err(format!(
+                        "from_json cannot reconstruct the live `Var` object backing variable \
+                         '{name}'; see `crate::interchange` for why"
+                    )))
+                }
+            };
+            let source = *node_of.get(edge.source).ok_or_else(|| {
+                DAGCircuitError::new_err(format!("edge source {} out of range", edge.source))
+            })?;
+            let target = *node_of.get(edge.target).ok_or_else(|| {
+                DAGCircuitError::new_err(format!("edge target {} out of range", edge.target))
+            })?;
+            dag.dag.add_edge(source, target, wire);
+        }
+
+        for qubit in dag.qubit_input_map.keys().copied().collect::<Vec<_>>() {
+            dag.validate_wire(Wire::Qubit(qubit))?;
+        }
+        for clbit in dag.clbit_input_map.keys().copied().collect::<Vec<_>>() {
+            dag.validate_wire(Wire::Clbit(clbit))?;
+        }
+
+        Ok(dag)
+    }
+
+    /// Reconstruct a single [PackedInstruction] from a [SerializedOp], rebuilding the underlying
+    /// Python operation object (exactly, via [StandardGate::create_py_op], when
+    /// `op.standard_gate` is present; otherwise a generic `Instruction` built from `op`'s
+    /// name/arity/params) and interning its qubits/clbits into `self`.
+    ///
+    /// The condition captured in `op.condition` is not re-applied (see `crate::interchange`), nor
+    /// is `op.duration`, since both were only captured as a `repr()` string.
+    fn deserialize_op(
+        &mut self,
+        py: Python,
+        op: &SerializedOp,
+        qubits: &[BitType],
+        clbits: &[BitType],
+    ) -> PyResult<PackedInstruction> {
+        let params: Vec<Param> = op.params.iter().map(SerializedParam::to_param).collect();
+        let built = if let Some(discriminant) = op.standard_gate {
+            let gate: StandardGate = bytemuck::checked::try_cast(discriminant).map_err(|_| {
+                DAGCircuitError::new_err(format!(
+                    "invalid standard gate discriminant: {discriminant}"
+                ))
+            })?;
+            gate.create_py_op(py, Some(&params), None)?
+        } else {
+            let params_list = PyList::new_bound(py, &params);
+            self.circuit_module
+                .instruction
+                .bind(py)
+                .call1((op.name.as_str(), op.num_qubits, op.num_clbits, params_list))?
+                .unbind()
+        };
+
+        let op_parts = convert_py_to_operation_type(py, built)?;
+        let qubits_id = Interner::intern(
+            &mut self.qargs_cache,
+            qubits.iter().map(|q| Qubit(*q)).collect(),
+        )?;
+        let clbits_id = Interner::intern(
+            &mut self.cargs_cache,
+            clbits.iter().map(|c| Clbit(*c)).collect(),
+        )?;
+        Ok(PackedInstruction::new(
+            op_parts.operation,
+            qubits_id,
+            clbits_id,
+            op_parts.params,
+            op.label.clone(),
+            None,
+            op.unit.clone(),
+            None,
+            #[cfg(feature = "cache_pygates")]
+            None,
+        ))
+    }
+
+    /// Walk a single wire from its input node to its output node, validating that every node it
+    /// passes through has exactly one outgoing edge on that wire, and that the walk terminates at
+    /// the wire's output node rather than dead-ending or branching.
+    fn validate_wire(&self, wire: Wire) -> PyResult<()> {
+        let (mut current, out_node) = match wire {
+            Wire::Qubit(qubit) => (self.qubit_input_map[&qubit], self.qubit_output_map[&qubit]),
+            Wire::Clbit(clbit) => (self.clbit_input_map[&clbit], self.clbit_output_map[&clbit]),
+            Wire::Var(_) => return Ok(()),
+        };
+
+        let mut steps = 0;
+        loop {
+            let mut next_edges = self
+                .dag
+                .edges_directed(current, Outgoing)
+                .filter(|e| *e.weight() == wire);
+            let next = match (next_edges.next(), next_edges.next()) {
+                (Some(edge), None) => edge.target(),
+                (None, _) => {
+                    return Err(DAGCircuitError::new_err(format!(
+                        "wire {wire:?} dead-ends before reaching its output node"
+                    )))
+                }
+                (Some(_), Some(_)) => {
+                    return Err(DAGCircuitError::new_err(format!(
+                        "wire {wire:?} branches into more than one outgoing edge"
+                    )))
+                }
+            };
+            if next == out_node {
+                return Ok(());
+            }
+            current = next;
+            steps += 1;
+            if steps > self.dag.node_count() {
+                return Err(DAGCircuitError::new_err(format!(
+                    "wire {wire:?} does not terminate at its output node"
+                )));
+            }
+        }
+    }
+
+    /// The `NodeIndex`-and-reference-taking core of the `depth` pymethod, split out so the
+    /// control-flow recursion can reuse a single borrowed `DepthFilter` instead of re-extracting
+    /// or cloning it from Python on every nested block.
+    fn depth_impl(
         &self,
-        namelist: HashSet<String>,
-    ) -> Option<impl Iterator<Item = Vec<NodeIndex>> + '_> {
+        py: Python,
+        recurse: bool,
+        filter_function: Option<&DepthFilter>,
+    ) -> PyResult<usize> {
+        Ok(if recurse {
+            let circuit_to_dag = CIRCUIT_TO_DAG.get_bound(py);
+            let mut node_lookup: HashMap<NodeIndex, usize> = HashMap::new();
+
+            for node in self.op_nodes(py, Some(CONTROL_FLOW_OP.get_bound(py).downcast()?), true)? {
+                let node = node.bind(py);
+                let weight = if node.is_instance(self.circuit_module.for_loop_op.bind(py))? {
+                    node.getattr("params")?.get_item(0)?.len()?
+                } else {
+                    1
+                };
+                let node_index = node.extract::<DAGNode>()?.node.unwrap();
+                if weight == 0 {
+                    node_lookup.insert(node_index, 0);
+                } else {
+                    let raw_blocks = node.getattr("op")?.getattr("blocks")?;
+                    let blocks: &Bound<PyList> = raw_blocks.downcast::<PyList>()?;
+                    let mut block_weights: Vec<usize> = Vec::with_capacity(blocks.len());
+                    for block in blocks.iter() {
+                        let inner_dag: &DAGCircuit = &circuit_to_dag.call1((block,))?.extract()?;
+                        block_weights.push(inner_dag.depth_impl(py, true, filter_function)?);
+                    }
+                    node_lookup.insert(node_index, weight * block_weights.iter().max().unwrap());
+                }
+            }
+
+            match filter_function {
+                None => {
+                    let weight_fn = |edge: EdgeReference<'_, Wire>| -> Result<usize, Infallible> {
+                        Ok(*node_lookup.get(&edge.target()).unwrap_or(&1))
+                    };
+                    (match rustworkx_core::dag_algo::longest_path(&self.dag, weight_fn).unwrap() {
+                        Some(res) => res.1,
+                        None => return Err(DAGCircuitError::new_err("not a DAG")),
+                    }) - 1
+                }
+                Some(filter_function) => {
+                    // Every node not already weighted by the control-flow recursion above
+                    // contributes 1 only if it's an operation matching the predicate; input,
+                    // output, and non-matching nodes contribute 0, so (unlike the unfiltered
+                    // case) there's no need to subtract an extra 1 for the trailing output node.
+                    let mut matches: HashSet<NodeIndex> = HashSet::new();
+                    for node in self.dag.node_indices() {
+                        if !node_lookup.contains_key(&node)
+                            && filter_function.node_matches(py, self, node)?
+                        {
+                            matches.insert(node);
+                        }
+                    }
+                    let weight_fn = |edge: EdgeReference<'_, Wire>| -> Result<usize, Infallible> {
+                        Ok(*node_lookup
+                            .get(&edge.target())
+                            .unwrap_or(&usize::from(matches.contains(&edge.target()))))
+                    };
+                    match rustworkx_core::dag_algo::longest_path(&self.dag, weight_fn).unwrap() {
+                        Some(res) => res.1,
+                        None => return Err(DAGCircuitError::new_err("not a DAG")),
+                    }
+                }
+            }
+        } else {
+            if CONTROL_FLOW_OP_NAMES
+                .iter()
+                .any(|x| self.op_names.contains_key(&x.to_string()))
+            {
+                return Err(DAGCircuitError::new_err("Depth with control flow is ambiguous. You may use `recurse=True` to get a result, but see this method's documentation for the meaning of this."));
+            }
+
+            match filter_function {
+                None => {
+                    let weight_fn = |_| -> Result<usize, Infallible> { Ok(1) };
+                    (match rustworkx_core::dag_algo::longest_path(&self.dag, weight_fn).unwrap() {
+                        Some(res) => res.1,
+                        None => return Err(DAGCircuitError::new_err("not a DAG")),
+                    }) - 1
+                }
+                Some(filter_function) => {
+                    let mut matches: HashSet<NodeIndex> = HashSet::new();
+                    for node in self.dag.node_indices() {
+                        if filter_function.node_matches(py, self, node)? {
+                            matches.insert(node);
+                        }
+                    }
+                    let weight_fn = |edge: EdgeReference<'_, Wire>| -> Result<usize, Infallible> {
+                        Ok(usize::from(matches.contains(&edge.target())))
+                    };
+                    match rustworkx_core::dag_algo::longest_path(&self.dag, weight_fn).unwrap() {
+                        Some(res) => res.1,
+                        None => return Err(DAGCircuitError::new_err("not a DAG")),
+                    }
+                }
+            }
+        })
+    }
+
+    /// Return the maximal gate runs with non-conditional op nodes of given names.
+    ///
+    /// Each inner `Vec` is independent of the others (no two runs share a node), so callers such
+    /// as a Rust port of `Optimize1qGatesDecomposition` can drive the outer `Vec` with a `rayon`
+    /// parallel iterator.
+    pub fn collect_runs(&self, namelist: HashSet<String>) -> Option<Vec<Vec<NodeIndex>>> {
         let filter_fn = move |node_index: NodeIndex| -> Result<bool, Infallible> {
             let node = &self.dag[node_index];
             match node {
@@ -3829,28 +5324,125 @@ impl DAGCircuit {
                     }),
                 _ => Ok(false),
             }
-        };
-        rustworkx_core::dag_algo::collect_runs(&self.dag, filter_fn)
-            .map(|node_iter| node_iter.map(|x| x.unwrap()))
+        };
+        rustworkx_core::dag_algo::collect_runs(&self.dag, filter_fn)
+            .map(|node_iter| node_iter.map(|x| x.unwrap()).collect())
+    }
+
+    /// Return the maximal runs of non-conditional 1q "op" nodes.
+    ///
+    /// Nodes that already have a pulse calibration attached (see `has_calibration_for`) are
+    /// excluded from runs, since they don't need resynthesizing to the device basis. As with
+    /// `collect_runs`, the runs are disjoint and may be driven in parallel by the caller.
+    pub fn collect_1q_runs(&self, py: Python) -> PyResult<Option<Vec<Vec<NodeIndex>>>> {
+        let filter_fn = move |node_index: NodeIndex| -> PyResult<bool> {
+            let node = &self.dag[node_index];
+            match node {
+                NodeType::Operation(inst) => Ok(inst.op.num_qubits() == 1
+                    && inst.op.num_clbits() == 0
+                    && inst.op.matrix(&inst.params).is_some()
+                    && match &inst.extra_attrs {
+                        None => true,
+                        Some(attrs) => attrs.condition.is_none(),
+                    }
+                    && !self.has_calibration_for_index(py, node_index)?),
+                _ => Ok(false),
+            }
+        };
+        Ok(
+            rustworkx_core::dag_algo::collect_runs(&self.dag, filter_fn)
+                .map(|node_iter| node_iter.map(|x| x.unwrap()).collect()),
+        )
+    }
+
+    /// Collect the 1q runs (see [DAGCircuit::collect_1q_runs]) and let `replace` compute each
+    /// run's replacement sequence in parallel over a `rayon` thread pool, then splice the
+    /// accepted replacements back into the DAG serially.
+    ///
+    /// `replace` is called with the cloned [PackedInstruction]s of a single run and returns
+    /// `Some(new_instructions)` to substitute the run, or `None` to leave it untouched. Every
+    /// node in a run touches exactly one qubit and no clbits (this is guaranteed by
+    /// `collect_1q_runs`), so a run can always be spliced out by connecting its predecessor
+    /// directly to `new_instructions[0]`, chaining `new_instructions` along that single qubit
+    /// wire, and connecting `new_instructions.last()` to the run's successor; an empty
+    /// `new_instructions` simply reconnects the predecessor straight to the successor.
+    ///
+    /// Returns without modifying the DAG if there are no 1q runs to process.
+    pub fn optimize_1q_runs_parallel<F>(&mut self, py: Python, replace: F) -> PyResult<()>
+    where
+        F: Fn(&[PackedInstruction]) -> Option<Vec<PackedInstruction>> + Sync,
+    {
+        let Some(runs) = self.collect_1q_runs(py)? else {
+            return Ok(());
+        };
+
+        let run_instructions: Vec<Vec<PackedInstruction>> = runs
+            .iter()
+            .map(|run| {
+                run.iter()
+                    .map(|node| match &self.dag[*node] {
+                        NodeType::Operation(instr) => instr.clone(),
+                        _ => unreachable!("collect_1q_runs only returns Operation nodes"),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // The expensive part -- typically multiplying each run's 2x2 matrices and synthesizing a
+        // replacement sequence -- has no data dependency across runs, so it can run on the thread
+        // pool; only the splicing below needs `&mut self`.
+        let replacements: Vec<Option<Vec<PackedInstruction>>> = run_instructions
+            .par_iter()
+            .map(|instructions| replace(instructions))
+            .collect();
+
+        for (run, replacement) in runs.into_iter().zip(replacements) {
+            if let Some(new_instructions) = replacement {
+                self.splice_1q_run(&run, new_instructions);
+            }
+        }
+
+        Ok(())
     }
 
-    /// Return a set of non-conditional runs of 1q "op" nodes.
-    pub fn collect_1q_runs(&self) -> Option<impl Iterator<Item = Vec<NodeIndex>> + '_> {
-        let filter_fn = move |node_index: NodeIndex| -> Result<bool, Infallible> {
-            let node = &self.dag[node_index];
-            match node {
-                NodeType::Operation(inst) => Ok(inst.op.num_qubits() == 1
-                    && inst.op.num_clbits() == 0
-                    && inst.op.matrix(&inst.params).is_some()
-                    && match &inst.extra_attrs {
-                        None => true,
-                        Some(attrs) => attrs.condition.is_none(),
-                    }),
-                _ => Ok(false),
-            }
+    /// Remove a single-qubit run of nodes (as produced by `collect_1q_runs`) from the DAG and
+    /// splice `new_instructions` into its place along the one qubit wire the run lives on.
+    fn splice_1q_run(&mut self, run: &[NodeIndex], new_instructions: Vec<PackedInstruction>) {
+        let qubit = match &self.dag[run[0]] {
+            NodeType::Operation(instr) => self.qargs_cache.intern(instr.qubits_id)[0],
+            _ => unreachable!("collect_1q_runs only returns Operation nodes"),
         };
-        rustworkx_core::dag_algo::collect_runs(&self.dag, filter_fn)
-            .map(|node_iter| node_iter.map(|x| x.unwrap()))
+
+        let predecessor = self
+            .dag
+            .edges_directed(run[0], Incoming)
+            .find(|e| matches!(e.weight(), Wire::Qubit(_)))
+            .map(|e| e.source())
+            .unwrap();
+        let successor = self
+            .dag
+            .edges_directed(*run.last().unwrap(), Outgoing)
+            .find(|e| matches!(e.weight(), Wire::Qubit(_)))
+            .map(|e| e.target())
+            .unwrap();
+
+        for node in run {
+            let op_name = match &self.dag[*node] {
+                NodeType::Operation(instr) => instr.op.name().to_string(),
+                _ => unreachable!("collect_1q_runs only returns Operation nodes"),
+            };
+            self.decrement_op(op_name);
+            self.dag.remove_node(*node);
+        }
+
+        let mut last = predecessor;
+        for instr in new_instructions {
+            self.increment_op(instr.op.name().to_string());
+            let node = self.dag.add_node(NodeType::Operation(instr));
+            self.dag.add_edge(last, node, Wire::Qubit(qubit));
+            last = node;
+        }
+        self.dag.add_edge(last, successor, Wire::Qubit(qubit));
     }
 
     /// Return a set of non-conditional runs of 2q "op" nodes.
@@ -3891,6 +5483,165 @@ impl DAGCircuit {
         rustworkx_core::dag_algo::collect_bicolor_runs(&self.dag, filter_fn, color_fn).unwrap()
     }
 
+    /// Generalize [DAGCircuit::collect_2q_runs] to maximal blocks of at most `max_block_width`
+    /// qubits, rather than exactly two.
+    ///
+    /// Nodes are walked in [DAGCircuit::topological_nodes] order while tracking, per qubit, which
+    /// open block (if any) currently owns it. A candidate op node is folded into the union of the
+    /// blocks already owning its qubits as long as that union's combined qubit support still fits
+    /// within `max_block_width` and the node passes `filter_fn`; otherwise those blocks are closed
+    /// off on the node's qubits and it seeds a fresh block of its own. Any node that fails
+    /// `filter_fn` (e.g. a barrier, a measurement, or anything the caller doesn't consider
+    /// block-safe) always closes off whatever blocks are open on its qubits without opening a new
+    /// one.
+    ///
+    /// Returns `None` if no blocks were collected. Otherwise each inner `Vec` is in topological
+    /// order and the outer `Vec` is ordered by each block's first node, so the blocks can be
+    /// spliced directly (e.g. via `replace_block_with_op`).
+    pub fn collect_block_runs<F>(
+        &self,
+        max_block_width: usize,
+        mut filter_fn: F,
+    ) -> PyResult<Option<Vec<Vec<NodeIndex>>>>
+    where
+        F: FnMut(NodeIndex) -> PyResult<bool>,
+    {
+        let mut dsu: UnionFind<usize> = UnionFind::new(self.dag.node_bound());
+        let mut block_qubits: HashMap<usize, IndexSet<Qubit>> = HashMap::new();
+        let mut active: HashMap<Qubit, usize> = HashMap::new();
+        let mut assignments: Vec<(NodeIndex, usize)> = Vec::new();
+
+        for node_index in self.topological_nodes()? {
+            let instr = match &self.dag[node_index] {
+                NodeType::Operation(instr) => instr,
+                _ => continue,
+            };
+            let qargs = self.qargs_cache.intern(instr.qubits_id);
+
+            let passes = !qargs.is_empty()
+                && qargs.len() <= max_block_width
+                && self.cargs_cache.intern(instr.clbits_id).is_empty()
+                && filter_fn(node_index)?;
+
+            if !passes {
+                // Close every block open on these qubits on all of its qubits, so no later node
+                // can join a block that this node depends on.
+                for q in qargs {
+                    let Some(slot) = active.get(q).copied() else {
+                        continue;
+                    };
+                    let root = dsu.find(slot);
+                    for owned in block_qubits.get(&root).into_iter().flatten() {
+                        if active.get(owned).map(|s| dsu.find(*s)) == Some(root) {
+                            active.remove(owned);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let owning_roots: IndexSet<usize> = qargs
+                .iter()
+                .filter_map(|q| active.get(q).copied().map(|slot| dsu.find(slot)))
+                .collect();
+
+            let mut combined: IndexSet<Qubit> = qargs.iter().copied().collect();
+            for root in &owning_roots {
+                combined.extend(block_qubits[root].iter().copied());
+            }
+
+            let slot = if !owning_roots.is_empty() && combined.len() > max_block_width {
+                // Doesn't fit alongside the blocks already owning these qubits: close those
+                // blocks off on this node's qubits and start a fresh block seeded by it alone.
+                for q in qargs {
+                    active.remove(q);
+                }
+                let slot = node_index.index();
+                block_qubits.insert(slot, qargs.iter().copied().collect());
+                slot
+            } else {
+                let mut roots = owning_roots.into_iter();
+                let target = roots.next().unwrap_or(node_index.index());
+                for other in roots {
+                    dsu.union(target, other);
+                    block_qubits.remove(&other);
+                }
+                let target = dsu.find(target);
+                block_qubits.insert(target, combined);
+                target
+            };
+
+            for q in qargs {
+                active.insert(*q, slot);
+            }
+            assignments.push((node_index, slot));
+        }
+
+        let mut blocks: IndexMap<usize, Vec<NodeIndex>> = IndexMap::new();
+        for (node_index, slot) in assignments {
+            blocks.entry(dsu.find(slot)).or_default().push(node_index);
+        }
+
+        if blocks.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(blocks.into_values().collect()))
+        }
+    }
+
+    /// Collect maximal contiguous blocks of 1q/2q operations that share an identical qubit
+    /// support, suitable for feeding straight into `replace_block_with_op` with
+    /// `cycle_check=false` (the blocks are disjoint and contiguous by construction, so
+    /// contracting them can never introduce a cycle).
+    ///
+    /// Unlike `collect_2q_runs`, which chains together any compatible 1q/2q operations connected
+    /// by single-successor edges regardless of whether consecutive nodes act on the same qubits,
+    /// this groups only nodes whose qubit support is exactly identical, and a node with a
+    /// different support (or with more than two qubits, or with any clbits, or that is a
+    /// conditional, control-flow, or directive operation such as a barrier) acts as a boundary
+    /// that closes off any block open on the qubits it touches.
+    pub fn collect_blocks(&self) -> PyResult<Vec<(Vec<NodeIndex>, Vec<Qubit>)>> {
+        let mut blocks: Vec<(Vec<NodeIndex>, Vec<Qubit>)> = Vec::new();
+        let mut open_block: HashMap<Qubit, usize> = HashMap::new();
+        for node_index in self.topological_nodes()? {
+            let instr = match &self.dag[node_index] {
+                NodeType::Operation(instr) => instr,
+                _ => continue,
+            };
+            let qargs = self.qargs_cache.intern(instr.qubits_id);
+            let is_block_candidate = !qargs.is_empty()
+                && qargs.len() <= 2
+                && self.cargs_cache.intern(instr.clbits_id).is_empty()
+                && !CONTROL_FLOW_OP_NAMES.contains(&instr.op.name())
+                && !instr.op.directive()
+                && match &instr.extra_attrs {
+                    None => true,
+                    Some(attrs) => attrs.condition.is_none(),
+                };
+
+            if is_block_candidate {
+                let candidate_block = qargs.first().and_then(|q| open_block.get(q).copied());
+                let extends = candidate_block.is_some_and(|b| {
+                    qargs.iter().all(|q| open_block.get(q) == Some(&b)) && &blocks[b].1 == qargs
+                });
+                if let Some(b) = candidate_block.filter(|_| extends) {
+                    blocks[b].0.push(node_index);
+                } else {
+                    blocks.push((vec![node_index], qargs.clone()));
+                    let block_index = blocks.len() - 1;
+                    for q in qargs {
+                        open_block.insert(*q, block_index);
+                    }
+                }
+            } else {
+                for q in qargs {
+                    open_block.remove(q);
+                }
+            }
+        }
+        Ok(blocks)
+    }
+
     fn increment_op(&mut self, op: String) {
         match self.op_names.entry(op) {
             hash_map::Entry::Occupied(mut o) => {
@@ -3935,6 +5686,86 @@ impl DAGCircuit {
             .unique()
     }
 
+    /// General light-cone query: the causal (`Backward`) or future (`Forward`) cone reachable
+    /// from a set of seed qubits and clbits, following quantum wires, classical wires, or both
+    /// (per `wires`).
+    ///
+    /// This generalizes [quantum_causal_cone][Self] (a `Backward`, `Quantum`-only, single-seed
+    /// query) to multiple seed wires, either direction, and classical wires. In `Both` mode a
+    /// node enters the cone as soon as it shares any wire already in the cone, and then
+    /// contributes *all* of its qubits and clbits to the frontier; unlike
+    /// `quantum_causal_cone` this does not special-case directive nodes (e.g. a wide barrier)
+    /// to only pull in the subset of its predecessors/successors that are already in-cone, so
+    /// it can be coarser across directives than that legacy query.
+    pub fn light_cone(
+        &self,
+        seed_qubits: impl IntoIterator<Item = Qubit>,
+        seed_clbits: impl IntoIterator<Item = Clbit>,
+        direction: ConeDirection,
+        wires: ConeWires,
+    ) -> LightCone {
+        let petgraph_direction = match direction {
+            ConeDirection::Backward => Incoming,
+            ConeDirection::Forward => Outgoing,
+        };
+        let follow_quantum = !matches!(wires, ConeWires::Classical);
+        let follow_classical = !matches!(wires, ConeWires::Quantum);
+
+        let mut cone = LightCone::default();
+        let mut queue: VecDeque<NodeIndex> = VecDeque::new();
+
+        if follow_quantum {
+            for qubit in seed_qubits {
+                cone.qubits.insert(qubit);
+                let boundary = match direction {
+                    ConeDirection::Backward => self.qubit_output_map[&qubit],
+                    ConeDirection::Forward => self.qubit_input_map[&qubit],
+                };
+                queue.extend(self.dag.neighbors_directed(boundary, petgraph_direction));
+            }
+        }
+        if follow_classical {
+            for clbit in seed_clbits {
+                cone.clbits.insert(clbit);
+                let boundary = match direction {
+                    ConeDirection::Backward => self.clbit_output_map[&clbit],
+                    ConeDirection::Forward => self.clbit_input_map[&clbit],
+                };
+                queue.extend(self.dag.neighbors_directed(boundary, petgraph_direction));
+            }
+        }
+
+        while let Some(cur_index) = queue.pop_front() {
+            if cone.nodes.contains(&cur_index) {
+                continue;
+            }
+            let packed = match &self.dag[cur_index] {
+                NodeType::Operation(packed) => packed,
+                _ => continue,
+            };
+            let node_qubits = self.qargs_cache.intern(packed.qubits_id);
+            let node_clbits = self.cargs_cache.intern(packed.clbits_id);
+
+            let shares_cone_wire = (follow_quantum
+                && node_qubits.iter().any(|q| cone.qubits.contains(q)))
+                || (follow_classical && node_clbits.iter().any(|c| cone.clbits.contains(c)));
+            if !shares_cone_wire {
+                continue;
+            }
+
+            cone.nodes.insert(cur_index);
+            if follow_quantum {
+                cone.qubits.extend(node_qubits.iter().copied());
+            }
+            if follow_classical {
+                cone.clbits.extend(node_clbits.iter().copied());
+            }
+            queue.extend(self.dag.neighbors_directed(cur_index, petgraph_direction));
+        }
+
+        cone
+    }
+
     /// Apply a [PackedInstruction] to the back of the circuit.
     ///
     /// The provided `instr` MUST be valid for this DAG, e.g. its
@@ -3946,7 +5777,7 @@ impl DAGCircuit {
     /// [DAGCircuit::copy_empty_like].
     fn push_back(&mut self, py: Python, instr: PackedInstruction) -> PyResult<NodeIndex> {
         let op_name = instr.op.name();
-        let (all_cbits, vars): (Vec<Clbit>, Option<Vec<PyObject>>) = {
+        let (all_cbits, vars): (Vec<Clbit>, Option<Vec<Var>>) = {
             if self.may_have_additional_wires(py, &instr) {
                 let mut clbits: IndexSet<Clbit> =
                     IndexSet::from_iter(self.cargs_cache.intern(instr.clbits_id).iter().cloned());
@@ -4018,7 +5849,7 @@ impl DAGCircuit {
     /// [DAGCircuit::copy_empty_like].
     fn push_front(&mut self, py: Python, inst: PackedInstruction) -> PyResult<NodeIndex> {
         let op_name = inst.op.name();
-        let (all_cbits, vars): (Vec<Clbit>, Option<Vec<PyObject>>) = {
+        let (all_cbits, vars): (Vec<Clbit>, Option<Vec<Var>>) = {
             if self.may_have_additional_wires(py, &inst) {
                 let mut clbits: IndexSet<Clbit> =
                     IndexSet::from_iter(self.cargs_cache.intern(inst.clbits_id).iter().cloned());
@@ -4074,6 +5905,138 @@ impl DAGCircuit {
         Ok(new_node)
     }
 
+    /// Apply a run of [PackedInstruction]s to the back of the circuit in one shot.
+    ///
+    /// This is equivalent to calling [DAGCircuit::push_back] once per instruction, except
+    /// that each wire's trailing output edge is only ever rewired once, after the whole
+    /// `instructions` iterator has been consumed, instead of once per instruction touching
+    /// that wire. `push_back` re-links an O(wires-on-that-instruction) set of edges on every
+    /// call, so a `push_back` loop over `n` instructions costs O(n * wires) edge churn; this
+    /// collapses that to O(n + wires). Callers that are appending a long, mostly-linear chain
+    /// (e.g. replaying the topological order of another DAG) should prefer this over a
+    /// `push_back` loop.
+    ///
+    /// The provided instructions MUST be valid for this DAG, e.g. their
+    /// bits, registers, vars, and interner IDs must be valid in this DAG. No validity checking
+    /// is performed (this is the `check=false` fast path); callers that need per-node validation
+    /// should use [DAGCircuit::push_back] in a loop instead.
+    ///
+    /// Returns the new nodes' indices in the same order as `instructions` was consumed.
+    pub fn extend<I>(&mut self, py: Python, instructions: I) -> PyResult<Vec<NodeIndex>>
+    where
+        I: IntoIterator<Item = PackedInstruction>,
+    {
+        let instructions = instructions.into_iter();
+        let mut new_nodes = Vec::with_capacity(instructions.size_hint().0);
+        let mut qubit_last: HashMap<Qubit, NodeIndex> = HashMap::new();
+        let mut clbit_last: HashMap<Clbit, NodeIndex> = HashMap::new();
+        let mut var_last: HashMap<Var, NodeIndex> = HashMap::new();
+
+        for instr in instructions {
+            let op_name = instr.op.name();
+            let (all_cbits, vars): (Vec<Clbit>, Option<Vec<Var>>) = {
+                if self.may_have_additional_wires(py, &instr) {
+                    let mut clbits: IndexSet<Clbit> = IndexSet::from_iter(
+                        self.cargs_cache.intern(instr.clbits_id).iter().cloned(),
+                    );
+                    let (additional_clbits, additional_vars) = self.additional_wires(py, &instr)?;
+                    for clbit in additional_clbits {
+                        clbits.insert(clbit);
+                    }
+                    (clbits.into_iter().collect(), Some(additional_vars))
+                } else {
+                    (
+                        self.cargs_cache
+                            .intern(instr.clbits_id)
+                            .iter()
+                            .copied()
+                            .collect(),
+                        None,
+                    )
+                }
+            };
+
+            self.increment_op(op_name.to_string());
+
+            let qubits: Vec<Qubit> = self.qargs_cache.intern(instr.qubits_id).to_vec();
+            let new_node = self.dag.add_node(NodeType::Operation(instr));
+            new_nodes.push(new_node);
+
+            for qubit in &qubits {
+                match qubit_last.entry(*qubit) {
+                    hash_map::Entry::Occupied(mut entry) => {
+                        self.dag.add_edge(*entry.get(), new_node, Wire::Qubit(*qubit));
+                        entry.insert(new_node);
+                    }
+                    hash_map::Entry::Vacant(entry) => {
+                        self.relink_predecessor(self.qubit_output_map[qubit], new_node, Wire::Qubit(*qubit));
+                        entry.insert(new_node);
+                    }
+                }
+            }
+            for clbit in &all_cbits {
+                match clbit_last.entry(*clbit) {
+                    hash_map::Entry::Occupied(mut entry) => {
+                        self.dag.add_edge(*entry.get(), new_node, Wire::Clbit(*clbit));
+                        entry.insert(new_node);
+                    }
+                    hash_map::Entry::Vacant(entry) => {
+                        self.relink_predecessor(self.clbit_output_map[clbit], new_node, Wire::Clbit(*clbit));
+                        entry.insert(new_node);
+                    }
+                }
+            }
+            for var in vars.iter().flatten() {
+                match var_last.entry(*var) {
+                    hash_map::Entry::Occupied(mut entry) => {
+                        self.dag.add_edge(*entry.get(), new_node, Wire::Var(*var));
+                        entry.insert(new_node);
+                    }
+                    hash_map::Entry::Vacant(entry) => {
+                        self.relink_predecessor(self.var_output_map[var], new_node, Wire::Var(*var));
+                        entry.insert(new_node);
+                    }
+                }
+            }
+        }
+
+        // Now that the whole chain has been appended, reconnect each wire that was touched
+        // to the DAG's output node exactly once.
+        for (qubit, last) in qubit_last {
+            self.dag
+                .add_edge(last, self.qubit_output_map[&qubit], Wire::Qubit(qubit));
+        }
+        for (clbit, last) in clbit_last {
+            self.dag
+                .add_edge(last, self.clbit_output_map[&clbit], Wire::Clbit(clbit));
+        }
+        for (var, last) in var_last {
+            self.dag
+                .add_edge(last, self.var_output_map[&var], Wire::Var(var));
+        }
+
+        Ok(new_nodes)
+    }
+
+    /// Connect `output_node`'s current predecessor(s) to `new_node` and remove the edge(s) that
+    /// used to terminate directly at the output node. No edge from `new_node` to `output_node`
+    /// is added here; [DAGCircuit::extend] adds that closing edge once the batch is done.
+    ///
+    /// Used by [DAGCircuit::extend] the first time a wire is touched in a batch, so that the
+    /// output node is only ever visited once per wire no matter how many instructions in the
+    /// batch use that wire.
+    fn relink_predecessor(&mut self, output_node: NodeIndex, new_node: NodeIndex, weight: Wire) {
+        let last_edges: Vec<_> = self
+            .dag
+            .edges_directed(output_node, Incoming)
+            .map(|e| (e.source(), e.id()))
+            .collect();
+        for (source, old_edge) in last_edges {
+            self.dag.add_edge(source, new_node, weight.clone());
+            self.dag.remove_edge(old_edge);
+        }
+    }
+
     fn topological_nodes(&self) -> PyResult<impl Iterator<Item = NodeIndex>> {
         let key = |node: NodeIndex| -> Result<(Option<Index>, Option<Index>), Infallible> {
             Ok(self.dag.node_weight(node).unwrap().key())
@@ -4095,10 +6058,7 @@ impl DAGCircuit {
         let (input_node, output_node) = match wire {
             Wire::Qubit(qubit) => (self.qubit_input_map[qubit], self.qubit_output_map[qubit]),
             Wire::Clbit(clbit) => (self.clbit_input_map[clbit], self.clbit_output_map[clbit]),
-            Wire::Var(var) => (
-                self.var_input_map.get(var).unwrap(),
-                self.var_output_map.get(var).unwrap(),
-            ),
+            Wire::Var(var) => (self.var_input_map[var], self.var_output_map[var]),
         };
 
         let child = self
@@ -4115,6 +6075,20 @@ impl DAGCircuit {
         Ok(child == output_node)
     }
 
+    /// Look up the interned [Var] handle for a var that has already been added to this DAG
+    /// (via `add_var`/`add_input_var`/`add_captured_var`/`add_declared_var`).
+    fn lookup_var(&self, var: &Bound<PyAny>) -> PyResult<Var> {
+        let var_name: String = var.getattr("name")?.extract()?;
+        self.var_indices.get(&var_name).copied().ok_or_else(|| {
+            DAGCircuitError::new_err(format!("var {} is not present in this circuit", var_name))
+        })
+    }
+
+    /// Get the actual Python var object a [Var] handle refers to.
+    pub(crate) fn get_var(&self, var: Var) -> &PyObject {
+        &self.var_order[var.index()]
+    }
+
     fn may_have_additional_wires(&self, py: Python, instr: &PackedInstruction) -> bool {
         let has_condition = match instr.condition() {
             None => false,
@@ -4144,8 +6118,8 @@ impl DAGCircuit {
         &self,
         py: Python,
         instr: &PackedInstruction,
-    ) -> PyResult<(Vec<Clbit>, Vec<PyObject>)> {
-        let wires_from_expr = |node: &Bound<PyAny>| -> PyResult<(Vec<Clbit>, Vec<PyObject>)> {
+    ) -> PyResult<(Vec<Clbit>, Vec<Var>)> {
+        let wires_from_expr = |node: &Bound<PyAny>| -> PyResult<(Vec<Clbit>, Vec<Var>)> {
             let mut clbits = Vec::new();
             let mut vars = Vec::new();
             for var in ITER_VARS.get_bound(py).call1((node,))?.iter()? {
@@ -4158,7 +6132,7 @@ impl DAGCircuit {
                         clbits.push(self.clbits.find(&bit?).unwrap());
                     }
                 } else {
-                    vars.push(var.unbind());
+                    vars.push(self.lookup_var(&var_var)?);
                 }
             }
             Ok((clbits, vars))
@@ -4199,7 +6173,7 @@ impl DAGCircuit {
             let op = inst.instruction.bind(py);
             if op.is_instance(CONTROL_FLOW_OP.get_bound(py))? {
                 for var in op.call_method0("iter_captured_vars")?.iter()? {
-                    vars.push(var?.unbind())
+                    vars.push(self.lookup_var(&var?)?)
                 }
                 if op.is_instance(SWITCH_CASE_OP.get_bound(py))? {
                     let target = op.getattr(intern!(py, "target"))?;
@@ -4278,14 +6252,21 @@ impl DAGCircuit {
                     (_, _) => Err(DAGCircuitError::new_err("classical wire already exists!")),
                 }
             }
-            Wire::Var(_) => {
-                // in_node = DAGInNode(wire=var)
-                // out_node = DAGOutNode(wire=var)
-                // in_node._node_id, out_node._node_id = self._multi_graph.add_nodes_from((in_node, out_node))
-                // self._multi_graph.add_edge(in_node._node_id, out_node._node_id, var)
-                // self.input_map[var] = in_node
-                // self.output_map[var] = out_node
-                todo!()
+            Wire::Var(var) => {
+                // Unlike qubits and clbits, a var's `VarIn`/`VarOut` node weights carry the
+                // actual Python var object, which this method never receives (only the interned
+                // `Var` handle) — so a var wire can't be conjured up here the way a qubit/clbit
+                // one can. Vars must always be registered through `add_var`, which already does
+                // this bookkeeping itself; this arm always returns an error, reporting either
+                // that the var wire already exists or that `add_var` must be used.
+                if self.var_input_map.contains_key(&var) || self.var_output_map.contains_key(&var)
+                {
+                    Err(DAGCircuitError::new_err("var wire already exists!"))
+                } else {
+                    Err(DAGCircuitError::new_err(
+                        "cannot add a var wire directly; register new variables with `add_var`",
+                    ))
+                }
             }
         }?;
 
@@ -4301,7 +6282,7 @@ impl DAGCircuit {
         let mut current_node = match wire {
             Wire::Qubit(qubit) => self.qubit_input_map.get(qubit),
             Wire::Clbit(clbit) => self.clbit_input_map.get(clbit),
-            Wire::Var(_) => todo!(),
+            Wire::Var(var) => self.var_input_map.get(var),
         }
         .cloned();
 
@@ -4337,7 +6318,10 @@ impl DAGCircuit {
                 self.clbit_input_map.shift_remove(&clbit),
                 self.clbit_output_map.shift_remove(&clbit),
             ),
-            Wire::Var(_) => todo!(),
+            Wire::Var(var) => (
+                self.var_input_map.shift_remove(&var),
+                self.var_output_map.shift_remove(&var),
+            ),
         };
 
         self.dag.remove_node(in_node.unwrap());
@@ -4483,6 +6467,230 @@ impl DAGCircuit {
         Ok(dag_node)
     }
 
+    /// One replacement job for [DAGCircuit::substitute_nodes_batch]: the node being replaced, the
+    /// subgraph replacing it, and the qubit/clbit/var maps from `other`'s space into `self`'s,
+    /// already resolved to interned `Var` handles.
+    struct PreparedSubstitution {
+        node: NodeIndex,
+        other: DAGCircuit,
+        qubit_map: HashMap<Qubit, Qubit>,
+        clbit_map: HashMap<Clbit, Clbit>,
+        var_map: HashMap<Var, Var>,
+    }
+
+    /// The `Py<PyAny>`-free part of a single substitution's work: which of `other`'s nodes survive
+    /// the qubit/clbit/var-support filter, and the internal/boundary edge lists needed to splice
+    /// them in, still given in terms of `other`'s own node indices. Every field here is a plain
+    /// `NodeIndex`/`Wire`, so this is `Send` and safe to compute for many independent
+    /// substitutions in parallel, unlike the node weights themselves (which carry `Py<PyAny>`
+    /// payloads and must only ever be cloned while holding the GIL).
+    struct SubstitutionEdges {
+        copied: Vec<NodeIndex>,
+        internal: Vec<(NodeIndex, NodeIndex, Wire)>,
+        /// `(other_side_index, self_side_index, wire)` for edges where `node`'s predecessor in
+        /// `self` feeds into the copied subgraph.
+        incoming: Vec<(NodeIndex, NodeIndex, Wire)>,
+        /// `(other_side_index, self_side_index, wire)` for edges where the copied subgraph feeds
+        /// one of `node`'s successors in `self`.
+        outgoing: Vec<(NodeIndex, NodeIndex, Wire)>,
+    }
+
+    /// Compute a single substitution's [SubstitutionEdges], reading only graph topology (no
+    /// `Py<PyAny>` access), so this can be called from any thread.
+    fn plan_substitution_edges(
+        self_dag: &StableDiGraph<NodeType, Wire>,
+        repl: &PreparedSubstitution,
+    ) -> PyResult<SubstitutionEdges> {
+        let node = repl.node;
+        let other = &repl.other;
+        let qubit_map = &repl.qubit_map;
+        let clbit_map = &repl.clbit_map;
+        let var_map = &repl.var_map;
+        let node_filter = |node: NodeIndex| -> bool {
+            match other.dag[node] {
+                NodeType::Operation(_) => !other
+                    .dag
+                    .edges_directed(node, petgraph::Direction::Outgoing)
+                    .any(|edge| match edge.weight() {
+                        Wire::Qubit(qubit) => !qubit_map.contains_key(qubit),
+                        Wire::Clbit(clbit) => !clbit_map.contains_key(clbit),
+                        Wire::Var(var) => !var_map.contains_key(var),
+                    }),
+                _ => false,
+            }
+        };
+        let reverse_qubit_map: HashMap<Qubit, Qubit> =
+            qubit_map.iter().map(|(x, y)| (*y, *x)).collect();
+        let reverse_clbit_map: HashMap<Clbit, Clbit> =
+            clbit_map.iter().map(|(x, y)| (*y, *x)).collect();
+        let reverse_var_map: HashMap<Var, Var> = var_map.iter().map(|(x, y)| (*y, *x)).collect();
+
+        let copied: Vec<NodeIndex> = other
+            .dag
+            .node_indices()
+            .filter(|n| node_filter(*n))
+            .collect();
+        if copied.is_empty() {
+            return Ok(SubstitutionEdges {
+                copied,
+                internal: Vec::new(),
+                incoming: Vec::new(),
+                outgoing: Vec::new(),
+            });
+        }
+        let copied_set: HashSet<NodeIndex> = copied.iter().copied().collect();
+
+        let internal: Vec<(NodeIndex, NodeIndex, Wire)> = other
+            .dag
+            .edge_references()
+            .filter(|edge| copied_set.contains(&edge.target()) && copied_set.contains(&edge.source()))
+            .map(|edge| {
+                let wire = match edge.weight() {
+                    Wire::Qubit(qubit) => Wire::Qubit(qubit_map[qubit]),
+                    Wire::Clbit(clbit) => Wire::Clbit(clbit_map[clbit]),
+                    Wire::Var(var) => Wire::Var(var_map[var]),
+                };
+                (edge.source(), edge.target(), wire)
+            })
+            .collect();
+
+        let mut incoming = Vec::new();
+        let mut outgoing = Vec::new();
+        let edges: Vec<(NodeIndex, NodeIndex, Wire)> = self_dag
+            .edges(node)
+            .map(|x| (x.source(), x.target(), x.weight().clone()))
+            .collect();
+        for (source, target, weight) in edges {
+            if source == node {
+                let wire_output_id = match weight {
+                    Wire::Qubit(qubit) => other.qubit_output_map.get(&reverse_qubit_map[&qubit]),
+                    Wire::Clbit(clbit) => other.clbit_output_map.get(&reverse_clbit_map[&clbit]),
+                    Wire::Var(var) => other.var_output_map.get(&reverse_var_map[&var]),
+                };
+                let old_index = wire_output_id
+                    .and_then(|x| other.dag.neighbors_directed(*x, Incoming).next());
+                if let Some(old_index) = old_index {
+                    if !copied_set.contains(&old_index) {
+                        return Err(PyIndexError::new_err(format!(
+                            "No mapped index {} found",
+                            old_index.index()
+                        )));
+                    }
+                    outgoing.push((old_index, target, weight));
+                }
+            } else {
+                let wire_input_id = match weight {
+                    Wire::Qubit(qubit) => other.qubit_input_map.get(&reverse_qubit_map[&qubit]),
+                    Wire::Clbit(clbit) => other.clbit_input_map.get(&reverse_clbit_map[&clbit]),
+                    Wire::Var(var) => other.var_input_map.get(&reverse_var_map[&var]),
+                };
+                let old_index = wire_input_id
+                    .and_then(|x| other.dag.neighbors_directed(*x, Outgoing).next());
+                if let Some(old_index) = old_index {
+                    if !copied_set.contains(&old_index) {
+                        return Err(PyIndexError::new_err(format!(
+                            "No mapped index {} found",
+                            old_index.index()
+                        )));
+                    }
+                    incoming.push((old_index, source, weight));
+                }
+            }
+        }
+
+        Ok(SubstitutionEdges {
+            copied,
+            internal,
+            incoming,
+            outgoing,
+        })
+    }
+
+    /// Apply many independent [DAGCircuit::substitute_node_with_subgraph]-style replacements in
+    /// one pass. Each `(node, other, qubit_map, clbit_map, var_map)` entry in `replacements` must
+    /// have a qubit/clbit support disjoint from every other entry's, since transpiler rewrite
+    /// passes (e.g. one-qubit-run optimization) routinely identify many such independent runs
+    /// whose rewrites have no data dependency on each other.
+    ///
+    /// The per-replacement edge-list computation (which of `other`'s nodes survive the
+    /// qubit/clbit/var filter, and how the copied subgraph's boundary wires resolve) touches no
+    /// `Py<PyAny>` values, so it runs in parallel via rayon. Anything that does need the GIL
+    /// happens serially on the calling thread: resolving `var_map` before the parallel step,
+    /// and cloning node weights and every mutation of `self.dag` after it.
+    ///
+    /// Returns one old-to-new node map per replacement, in the same order as `replacements`.
+    pub fn substitute_nodes_batch(
+        &mut self,
+        py: Python,
+        replacements: Vec<(
+            NodeIndex,
+            DAGCircuit,
+            HashMap<Qubit, Qubit>,
+            HashMap<Clbit, Clbit>,
+            Py<PyDict>,
+        )>,
+    ) -> PyResult<Vec<IndexMap<NodeIndex, NodeIndex>>> {
+        let mut prepared = Vec::with_capacity(replacements.len());
+        for (node, other, qubit_map, clbit_map, var_map) in replacements {
+            if self.dag.node_weight(node).is_none() {
+                return Err(PyIndexError::new_err(format!(
+                    "Specified node {} is not in this graph",
+                    node.index()
+                )));
+            }
+            let mut var_map_idx: HashMap<Var, Var> = HashMap::with_capacity(var_map.bind(py).len());
+            for (other_var, self_var) in var_map.bind(py).iter() {
+                let other_var = other.lookup_var(&other_var)?;
+                let self_var = self.lookup_var(&self_var)?;
+                var_map_idx.insert(other_var, self_var);
+            }
+            prepared.push(PreparedSubstitution {
+                node,
+                other,
+                qubit_map,
+                clbit_map,
+                var_map: var_map_idx,
+            });
+        }
+
+        let self_dag = &self.dag;
+        let plans: Vec<SubstitutionEdges> = prepared
+            .par_iter()
+            .map(|repl| Self::plan_substitution_edges(self_dag, repl))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let mut out_maps = Vec::with_capacity(prepared.len());
+        for (repl, plan) in prepared.iter().zip(plans) {
+            if plan.copied.is_empty() {
+                self.remove_op_node(repl.node);
+                out_maps.push(IndexMap::new());
+                continue;
+            }
+            let mut out_map: IndexMap<NodeIndex, NodeIndex> =
+                IndexMap::with_capacity(plan.copied.len());
+            for old_index in &plan.copied {
+                let new_index = self.dag.add_node(repl.other.dag[*old_index].clone());
+                out_map.insert(*old_index, new_index);
+            }
+            for (source, target, wire) in &plan.internal {
+                self.dag
+                    .add_edge(out_map[source], out_map[target], wire.clone());
+            }
+            for (old_index, boundary, wire) in &plan.incoming {
+                self.dag
+                    .add_edge(*boundary, out_map[old_index], wire.clone());
+            }
+            for (old_index, boundary, wire) in &plan.outgoing {
+                self.dag
+                    .add_edge(out_map[old_index], *boundary, wire.clone());
+            }
+            self.remove_op_node(repl.node);
+            out_maps.push(out_map);
+        }
+
+        Ok(out_maps)
+    }
+
     fn substitute_node_with_subgraph(
         &mut self,
         py: Python,
@@ -4498,6 +6706,13 @@ impl DAGCircuit {
                 node.index()
             )));
         }
+        let mut var_map_idx: HashMap<Var, Var> = HashMap::with_capacity(var_map.bind(py).len());
+        for (other_var, self_var) in var_map.bind(py).iter() {
+            let other_var = other.lookup_var(&other_var)?;
+            let self_var = self.lookup_var(&self_var)?;
+            var_map_idx.insert(other_var, self_var);
+        }
+        let var_map = var_map_idx;
         let node_filter = |node: NodeIndex| -> bool {
             match other.dag[node] {
                 NodeType::Operation(_) => !other
@@ -4506,7 +6721,7 @@ impl DAGCircuit {
                     .any(|edge| match edge.weight() {
                         Wire::Qubit(qubit) => !qubit_map.contains_key(qubit),
                         Wire::Clbit(clbit) => !clbit_map.contains_key(clbit),
-                        Wire::Var(_) => todo!(),
+                        Wire::Var(var) => !var_map.contains_key(var),
                     }),
                 _ => return false,
             }
@@ -4515,6 +6730,7 @@ impl DAGCircuit {
             qubit_map.iter().map(|(x, y)| (*y, *x)).collect();
         let reverse_clbit_map: HashMap<Clbit, Clbit> =
             clbit_map.iter().map(|(x, y)| (*y, *x)).collect();
+        let reverse_var_map: HashMap<Var, Var> = var_map.iter().map(|(x, y)| (*y, *x)).collect();
         // Copy nodes from other to self
         let mut out_map: IndexMap<NodeIndex, NodeIndex> =
             IndexMap::with_capacity(other.dag.node_count());
@@ -4542,7 +6758,7 @@ impl DAGCircuit {
                 match edge.weight() {
                     Wire::Qubit(qubit) => Wire::Qubit(qubit_map[qubit]),
                     Wire::Clbit(clbit) => Wire::Clbit(clbit_map[clbit]),
-                    Wire::Var(_) => todo!(),
+                    Wire::Var(var) => Wire::Var(var_map[var]),
                 },
             );
         }
@@ -4557,7 +6773,7 @@ impl DAGCircuit {
                 let wire_output_id = match weight {
                     Wire::Qubit(qubit) => other.qubit_output_map.get(&reverse_qubit_map[&qubit]),
                     Wire::Clbit(clbit) => other.clbit_output_map.get(&reverse_clbit_map[&clbit]),
-                    Wire::Var(_) => todo!(),
+                    Wire::Var(var) => other.var_output_map.get(&reverse_var_map[&var]),
                 };
                 let old_index = wire_output_id
                     .map(|x| other.dag.neighbors_directed(*x, Incoming).next())
@@ -4579,7 +6795,7 @@ impl DAGCircuit {
                 let wire_input_id = match weight {
                     Wire::Qubit(qubit) => other.qubit_input_map.get(&reverse_qubit_map[&qubit]),
                     Wire::Clbit(clbit) => other.clbit_input_map.get(&reverse_clbit_map[&clbit]),
-                    Wire::Var(_) => todo!(),
+                    Wire::Var(var) => other.var_input_map.get(&reverse_var_map[&var]),
                 };
                 let old_index = wire_input_id
                     .map(|x| other.dag.neighbors_directed(*x, Outgoing).next())
@@ -4628,10 +6844,12 @@ impl DAGCircuit {
         let out_node = NodeType::VarOut(var.clone().unbind());
         let in_index = self.dag.add_node(in_node);
         let out_index = self.dag.add_node(out_node);
-        self.dag
-            .add_edge(in_index, out_index, Wire::Var(var.clone().unbind()));
-        self.var_input_map.insert(var.clone().unbind(), in_index);
-        self.var_output_map.insert(var.clone().unbind(), out_index);
+        let var_index = Var(self.var_order.len() as u32);
+        self.var_order.push(var.clone().unbind());
+        self.var_indices.insert(var_name.clone(), var_index);
+        self.dag.add_edge(in_index, out_index, Wire::Var(var_index));
+        self.var_input_map.insert(var_index, in_index);
+        self.var_output_map.insert(var_index, out_index);
         self.vars_by_type[type_ as usize]
             .bind(py)
             .add(var.clone().unbind())?;
@@ -4646,4 +6864,67 @@ impl DAGCircuit {
         );
         Ok(())
     }
+
+    /// Check that every 2-qubit operation in this DAG is between two qubits that are adjacent in
+    /// `coupling`, recursing into control-flow blocks.
+    ///
+    /// `coupling` is a set of directed edges `[physical_qubit_0, physical_qubit_1]`; an operation
+    /// is considered supported if either direction of its qubit pair appears in `coupling`.
+    ///
+    /// Returns the name and physical qubits of the first offending operation found, in the same
+    /// `Option<(String, [u32; 2])>` style as the `CheckMap`-style checks in `apply_layout`.
+    /// Verify every 2-qubit operation in this DAG (recursing into control-flow blocks) acts on an
+    /// edge of `coupling`, a set of physical-qubit pairs. `qubit_map` gives the physical qubit for
+    /// each of this DAG's qubits, by index.
+    ///
+    /// Returns `None` if every 2-qubit operation is supported, or else `(name, qubits)` for
+    /// the first unsupported operation found, giving its name and the offending physical qubits.
+    /// Operations on three or more qubits are not checked.
+    /// Exposed crate-wide (rather than just to Python via `check_coupling`) so other native
+    /// transpiler passes, such as routing/layout checks in `accelerate`, can call it directly
+    /// instead of round-tripping through Python.
+    pub fn check_map(
+        &self,
+        py: Python,
+        coupling: &HashSet<[u32; 2]>,
+        qubit_map: &[Qubit],
+    ) -> PyResult<Option<(String, [u32; 2])>> {
+        let circuit_to_dag = CIRCUIT_TO_DAG.get_bound(py);
+        for (_, weight) in self.dag.node_references() {
+            let NodeType::Operation(packed) = weight else {
+                continue;
+            };
+            let qargs = self.qargs_cache.intern(packed.qubits_id);
+            if qargs.len() == 2 {
+                let physical = [
+                    qubit_map[qargs[0].0 as usize].0,
+                    qubit_map[qargs[1].0 as usize].0,
+                ];
+                if !coupling.contains(&physical) && !coupling.contains(&[physical[1], physical[0]])
+                {
+                    return Ok(Some((packed.op.name().to_string(), physical)));
+                }
+            }
+
+            let op_name = packed.op.name();
+            if CONTROL_FLOW_OP_NAMES.contains(&op_name) {
+                let py_op = packed.unpack_py_op(py)?.into_bound(py);
+                let blocks = py_op.getattr(intern!(py, "blocks"))?;
+                for block in blocks.iter()? {
+                    let block = block?;
+                    let inner_dag: DAGCircuit = circuit_to_dag.call1((block,))?.extract()?;
+                    // Inner block qubits are contiguous from 0, so they can be mapped onto the
+                    // outer qubits positionally via this operation's own qargs.
+                    let inner_qubit_map: Vec<Qubit> =
+                        qargs.iter().map(|q| qubit_map[q.0 as usize]).collect();
+                    if let Some(violation) =
+                        inner_dag.check_map(py, coupling, &inner_qubit_map)?
+                    {
+                        return Ok(Some(violation));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
 }
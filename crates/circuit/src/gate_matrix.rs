@@ -0,0 +1,766 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2024
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+use ndarray::Array2;
+use num_complex::Complex64;
+use std::f64::consts::FRAC_1_SQRT_2;
+
+// Static, parameter-free gate matrices, in the same qubit-0-is-rightmost basis ordering used
+// throughout the rest of the crate (e.g. `CX_GATE`'s control is qubit 0, target qubit 1).
+
+pub static ONE_QUBIT_IDENTITY: [[Complex64; 2]; 2] = [
+    [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+    [Complex64::new(0., 0.), Complex64::new(1., 0.)],
+];
+
+pub static X_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(0., 0.), Complex64::new(1., 0.)],
+    [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+];
+
+pub static Y_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(0., 0.), Complex64::new(0., -1.)],
+    [Complex64::new(0., 1.), Complex64::new(0., 0.)],
+];
+
+pub static Z_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+    [Complex64::new(0., 0.), Complex64::new(-1., 0.)],
+];
+
+pub static H_GATE: [[Complex64; 2]; 2] = [
+    [
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+    ],
+    [
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+        Complex64::new(-FRAC_1_SQRT_2, 0.),
+    ],
+];
+
+pub static S_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+    [Complex64::new(0., 0.), Complex64::new(0., 1.)],
+];
+
+pub static SDG_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+    [Complex64::new(0., 0.), Complex64::new(0., -1.)],
+];
+
+pub static T_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+    ],
+];
+
+pub static TDG_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+    ],
+];
+
+pub static SX_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(0.5, 0.5), Complex64::new(0.5, -0.5)],
+    [Complex64::new(0.5, -0.5), Complex64::new(0.5, 0.5)],
+];
+
+pub static SXDG_GATE: [[Complex64; 2]; 2] = [
+    [Complex64::new(0.5, -0.5), Complex64::new(0.5, 0.5)],
+    [Complex64::new(0.5, 0.5), Complex64::new(0.5, -0.5)],
+];
+
+pub static CX_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+];
+
+pub static CY_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., -1.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 1.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+];
+
+pub static CZ_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(-1., 0.),
+    ],
+];
+
+pub static CH_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(-FRAC_1_SQRT_2, 0.),
+    ],
+];
+
+pub static CS_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 1.),
+    ],
+];
+
+pub static CSDG_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., -1.),
+    ],
+];
+
+pub static CSX_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0.5, 0.5),
+        Complex64::new(0., 0.),
+        Complex64::new(0.5, -0.5),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0.5, -0.5),
+        Complex64::new(0., 0.),
+        Complex64::new(0.5, 0.5),
+    ],
+];
+
+pub static SWAP_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+    ],
+];
+
+pub static ISWAP_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 1.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 1.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+    ],
+];
+
+pub static DCX_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+];
+
+pub static ECR_GATE: [[Complex64; 4]; 4] = [
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., FRAC_1_SQRT_2),
+    ],
+    [
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., -FRAC_1_SQRT_2),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., FRAC_1_SQRT_2),
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+    ],
+    [
+        Complex64::new(0., -FRAC_1_SQRT_2),
+        Complex64::new(0., 0.),
+        Complex64::new(FRAC_1_SQRT_2, 0.),
+        Complex64::new(0., 0.),
+    ],
+];
+
+pub static CCX_GATE: [[Complex64; 8]; 8] = [
+    [
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+    ],
+    [
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(1., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+        Complex64::new(0., 0.),
+    ],
+];
+
+pub static CCZ_GATE: [[Complex64; 8]; 8] = {
+    let mut rows = [[Complex64::new(0., 0.); 8]; 8];
+    let mut i = 0;
+    while i < 8 {
+        rows[i][i] = Complex64::new(1., 0.);
+        i += 1;
+    }
+    rows[7][7] = Complex64::new(-1., 0.);
+    rows
+};
+
+pub static CSWAP_GATE: [[Complex64; 8]; 8] = {
+    let mut rows = [[Complex64::new(0., 0.); 8]; 8];
+    let mut i = 0;
+    while i < 8 {
+        rows[i][i] = Complex64::new(1., 0.);
+        i += 1;
+    }
+    // Control is qubit 0; swap qubits 1 and 2 when it is set, i.e. swap basis states 3 (0b011)
+    // and 5 (0b101).
+    rows[3][3] = Complex64::new(0., 0.);
+    rows[5][5] = Complex64::new(0., 0.);
+    rows[3][5] = Complex64::new(1., 0.);
+    rows[5][3] = Complex64::new(1., 0.);
+    rows
+};
+
+/// The "simplified Toffoli" relative-phase `RCCXGate`: agrees with `CCX_GATE` (controls qubits 0
+/// and 1, target qubit 2) up to relative phases -- basis state 5 picks up a -1, and the exchange
+/// of states 3 and 7 carries phases -i and i -- which is cheaper to synthesize out of single- and
+/// two-qubit gates than an exact Toffoli.
+pub static RCCX_GATE: [[Complex64; 8]; 8] = {
+    let mut rows = [[Complex64::new(0., 0.); 8]; 8];
+    let mut i = 0;
+    while i < 8 {
+        rows[i][i] = Complex64::new(1., 0.);
+        i += 1;
+    }
+    rows[3][3] = Complex64::new(0., 0.);
+    rows[7][7] = Complex64::new(0., 0.);
+    rows[3][7] = Complex64::new(0., -1.);
+    rows[7][3] = Complex64::new(0., 1.);
+    rows[5][5] = Complex64::new(-1., 0.);
+    rows
+};
+
+/// The relative-phase three-control analogue of [RCCX_GATE].
+pub static RC3X_GATE: [[Complex64; 16]; 16] = {
+    let mut rows = [[Complex64::new(0., 0.); 16]; 16];
+    let mut i = 0;
+    while i < 16 {
+        rows[i][i] = Complex64::new(1., 0.);
+        i += 1;
+    }
+    rows[3][3] = Complex64::new(0., 1.);
+    rows[11][11] = Complex64::new(0., -1.);
+    rows[7][7] = Complex64::new(0., 0.);
+    rows[15][15] = Complex64::new(0., 0.);
+    rows[7][15] = Complex64::new(1., 0.);
+    rows[15][7] = Complex64::new(-1., 0.);
+    rows
+};
+
+// Parametrized single-qubit gates.
+
+pub fn global_phase_gate(theta: f64) -> [[Complex64; 1]; 1] {
+    [[Complex64::new(0., theta).exp()]]
+}
+
+pub fn phase_gate(lam: f64) -> [[Complex64; 2]; 2] {
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., lam).exp()],
+    ]
+}
+
+pub fn u1_gate(lam: f64) -> [[Complex64; 2]; 2] {
+    phase_gate(lam)
+}
+
+pub fn u2_gate(phi: f64, lam: f64) -> [[Complex64; 2]; 2] {
+    [
+        [
+            Complex64::new(FRAC_1_SQRT_2, 0.),
+            -Complex64::new(0., lam).exp() * FRAC_1_SQRT_2,
+        ],
+        [
+            Complex64::new(0., phi).exp() * FRAC_1_SQRT_2,
+            Complex64::new(0., phi + lam).exp() * FRAC_1_SQRT_2,
+        ],
+    ]
+}
+
+pub fn u3_gate(theta: f64, phi: f64, lam: f64) -> [[Complex64; 2]; 2] {
+    let cos = (theta / 2.).cos();
+    let sin = (theta / 2.).sin();
+    [
+        [
+            Complex64::new(cos, 0.),
+            -Complex64::new(0., lam).exp() * sin,
+        ],
+        [
+            Complex64::new(0., phi).exp() * sin,
+            Complex64::new(0., phi + lam).exp() * cos,
+        ],
+    ]
+}
+
+pub fn u_gate(theta: f64, phi: f64, lam: f64) -> [[Complex64; 2]; 2] {
+    u3_gate(theta, phi, lam)
+}
+
+pub fn rx_gate(theta: f64) -> [[Complex64; 2]; 2] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let isin = Complex64::new(0., -(theta / 2.).sin());
+    [[cos, isin], [isin, cos]]
+}
+
+pub fn ry_gate(theta: f64) -> [[Complex64; 2]; 2] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let sin = Complex64::new((theta / 2.).sin(), 0.);
+    [[cos, -sin], [sin, cos]]
+}
+
+pub fn rz_gate(theta: f64) -> [[Complex64; 2]; 2] {
+    [
+        [Complex64::new(0., -theta / 2.).exp(), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., theta / 2.).exp()],
+    ]
+}
+
+pub fn r_gate(theta: f64, phi: f64) -> [[Complex64; 2]; 2] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let sin = (theta / 2.).sin();
+    [
+        [cos, -Complex64::new(0., -phi).exp() * Complex64::new(0., sin)],
+        [
+            -Complex64::new(0., phi).exp() * Complex64::new(0., sin),
+            cos,
+        ],
+    ]
+}
+
+// Controlled single-qubit-rotation gates: identity except on the `{1, 3}` subspace where the
+// (qubit-0) control is set, which carries the corresponding single-qubit rotation.
+
+pub fn crx_gate(theta: f64) -> [[Complex64; 4]; 4] {
+    let [[a, b], [c, d]] = rx_gate(theta);
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), a, Complex64::new(0., 0.), b],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), c, Complex64::new(0., 0.), d],
+    ]
+}
+
+pub fn cry_gate(theta: f64) -> [[Complex64; 4]; 4] {
+    let [[a, b], [c, d]] = ry_gate(theta);
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), a, Complex64::new(0., 0.), b],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), c, Complex64::new(0., 0.), d],
+    ]
+}
+
+pub fn crz_gate(theta: f64) -> [[Complex64; 4]; 4] {
+    let [[a, b], [c, d]] = rz_gate(theta);
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), a, Complex64::new(0., 0.), b],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), c, Complex64::new(0., 0.), d],
+    ]
+}
+
+pub fn cp_gate(lam: f64) -> [[Complex64; 4]; 4] {
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., lam).exp()],
+    ]
+}
+
+pub fn cu1_gate(lam: f64) -> [[Complex64; 4]; 4] {
+    cp_gate(lam)
+}
+
+pub fn cu3_gate(theta: f64, phi: f64, lam: f64) -> [[Complex64; 4]; 4] {
+    let [[a, b], [c, d]] = u3_gate(theta, phi, lam);
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), a, Complex64::new(0., 0.), b],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), c, Complex64::new(0., 0.), d],
+    ]
+}
+
+pub fn cu_gate(theta: f64, phi: f64, lam: f64, gamma: f64) -> [[Complex64; 4]; 4] {
+    let phase = Complex64::new(0., gamma).exp();
+    let [[a, b], [c, d]] = u3_gate(theta, phi, lam);
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), phase * a, Complex64::new(0., 0.), phase * b],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), phase * c, Complex64::new(0., 0.), phase * d],
+    ]
+}
+
+// Two-qubit parametrized entangling gates.
+
+pub fn rxx_gate(theta: f64) -> [[Complex64; 4]; 4] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let isin = Complex64::new(0., -(theta / 2.).sin());
+    [
+        [cos, Complex64::new(0., 0.), Complex64::new(0., 0.), isin],
+        [Complex64::new(0., 0.), cos, isin, Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), isin, cos, Complex64::new(0., 0.)],
+        [isin, Complex64::new(0., 0.), Complex64::new(0., 0.), cos],
+    ]
+}
+
+pub fn ryy_gate(theta: f64) -> [[Complex64; 4]; 4] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let isin = Complex64::new(0., (theta / 2.).sin());
+    [
+        [cos, Complex64::new(0., 0.), Complex64::new(0., 0.), isin],
+        [Complex64::new(0., 0.), cos, -isin, Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), -isin, cos, Complex64::new(0., 0.)],
+        [isin, Complex64::new(0., 0.), Complex64::new(0., 0.), cos],
+    ]
+}
+
+pub fn rzz_gate(theta: f64) -> [[Complex64; 4]; 4] {
+    let pos = Complex64::new(0., theta / 2.).exp();
+    let neg = Complex64::new(0., -theta / 2.).exp();
+    [
+        [neg, Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), pos, Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), pos, Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), neg],
+    ]
+}
+
+pub fn rzx_gate(theta: f64) -> [[Complex64; 4]; 4] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let isin = Complex64::new(0., -(theta / 2.).sin());
+    [
+        [cos, Complex64::new(0., 0.), isin, Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), cos, Complex64::new(0., 0.), -isin],
+        [isin, Complex64::new(0., 0.), cos, Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), -isin, Complex64::new(0., 0.), cos],
+    ]
+}
+
+pub fn xx_minus_yy_gate(theta: f64, beta: f64) -> [[Complex64; 4]; 4] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let isin = Complex64::new(0., -(theta / 2.).sin());
+    let pos_beta = Complex64::new(0., beta).exp();
+    let neg_beta = Complex64::new(0., -beta).exp();
+    [
+        [cos, Complex64::new(0., 0.), Complex64::new(0., 0.), isin * neg_beta],
+        [Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.), Complex64::new(0., 0.)],
+        [isin * pos_beta, Complex64::new(0., 0.), Complex64::new(0., 0.), cos],
+    ]
+}
+
+pub fn xx_plus_yy_gate(theta: f64, beta: f64) -> [[Complex64; 4]; 4] {
+    let cos = Complex64::new((theta / 2.).cos(), 0.);
+    let isin = Complex64::new(0., -(theta / 2.).sin());
+    let pos_beta = Complex64::new(0., beta).exp();
+    let neg_beta = Complex64::new(0., -beta).exp();
+    [
+        [Complex64::new(1., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), cos, isin * neg_beta, Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), isin * pos_beta, cos, Complex64::new(0., 0.)],
+        [Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(0., 0.), Complex64::new(1., 0.)],
+    ]
+}
+
+/// Build an `n`-controlled version of the single-target-qubit-register `base` unitary, as the
+/// identity on all other basis states and `base` on the subspace where the low `num_ctrl_qubits`
+/// bits (i.e. the control qubits, by this crate's convention that qubit 0 is the least
+/// significant/right-most wire) are all set. Used to back the various multi-controlled standard
+/// gates (`C3XGate`, `C4XGate`, `C3SXGate`, ...) instead of storing their exponentially large
+/// matrices as static arrays.
+pub fn mcx_matrix(num_ctrl_qubits: u32, base: &Array2<Complex64>) -> Array2<Complex64> {
+    let base_dim = base.shape()[0];
+    let step = 1usize << num_ctrl_qubits;
+    let offset = step - 1;
+    let mut matrix = Array2::<Complex64>::eye(step * base_dim);
+    for i in 0..base_dim {
+        for j in 0..base_dim {
+            matrix[[offset + i * step, offset + j * step]] = base[[i, j]];
+        }
+    }
+    matrix
+}
@@ -0,0 +1,143 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2024
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+//! A lowered, serde-friendly representation of a [crate::dag_circuit::DAGCircuit], used by
+//! `DAGCircuit::to_json`/`DAGCircuit::from_json` as a stable, Python-independent interchange
+//! format for the graph structure (nodes, wires, and interned bit indices). This is distinct
+//! from `_to_dot`/`build_dot`, which only ever produces a one-way Graphviz rendering for
+//! visualization; this format is meant to be read back in.
+//!
+//! Only the bit-level graph structure round-trips with full fidelity: `StandardGate` operations
+//! round-trip exactly (via their raw discriminant byte), but an arbitrary Python-defined gate or
+//! instruction is reduced to its name/arity/params/label/duration/unit/condition and rebuilt on
+//! import as a generic `Instruction`, which loses any custom behavior the original Python class
+//! implemented. Similarly, a non-numeric [crate::operations::Param] (a `ParameterExpression` or
+//! other Python object) is captured only as its `repr()`. Registers (`qregs`/`cregs`) are not
+//! part of this format; only the loose bits that the graph's wires touch are reconstructed.
+//!
+//! Real-time classical variables (`Wire::Var`, `NodeType::VarIn`/`VarOut`) are captured by name
+//! and [SerializedVarType] so `to_json` can describe a DAG that uses them, but `from_json` cannot
+//! rebuild the live Python `Var` object a variable wire needs (there is no accessible constructor
+//! for one from this crate, only `DAGCircuit::add_var`, which requires an already-built object) —
+//! a DAG containing a var is therefore accepted by `to_json` but rejected by `from_json`.
+
+use crate::operations::Param;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A lowered, serde-friendly stand-in for the crate-private `DAGVarType`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SerializedVarType {
+    Input,
+    Capture,
+    Declare,
+}
+
+/// A lowered, serde-friendly stand-in for [crate::operations::Param].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SerializedParam {
+    /// A [crate::operations::Param::Float], round-tripped exactly.
+    Float(f64),
+    /// A [crate::operations::Param::ParameterExpression] or [crate::operations::Param::Obj],
+    /// captured only as its `repr()`. Reconstructing the original Python object from this string
+    /// is not attempted.
+    Opaque(String),
+}
+
+impl SerializedParam {
+    pub fn from_param(py: Python, param: &Param) -> PyResult<Self> {
+        Ok(match param {
+            Param::Float(value) => SerializedParam::Float(*value),
+            Param::ParameterExpression(obj) | Param::Obj(obj) => {
+                SerializedParam::Opaque(obj.bind(py).repr()?.to_string())
+            }
+        })
+    }
+
+    /// The inverse of [SerializedParam::from_param]. An `Opaque` value cannot be reconstructed
+    /// (its original Python object was never captured, only its `repr()`), so it comes back as
+    /// `Param::Float(0.0)`; this is the one place a round-trip through this format can silently
+    /// change a circuit's behavior, so callers working with `ParameterExpression`-valued params
+    /// or global phase should not rely on `to_json`/`from_json` for full fidelity.
+    pub fn to_param(&self) -> Param {
+        match self {
+            SerializedParam::Float(value) => Param::Float(*value),
+            SerializedParam::Opaque(_) => Param::Float(0.0),
+        }
+    }
+}
+
+/// A lowered stand-in for a single `PackedInstruction`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializedOp {
+    pub name: String,
+    /// `Some(discriminant)` when the operation is a [crate::operations::StandardGate], letting
+    /// `from_json` reconstruct it exactly via `bytemuck::checked::try_cast`. `None` for any other
+    /// operation, which is rebuilt as a generic `Instruction` from the remaining fields.
+    pub standard_gate: Option<u8>,
+    pub num_qubits: u32,
+    pub num_clbits: u32,
+    pub params: Vec<SerializedParam>,
+    pub label: Option<String>,
+    pub duration: Option<String>,
+    pub unit: Option<String>,
+    /// The condition's `repr()`, captured for informational purposes only. `from_json` does not
+    /// attempt to re-apply a condition to the reconstructed operation, since rebuilding the
+    /// original condition resource (a `Clbit` or `ClassicalRegister`) from its `repr()` alone is
+    /// not generally possible.
+    pub condition: Option<String>,
+}
+
+/// A lowered stand-in for a single node in the DAG's `StableDiGraph`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SerializedNode {
+    QubitIn(u32),
+    QubitOut(u32),
+    ClbitIn(u32),
+    ClbitOut(u32),
+    /// Captured for `to_json`'s sake only; see the module-level docs on why `from_json` cannot
+    /// rebuild the live `Var` object this node's wire needs.
+    VarIn { name: String, type_: SerializedVarType },
+    /// As [SerializedNode::VarIn].
+    VarOut { name: String },
+    Operation {
+        qubits: Vec<u32>,
+        clbits: Vec<u32>,
+        op: SerializedOp,
+    },
+}
+
+/// The kind and interned index (or, for a var, name) of a single edge's wire.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SerializedWireKind {
+    Qubit(u32),
+    Clbit(u32),
+    Var(String),
+}
+
+/// A single edge of the `StableDiGraph`, with its endpoints given as positions into
+/// [SerializedDag::nodes].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializedWire {
+    pub source: usize,
+    pub target: usize,
+    pub wire: SerializedWireKind,
+}
+
+/// The full lowered representation of a [crate::dag_circuit::DAGCircuit], as produced by
+/// `DAGCircuit::to_json` and consumed by `DAGCircuit::from_json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializedDag {
+    pub global_phase: SerializedParam,
+    pub nodes: Vec<SerializedNode>,
+    pub edges: Vec<SerializedWire>,
+}
@@ -15,8 +15,10 @@ pub mod circuit_instruction;
 pub mod dag_circuit;
 pub mod gate_matrix;
 pub mod imports;
+pub mod interchange;
 pub mod operations;
 pub mod parameter_table;
+pub mod qasm;
 
 mod bit_data;
 mod dag_node;
@@ -25,6 +27,7 @@ mod interner;
 
 use pyo3::prelude::*;
 use pyo3::types::{PySequence, PySlice, PyTuple};
+use pyo3::wrap_pyfunction;
 use std::ops::Deref;
 
 /// A private enumeration type used to extract arguments to pymethod
@@ -92,5 +95,6 @@ pub fn circuit(m: Bound<PyModule>) -> PyResult<()> {
     m.add_class::<operations::PyInstruction>()?;
     m.add_class::<operations::PyGate>()?;
     m.add_class::<operations::PyOperation>()?;
+    m.add_function(wrap_pyfunction!(qasm::parse_qasm, &m)?)?;
     Ok(())
 }
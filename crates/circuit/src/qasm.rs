@@ -0,0 +1,686 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2024
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+//! A from-scratch OpenQASM front-end that builds a [`CircuitData`] of [`StandardGate`]s directly
+//! in Rust, so importing a QASM program doesn't need a round trip through Python.
+//!
+//! This covers the common subset shared by OpenQASM 2.0 and the parts of OpenQASM 3 that matter
+//! for gate-level circuits: register declarations (`qreg`/`creg` or OpenQASM 3's `qubit`/`bit`),
+//! built-in and `gate ... { ... }`-defined gate calls (with register broadcasting), and
+//! parameter expressions over `+ - * /`, unary minus, parentheses, numeric literals and `pi`.
+//! `barrier`, classical control flow (`if`), `measure`, and `reset` are parsed just enough to be skipped,
+//! since a `StandardGate`-only `CircuitData` has no representation for them; callers that need
+//! those should continue to go through the existing Python-side QASM importer.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use smallvec::{smallvec, SmallVec};
+
+use crate::circuit_data::CircuitData;
+use crate::operations::{Param, StandardGate};
+use crate::Qubit;
+
+const FLOAT_ZERO: Param = Param::Float(0.0);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Arrow,
+    Symbol(char),
+}
+
+fn tokenize(source: &str) -> PyResult<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' {
+            i += 1;
+            let start = i;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            tokens.push(Token::Str(chars[start..i].iter().collect()));
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            let mut is_float = false;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit()
+                    || chars[i] == '.'
+                    || chars[i] == 'e'
+                    || chars[i] == 'E'
+                    || ((chars[i] == '+' || chars[i] == '-')
+                        && matches!(chars.get(i.wrapping_sub(1)), Some('e') | Some('E'))))
+            {
+                if matches!(chars[i], '.' | 'e' | 'E') {
+                    is_float = true;
+                }
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let bad_number = || PyValueError::new_err(format!("invalid numeric literal '{text}'"));
+            tokens.push(if is_float {
+                Token::Float(text.parse().map_err(|_| bad_number())?)
+            } else {
+                Token::Int(text.parse().map_err(|_| bad_number())?)
+            });
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '-' && chars.get(i + 1) == Some(&'>') {
+            tokens.push(Token::Arrow);
+            i += 2;
+        } else if "{}()[];,*/+-^=".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(PyValueError::new_err(format!(
+                "unexpected character '{c}' in OpenQASM source"
+            )));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A (not yet numerically evaluated) parameter expression: literals/`pi`/arithmetic over
+/// literals, or a reference to a formal parameter name bound inside a `gate ... { ... }` body.
+#[derive(Clone, Debug)]
+enum Expr {
+    Num(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, bindings: &HashMap<String, f64>) -> PyResult<f64> {
+        Ok(match self {
+            Expr::Num(n) => *n,
+            Expr::Var(name) => *bindings
+                .get(name)
+                .ok_or_else(|| PyValueError::new_err(format!("unbound parameter '{name}'")))?,
+            Expr::Neg(e) => -e.eval(bindings)?,
+            Expr::Add(a, b) => a.eval(bindings)? + b.eval(bindings)?,
+            Expr::Sub(a, b) => a.eval(bindings)? - b.eval(bindings)?,
+            Expr::Mul(a, b) => a.eval(bindings)? * b.eval(bindings)?,
+            Expr::Div(a, b) => a.eval(bindings)? / b.eval(bindings)?,
+        })
+    }
+}
+
+/// A reference to a qubit argument as written at a call site: either a single indexed qubit of a
+/// register (`q[2]`), a whole register to broadcast over (`q`), or (only inside a `gate ... {
+/// ... }` body) a bare formal qubit name.
+#[derive(Clone, Debug)]
+struct QubitRef {
+    name: String,
+    index: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+struct GateCall {
+    name: String,
+    args: Vec<Expr>,
+    qubits: Vec<QubitRef>,
+}
+
+/// A user `gate name(params) qubits { body }` definition, kept in its unevaluated/unresolved
+/// form so it can be inlined afresh (substituting this call's own argument expressions and
+/// actual qubits) at every call site -- mirroring the way OpenQASM itself has no notion of a
+/// gate "object" independent of a particular call.
+#[derive(Clone, Debug)]
+struct GateDef {
+    params: Vec<String>,
+    qubits: Vec<String>,
+    body: Vec<GateCall>,
+}
+
+#[derive(Default)]
+struct Registers {
+    qubits: HashMap<String, (u32, u32)>,
+    clbits: HashMap<String, (u32, u32)>,
+    num_qubits: u32,
+    num_clbits: u32,
+}
+
+impl Registers {
+    fn declare_qreg(&mut self, name: String, size: u32) {
+        self.qubits.insert(name, (self.num_qubits, size));
+        self.num_qubits += size;
+    }
+
+    fn declare_creg(&mut self, name: String, size: u32) {
+        self.clbits.insert(name, (self.num_clbits, size));
+        self.num_clbits += size;
+    }
+
+    /// Resolve a top-level qubit argument to the global qubit indices it refers to: one index
+    /// for `name[i]`, or the whole register (in order) for a bare `name`.
+    fn resolve(&self, qref: &QubitRef) -> PyResult<Vec<Qubit>> {
+        let &(start, size) = self.qubits.get(&qref.name).ok_or_else(|| {
+            PyValueError::new_err(format!("reference to undeclared register '{}'", qref.name))
+        })?;
+        Ok(match qref.index {
+            Some(i) if i < size => vec![Qubit(start + i)],
+            Some(i) => {
+                return Err(PyValueError::new_err(format!(
+                    "index {i} out of range for register '{}' of size {size}",
+                    qref.name
+                )))
+            }
+            None => (start..start + size).map(Qubit).collect(),
+        })
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn bump(&mut self) -> PyResult<Token> {
+        let tok = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| PyValueError::new_err("unexpected end of OpenQASM source"))?;
+        self.pos += 1;
+        Ok(tok)
+    }
+
+    fn expect_symbol(&mut self, c: char) -> PyResult<()> {
+        match self.bump()? {
+            Token::Symbol(s) if s == c => Ok(()),
+            other => Err(PyValueError::new_err(format!("expected '{c}', found {other:?}"))),
+        }
+    }
+
+    fn eat_symbol(&mut self, c: char) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_ident(&mut self) -> PyResult<String> {
+        match self.bump()? {
+            Token::Ident(name) => Ok(name),
+            other => Err(PyValueError::new_err(format!("expected an identifier, found {other:?}"))),
+        }
+    }
+
+    fn expect_uint(&mut self) -> PyResult<u32> {
+        match self.bump()? {
+            Token::Int(n) if n >= 0 => Ok(n as u32),
+            other => Err(PyValueError::new_err(format!(
+                "expected a non-negative integer, found {other:?}"
+            ))),
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn parse_expr(&mut self) -> PyResult<Expr> {
+        let mut lhs = self.parse_term()?;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol('+')) => {
+                    self.pos += 1;
+                    lhs = Expr::Add(Box::new(lhs), Box::new(self.parse_term()?));
+                }
+                Some(Token::Symbol('-')) => {
+                    self.pos += 1;
+                    lhs = Expr::Sub(Box::new(lhs), Box::new(self.parse_term()?));
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn parse_term(&mut self) -> PyResult<Expr> {
+        let mut lhs = self.parse_factor()?;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol('*')) => {
+                    self.pos += 1;
+                    lhs = Expr::Mul(Box::new(lhs), Box::new(self.parse_factor()?));
+                }
+                Some(Token::Symbol('/')) => {
+                    self.pos += 1;
+                    lhs = Expr::Div(Box::new(lhs), Box::new(self.parse_factor()?));
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    // factor := '-' factor | primary
+    fn parse_factor(&mut self) -> PyResult<Expr> {
+        if self.eat_symbol('-') {
+            return Ok(Expr::Neg(Box::new(self.parse_factor()?)));
+        }
+        self.parse_primary()
+    }
+
+    // primary := INT | FLOAT | 'pi' | IDENT | '(' expr ')'
+    fn parse_primary(&mut self) -> PyResult<Expr> {
+        match self.bump()? {
+            Token::Int(n) => Ok(Expr::Num(n as f64)),
+            Token::Float(f) => Ok(Expr::Num(f)),
+            Token::Ident(name) if name == "pi" => Ok(Expr::Num(PI)),
+            Token::Ident(name) => Ok(Expr::Var(name)),
+            Token::Symbol('(') => {
+                let inner = self.parse_expr()?;
+                self.expect_symbol(')')?;
+                Ok(inner)
+            }
+            other => Err(PyValueError::new_err(format!(
+                "expected a number, 'pi', a parameter name, or '(', found {other:?}"
+            ))),
+        }
+    }
+
+    /// `(expr, expr, ...)`, or no arguments at all if there's no parenthesized list.
+    fn parse_call_args(&mut self) -> PyResult<Vec<Expr>> {
+        if !self.eat_symbol('(') {
+            return Ok(Vec::new());
+        }
+        let mut args = Vec::new();
+        if !self.eat_symbol(')') {
+            loop {
+                args.push(self.parse_expr()?);
+                if self.eat_symbol(')') {
+                    break;
+                }
+                self.expect_symbol(',')?;
+            }
+        }
+        Ok(args)
+    }
+
+    /// `name[index]` or bare `name`.
+    fn parse_qubit_ref(&mut self) -> PyResult<QubitRef> {
+        let name = self.expect_ident()?;
+        let index = if self.eat_symbol('[') {
+            let i = self.expect_uint()?;
+            self.expect_symbol(']')?;
+            Some(i)
+        } else {
+            None
+        };
+        Ok(QubitRef { name, index })
+    }
+
+    fn parse_qubit_ref_list(&mut self) -> PyResult<Vec<QubitRef>> {
+        let mut qubits = vec![self.parse_qubit_ref()?];
+        while self.eat_symbol(',') {
+            qubits.push(self.parse_qubit_ref()?);
+        }
+        Ok(qubits)
+    }
+
+    fn parse_gate_call(&mut self, name: String) -> PyResult<GateCall> {
+        let args = self.parse_call_args()?;
+        let qubits = self.parse_qubit_ref_list()?;
+        self.expect_symbol(';')?;
+        Ok(GateCall { name, args, qubits })
+    }
+
+    /// `gate name(params) qubits { body }`; `(params)` may be omitted entirely.
+    fn parse_gate_def(&mut self) -> PyResult<(String, GateDef)> {
+        let name = self.expect_ident()?;
+        let mut params = Vec::new();
+        if self.eat_symbol('(') {
+            if !self.eat_symbol(')') {
+                loop {
+                    params.push(self.expect_ident()?);
+                    if self.eat_symbol(')') {
+                        break;
+                    }
+                    self.expect_symbol(',')?;
+                }
+            }
+        }
+        let mut qubits = vec![self.expect_ident()?];
+        while self.eat_symbol(',') {
+            qubits.push(self.expect_ident()?);
+        }
+        self.expect_symbol('{')?;
+        let mut body = Vec::new();
+        while !self.eat_symbol('}') {
+            let call_name = self.expect_ident()?;
+            body.push(self.parse_gate_call(call_name)?);
+        }
+        Ok((
+            name,
+            GateDef {
+                params,
+                qubits,
+                body,
+            },
+        ))
+    }
+
+    /// Skip tokens up to (and including) the next `;`, for statements this front-end parses only
+    /// far enough to recognize and discard, since `CircuitData` has no slot for them.
+    fn skip_statement(&mut self) -> PyResult<()> {
+        loop {
+            match self.bump()? {
+                Token::Symbol(';') => return Ok(()),
+                _ => continue,
+            }
+        }
+    }
+}
+
+/// The handful of names every OpenQASM `gate` body / call site may use that this crate maps
+/// directly onto a `StandardGate`, independent of anything a `qelib1.inc`-style `include` would
+/// otherwise need to supply.
+fn standard_gate_for(name: &str, num_args: usize) -> Option<StandardGate> {
+    use StandardGate::*;
+    Some(match (name, num_args) {
+        ("id", 1) => IGate,
+        ("x", 1) => XGate,
+        ("y", 1) => YGate,
+        ("z", 1) => ZGate,
+        ("h", 1) => HGate,
+        ("s", 1) => SGate,
+        ("sdg", 1) => SdgGate,
+        ("t", 1) => TGate,
+        ("tdg", 1) => TdgGate,
+        ("sx", 1) => SXGate,
+        ("sxdg", 1) => SXdgGate,
+        ("u1", 1) => U1Gate,
+        ("u2", 1) => U2Gate,
+        ("u3", 1) | ("u", 1) => U3Gate,
+        ("p", 1) => PhaseGate,
+        ("rx", 1) => RXGate,
+        ("ry", 1) => RYGate,
+        ("rz", 1) => RZGate,
+        ("r", 1) => RGate,
+        ("cx", 2) => CXGate,
+        ("cy", 2) => CYGate,
+        ("cz", 2) => CZGate,
+        ("ch", 2) => CHGate,
+        ("swap", 2) => SwapGate,
+        ("iswap", 2) => ISwapGate,
+        ("dcx", 2) => DCXGate,
+        ("ecr", 2) => ECRGate,
+        ("cp", 2) => CPhaseGate,
+        ("cu1", 2) => CU1Gate,
+        ("cu3", 2) => CU3Gate,
+        ("cu", 2) => CUGate,
+        ("crx", 2) => CRXGate,
+        ("cry", 2) => CRYGate,
+        ("crz", 2) => CRZGate,
+        ("cs", 2) => CSGate,
+        ("csdg", 2) => CSdgGate,
+        ("csx", 2) => CSXGate,
+        ("rxx", 2) => RXXGate,
+        ("ryy", 2) => RYYGate,
+        ("rzz", 2) => RZZGate,
+        ("rzx", 2) => RZXGate,
+        ("xx_minus_yy", 2) => XXMinusYYGate,
+        ("xx_plus_yy", 2) => XXPlusYYGate,
+        ("ccx", 3) => CCXGate,
+        ("ccz", 3) => CCZGate,
+        ("cswap", 3) => CSwapGate,
+        ("rccx", 3) => RCCXGate,
+        ("c3x", 4) => C3XGate,
+        ("c3sqrtx", 4) => C3SXGate,
+        ("rc3x", 4) => RC3XGate,
+        ("c4x", 5) => C4XGate,
+        _ => return None,
+    })
+}
+
+/// Builds up the flat, fully-resolved `(StandardGate, params, qubits)` instruction list that
+/// backs the parsed circuit, inlining user `gate` bodies as it goes.
+struct Builder<'a> {
+    registers: &'a Registers,
+    gate_defs: &'a HashMap<String, GateDef>,
+    instructions: Vec<(StandardGate, SmallVec<[Param; 3]>, SmallVec<[Qubit; 2]>)>,
+    /// Names of user-defined gates whose body is currently being inlined, innermost last; used to
+    /// reject a self- or mutually-recursive `gate` definition with an error instead of recursing
+    /// `append_call` without bound until the process stack overflows.
+    expanding: Vec<String>,
+}
+
+impl<'a> Builder<'a> {
+    /// Resolve a call's qubit arguments to one fully-indexed qubit list per broadcast instance,
+    /// expanding any whole-register argument in lockstep with the others (OpenQASM's implicit
+    /// `for i in 0..n: gate ... reg1[i], reg2[i], ...` broadcasting over registers of equal size).
+    fn broadcast_qubits(
+        &self,
+        qubits: &[QubitRef],
+        formal_to_actual: Option<&HashMap<String, Qubit>>,
+    ) -> PyResult<Vec<Vec<Qubit>>> {
+        let resolved: Vec<Vec<Qubit>> = qubits
+            .iter()
+            .map(|qref| match formal_to_actual {
+                Some(bound) => bound.get(&qref.name).copied().map(|q| vec![q]).ok_or_else(|| {
+                    PyValueError::new_err(format!("unbound qubit parameter '{}'", qref.name))
+                }),
+                None => self.registers.resolve(qref),
+            })
+            .collect::<PyResult<_>>()?;
+        let count = resolved.iter().map(Vec::len).max().unwrap_or(1).max(1);
+        for r in &resolved {
+            if r.len() != 1 && r.len() != count {
+                return Err(PyValueError::new_err(
+                    "broadcast over registers of mismatched size in gate call",
+                ));
+            }
+        }
+        Ok((0..count)
+            .map(|i| resolved.iter().map(|r| r[i % r.len()]).collect())
+            .collect())
+    }
+
+    /// Evaluate and append one gate call, recursively inlining a user-defined gate's body (with
+    /// its own formal parameters/qubits substituted) when `call.name` isn't a `StandardGate`.
+    fn append_call(
+        &mut self,
+        call: &GateCall,
+        param_bindings: &HashMap<String, f64>,
+        formal_to_actual: Option<&HashMap<String, Qubit>>,
+    ) -> PyResult<()> {
+        let params: SmallVec<[Param; 3]> = call
+            .args
+            .iter()
+            .map(|e| e.eval(param_bindings).map(Param::Float))
+            .collect::<PyResult<_>>()?;
+        let instances = self.broadcast_qubits(&call.qubits, formal_to_actual)?;
+
+        if let Some(standard) = standard_gate_for(&call.name, call.qubits.len()) {
+            for qubits in instances {
+                self.instructions
+                    .push((standard, params.clone(), qubits.into_iter().collect()));
+            }
+            return Ok(());
+        }
+
+        if self.expanding.iter().any(|name| name == &call.name) {
+            return Err(PyValueError::new_err(format!(
+                "gate '{}' is defined recursively (via {})",
+                call.name,
+                self.expanding.join(" -> ")
+            )));
+        }
+
+        let def = self.gate_defs.get(&call.name).ok_or_else(|| {
+            PyValueError::new_err(format!("reference to undefined gate '{}'", call.name))
+        })?;
+        if def.params.len() != call.args.len() {
+            return Err(PyValueError::new_err(format!(
+                "gate '{}' takes {} parameter(s), {} given",
+                call.name,
+                def.params.len(),
+                call.args.len()
+            )));
+        }
+        let evaluated_params: Vec<f64> = call
+            .args
+            .iter()
+            .map(|e| e.eval(param_bindings))
+            .collect::<PyResult<_>>()?;
+        for qubits in instances {
+            if def.qubits.len() != qubits.len() {
+                return Err(PyValueError::new_err(format!(
+                    "gate '{}' takes {} qubit(s), {} given",
+                    call.name,
+                    def.qubits.len(),
+                    qubits.len()
+                )));
+            }
+            let inner_params: HashMap<String, f64> = def
+                .params
+                .iter()
+                .cloned()
+                .zip(evaluated_params.iter().copied())
+                .collect();
+            let inner_qubits: HashMap<String, Qubit> =
+                def.qubits.iter().cloned().zip(qubits).collect();
+            let body = def.body.clone();
+            self.expanding.push(call.name.clone());
+            for inner_call in &body {
+                let result = self.append_call(inner_call, &inner_params, Some(&inner_qubits));
+                if result.is_err() {
+                    self.expanding.pop();
+                    return result;
+                }
+            }
+            self.expanding.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Parse an OpenQASM 2.0 or 3.0 source string into a `CircuitData` built entirely from
+/// `StandardGate`s. `measure`, `reset`, `barrier` and classical control flow are recognized and
+/// skipped, since there's no `StandardGate` to represent them; a circuit that relies on any of
+/// those should go through the existing Python-side importer instead.
+#[pyfunction]
+pub fn parse_qasm(py: Python, source: &str) -> PyResult<CircuitData> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut registers = Registers::default();
+    let mut gate_defs: HashMap<String, GateDef> = HashMap::new();
+    let mut calls: Vec<GateCall> = Vec::new();
+
+    while parser.peek().is_some() {
+        let head = parser.expect_ident()?;
+        match head.as_str() {
+            "OPENQASM" => parser.skip_statement()?,
+            "include" => parser.skip_statement()?,
+            "qreg" => {
+                let name = parser.expect_ident()?;
+                parser.expect_symbol('[')?;
+                let size = parser.expect_uint()?;
+                parser.expect_symbol(']')?;
+                parser.expect_symbol(';')?;
+                registers.declare_qreg(name, size);
+            }
+            "qubit" => {
+                // OpenQASM 3: `qubit[size] name;` or the scalar form `qubit name;`.
+                let size = if parser.eat_symbol('[') {
+                    let size = parser.expect_uint()?;
+                    parser.expect_symbol(']')?;
+                    size
+                } else {
+                    1
+                };
+                let name = parser.expect_ident()?;
+                parser.expect_symbol(';')?;
+                registers.declare_qreg(name, size);
+            }
+            "creg" => {
+                let name = parser.expect_ident()?;
+                parser.expect_symbol('[')?;
+                let size = parser.expect_uint()?;
+                parser.expect_symbol(']')?;
+                parser.expect_symbol(';')?;
+                registers.declare_creg(name, size);
+            }
+            "bit" => {
+                let size = if parser.eat_symbol('[') {
+                    let size = parser.expect_uint()?;
+                    parser.expect_symbol(']')?;
+                    size
+                } else {
+                    1
+                };
+                let name = parser.expect_ident()?;
+                parser.expect_symbol(';')?;
+                registers.declare_creg(name, size);
+            }
+            "gate" => {
+                let (name, def) = parser.parse_gate_def()?;
+                gate_defs.insert(name, def);
+            }
+            "barrier" | "measure" | "reset" | "if" => parser.skip_statement()?,
+            name => calls.push(parser.parse_gate_call(name.to_string())?),
+        }
+    }
+
+    let mut builder = Builder {
+        registers: &registers,
+        gate_defs: &gate_defs,
+        instructions: Vec::new(),
+        expanding: Vec::new(),
+    };
+    let empty_params = HashMap::new();
+    for call in &calls {
+        builder.append_call(call, &empty_params, None)?;
+    }
+
+    CircuitData::from_standard_gates(
+        py,
+        registers.num_qubits.max(1),
+        builder.instructions,
+        FLOAT_ZERO,
+    )
+}
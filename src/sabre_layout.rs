@@ -11,6 +11,7 @@
 // that they have been altered from the originals.
 #![allow(clippy::too_many_arguments)]
 
+use hashbrown::HashMap;
 use hashbrown::HashSet;
 use ndarray::prelude::*;
 use numpy::IntoPyArray;
@@ -27,10 +28,11 @@ use crate::getenv_use_multiple_threads;
 use crate::nlayout::NLayout;
 use crate::sabre_swap::neighbor_table::NeighborTable;
 use crate::sabre_swap::sabre_dag::SabreDAG;
-use crate::sabre_swap::swap_map::SwapMap;
+use crate::sabre_swap::swap_map::{NodeBlockResults, SwapMap};
 use crate::sabre_swap::{build_swap_map_inner, Heuristic};
 
 #[pyfunction]
+#[pyo3(signature = (num_clbits, dag_nodes, neighbor_table, distance_matrix, heuristic, seed, max_iterations, num_swap_trials, num_layout_trials, partial_layouts=vec![], node_blocks=HashMap::new(), gate_errors=None))]
 pub fn sabre_layout_and_routing(
     py: Python,
     num_clbits: usize,
@@ -42,46 +44,76 @@ pub fn sabre_layout_and_routing(
     max_iterations: usize,
     num_swap_trials: usize,
     num_layout_trials: usize,
-) -> ([NLayout; 2], SwapMap, PyObject) {
+    partial_layouts: Vec<Vec<Option<usize>>>,
+    // Control-flow ops (if/else, for, while, ...) in `dag_nodes`, keyed by their node index, each
+    // mapping to the `SabreDAG` of one of its body blocks, with the same logical qubit indices as
+    // the outer circuit. `layout_trial` remaps these alongside the outer nodes on every pass, and
+    // routes into them recursively (see `sabre_swap::route_control_flow_node`), so the layout
+    // this function settles on already accounts for however the branches get routed.
+    node_blocks: HashMap<usize, Vec<SabreDAG>>,
+    // Optional `num_qubits x num_qubits` per-edge 2q gate error rates. When given, trials are
+    // ranked by total estimated error (`-ln(1 - error)` summed over every routed 2q gate and
+    // inserted swap, each swap counting as three 2q operations) instead of raw swap count, so a
+    // layout that needs slightly more swaps on high-fidelity links can still win over one with
+    // fewer swaps on noisy ones.
+    gate_errors: Option<PyReadonlyArray2<f64>>,
+) -> ([NLayout; 2], SwapMap, PyObject, NodeBlockResults) {
     let run_in_parallel = getenv_use_multiple_threads();
+    let dist = distance_matrix.as_array();
+    let num_physical_qubits = dist.shape()[0];
+    // Besides the `num_layout_trials` random-shuffle trials, also seed: one deterministic layout
+    // built by matching the circuit's densest-interacting qubits to the coupling graph's
+    // densest-connected subset (which on sparse, e.g. near-linear, hardware tends to need far
+    // fewer swaps than any random shuffle recovers), and one trial per caller-supplied partial
+    // layout, each of which pins some logical qubits to specific physical ones (from a prior
+    // pass, a hardware constraint, or some other external hint) and leaves the rest to a
+    // seeded random fill.
+    let dense_layout: Vec<Option<usize>> =
+        dense_layout(num_physical_qubits, &dag_nodes, neighbor_table)
+            .into_iter()
+            .map(Some)
+            .collect();
+    let empty_layout: Vec<Option<usize>> = vec![None; num_physical_qubits];
+    let partial_layouts: Vec<Vec<Option<usize>>> = std::iter::once(dense_layout)
+        .chain(partial_layouts)
+        .chain(std::iter::repeat(empty_layout).take(num_layout_trials))
+        .collect();
     let outer_rng = Pcg64Mcg::seed_from_u64(seed);
-    let seed_vec: Vec<u64> = outer_rng
+    let trial_seeds: Vec<u64> = outer_rng
         .sample_iter(&rand::distributions::Standard)
-        .take(num_layout_trials)
+        .take(partial_layouts.len())
         .collect();
-    let dist = distance_matrix.as_array();
+    let gate_errors = gate_errors.as_ref().map(|e| e.as_array());
     let result = if run_in_parallel {
-        seed_vec
+        trial_seeds
             .into_par_iter()
+            .zip(partial_layouts.into_par_iter())
             .enumerate()
-            .map(|(index, seed_trial)| {
-                (
-                    index,
-                    layout_trial(
-                        num_clbits,
-                        dag_nodes.clone(),
-                        neighbor_table,
-                        &dist,
-                        heuristic,
-                        seed_trial,
-                        max_iterations,
-                        num_swap_trials,
-                    ),
-                )
-            })
-            .min_by_key(|(index, result)| {
-                (
-                    result.1.map.values().map(|x| x.len()).sum::<usize>(),
-                    *index,
-                )
+            .map(|(index, (seed_trial, partial_layout))| {
+                let trial = layout_trial(
+                    num_clbits,
+                    dag_nodes.clone(),
+                    neighbor_table,
+                    &dist,
+                    heuristic,
+                    seed_trial,
+                    max_iterations,
+                    num_swap_trials,
+                    partial_layout,
+                    &node_blocks,
+                );
+                let cost = trial_cost(&trial.4, &trial.1, &trial.3, gate_errors.as_ref());
+                (index, cost, trial)
             })
+            .min_by(|(ia, ca, _), (ib, cb, _)| ca.partial_cmp(cb).unwrap().then(ia.cmp(ib)))
             .unwrap()
-            .1
+            .2
     } else {
-        seed_vec
+        trial_seeds
             .into_iter()
-            .map(|seed_trial| {
-                layout_trial(
+            .zip(partial_layouts)
+            .map(|(seed_trial, partial_layout)| {
+                let trial = layout_trial(
                     num_clbits,
                     dag_nodes.clone(),
                     neighbor_table,
@@ -90,12 +122,201 @@ pub fn sabre_layout_and_routing(
                     seed_trial,
                     max_iterations,
                     num_swap_trials,
-                )
+                    partial_layout,
+                    &node_blocks,
+                );
+                let cost = trial_cost(&trial.4, &trial.1, &trial.3, gate_errors.as_ref());
+                (cost, trial)
             })
-            .min_by_key(|result| result.1.map.values().map(|x| x.len()).sum::<usize>())
+            .min_by(|(ca, _), (cb, _)| ca.partial_cmp(cb).unwrap())
             .unwrap()
+            .1
     };
-    (result.0, result.1, result.2.into_pyarray(py).into())
+    (
+        result.0,
+        result.1,
+        result.2.into_pyarray(py).into(),
+        result.3,
+    )
+}
+
+/// Score one trial's total routing cost: if `gate_errors` is given, the summed
+/// `-ln(1 - error)` over every routed 2q gate and inserted swap in `layout_dag`/`swap_map` (each
+/// swap counting as three 2q operations on its physical edge), recursing into any control-flow
+/// blocks via `node_block_results`; otherwise, the plain count of inserted swaps, matching the
+/// metric used before gate errors were supported.
+fn trial_cost(
+    layout_dag: &SabreDAG,
+    swap_map: &SwapMap,
+    node_block_results: &NodeBlockResults,
+    gate_errors: Option<&ArrayView2<f64>>,
+) -> f64 {
+    match gate_errors {
+        Some(errors) => trial_error_cost(layout_dag, swap_map, node_block_results, errors),
+        None => swap_map.map.values().map(|swaps| swaps.len()).sum::<usize>() as f64,
+    }
+}
+
+fn trial_error_cost(
+    layout_dag: &SabreDAG,
+    swap_map: &SwapMap,
+    node_block_results: &NodeBlockResults,
+    gate_errors: &ArrayView2<f64>,
+) -> f64 {
+    let mut total = 0.;
+    for (_, qargs) in layout_dag.dag.node_weights() {
+        if let [a, b] = qargs[..] {
+            total += edge_error_cost(gate_errors, a, b, 1);
+        }
+    }
+    for swaps in swap_map.map.values() {
+        for &[a, b] in swaps {
+            total += edge_error_cost(gate_errors, a, b, 3);
+        }
+    }
+    for (node, blocks) in &layout_dag.node_blocks {
+        let Some(block_results) = node_block_results.results.get(node) else {
+            continue;
+        };
+        for (block, result) in blocks.iter().zip(block_results.iter()) {
+            total += trial_error_cost(
+                block,
+                &result.swap_map,
+                &result.node_block_results,
+                gate_errors,
+            );
+            for &[a, b] in &result.swap_epilogue {
+                total += edge_error_cost(gate_errors, a, b, 3);
+            }
+        }
+    }
+    total
+}
+
+/// The error-weighted cost of `multiplicity` 2q operations on the physical edge `(a, b)`, using
+/// whichever direction of the (possibly asymmetric) `gate_errors` matrix is worse.
+fn edge_error_cost(gate_errors: &ArrayView2<f64>, a: usize, b: usize, multiplicity: u32) -> f64 {
+    let error = gate_errors[[a, b]].max(gate_errors[[b, a]]);
+    -(1. - error).ln() * multiplicity as f64
+}
+
+/// Build a deterministic starting layout (a `logic_to_phys` mapping) by matching the circuit's
+/// most-interacting logical qubits to the coupling graph's densest-connected physical qubits.
+///
+/// This counts, for each logical qubit, the 2q gates in `dag_nodes` that touch it (a per-qubit
+/// total only; which pairs interact is not recorded), then greedily grows a subset of physical qubits from
+/// `neighbor_table`, at each step adding whichever remaining physical qubit has the most edges
+/// into the subset so far, until the subset is as large as the number of logical qubits that
+/// actually interact. Logical qubits are ranked by total interaction weight and physical qubits
+/// in the subset by their induced degree, and the two rankings are zipped together; any
+/// non-interacting (ancilla) logical qubits fill the remaining physical qubits arbitrarily.
+fn dense_layout(
+    num_qubits: usize,
+    dag_nodes: &[(usize, Vec<usize>, HashSet<usize>)],
+    neighbor_table: &NeighborTable,
+) -> Vec<usize> {
+    let mut interaction_weight = vec![0usize; num_qubits];
+    for (_, qargs, _) in dag_nodes {
+        if let [a, b] = qargs[..] {
+            interaction_weight[a] += 1;
+            interaction_weight[b] += 1;
+        }
+    }
+    let active_count = interaction_weight.iter().filter(|&&w| w > 0).count();
+    let subset = densest_subgraph(neighbor_table, active_count.max(1).min(num_qubits));
+    let subset_set: HashSet<usize> = subset.iter().copied().collect();
+    let mut subset_by_induced_degree = subset.clone();
+    subset_by_induced_degree.sort_by_key(|&phys| {
+        let induced_degree = neighbor_table.neighbors[phys]
+            .iter()
+            .filter(|n| subset_set.contains(n))
+            .count();
+        std::cmp::Reverse(induced_degree)
+    });
+    let mut logical_by_weight: Vec<usize> = (0..num_qubits).collect();
+    logical_by_weight.sort_by_key(|&q| std::cmp::Reverse(interaction_weight[q]));
+    let mut logic_to_phys = vec![0; num_qubits];
+    let mut assigned_phys = vec![false; num_qubits];
+    for (logic, phys) in logical_by_weight
+        .iter()
+        .zip(subset_by_induced_degree.iter())
+    {
+        logic_to_phys[*logic] = *phys;
+        assigned_phys[*phys] = true;
+    }
+    let mut remaining_phys = (0..num_qubits).filter(|p| !assigned_phys[*p]);
+    for &logic in &logical_by_weight[subset_by_induced_degree.len()..] {
+        logic_to_phys[logic] = remaining_phys.next().unwrap();
+    }
+    logic_to_phys
+}
+
+/// Greedily grow a connected set of `size` physical qubits from `neighbor_table`, starting at the
+/// highest-degree qubit and at each step adding whichever qubit adjacent to the current set has
+/// the most edges into it, which tends to find a dense (if not globally optimal) induced subgraph.
+fn densest_subgraph(neighbor_table: &NeighborTable, size: usize) -> Vec<usize> {
+    let num_physical_qubits = neighbor_table.neighbors.len();
+    let start = (0..num_physical_qubits)
+        .max_by_key(|&p| neighbor_table.neighbors[p].len())
+        .unwrap_or(0);
+    let mut in_subset = vec![false; num_physical_qubits];
+    in_subset[start] = true;
+    let mut subset = vec![start];
+    while subset.len() < size {
+        let candidate = (0..num_physical_qubits)
+            .filter(|p| !in_subset[*p])
+            .max_by_key(|&p| {
+                neighbor_table.neighbors[p]
+                    .iter()
+                    .filter(|n| in_subset[**n])
+                    .count()
+            });
+        match candidate {
+            Some(next) => {
+                in_subset[next] = true;
+                subset.push(next);
+            }
+            None => break,
+        }
+    }
+    subset
+}
+
+/// Turn a partial layout (one entry per logical qubit, `Some(phys)` for a pinned physical qubit
+/// or `None` for an unconstrained one) into a full `NLayout`, filling the unconstrained logical
+/// qubits with a random shuffle of whichever physical qubits the pins didn't already claim.
+fn resolve_partial_layout(
+    partial_layout: &[Option<usize>],
+    num_physical_qubits: usize,
+    rng: &mut Pcg64Mcg,
+) -> NLayout {
+    let mut logic_to_phys = vec![usize::MAX; partial_layout.len()];
+    let mut phys_assigned = vec![false; num_physical_qubits];
+    for (logic, &phys) in partial_layout.iter().enumerate() {
+        if let Some(phys) = phys {
+            logic_to_phys[logic] = phys;
+            phys_assigned[phys] = true;
+        }
+    }
+    let mut free_phys: Vec<usize> = (0..num_physical_qubits)
+        .filter(|phys| !phys_assigned[*phys])
+        .collect();
+    free_phys.shuffle(rng);
+    let mut free_phys = free_phys.into_iter();
+    for phys in logic_to_phys.iter_mut() {
+        if *phys == usize::MAX {
+            *phys = free_phys.next().unwrap();
+        }
+    }
+    let mut phys_to_logic = vec![0; num_physical_qubits];
+    logic_to_phys
+        .iter()
+        .enumerate()
+        .for_each(|(logic, phys)| phys_to_logic[*phys] = logic);
+    NLayout {
+        logic_to_phys,
+        phys_to_logic,
+    }
 }
 
 fn layout_trial(
@@ -107,31 +328,34 @@ fn layout_trial(
     seed: u64,
     max_iterations: usize,
     num_swap_trials: usize,
-) -> ([NLayout; 2], SwapMap, Vec<usize>) {
-    // Pick a random initial layout and fully populate ancillas in that layout too
+    partial_layout: Vec<Option<usize>>,
+    node_blocks: &HashMap<usize, Vec<SabreDAG>>,
+) -> ([NLayout; 2], SwapMap, Vec<usize>, NodeBlockResults, SabreDAG) {
+    // Honor any pinned logical qubits in `partial_layout`, then fully populate the rest
+    // (including ancillas) with a random shuffle of whichever physical qubits are left.
     let num_physical_qubits = distance_matrix.shape()[0];
     let mut rng = Pcg64Mcg::seed_from_u64(seed);
-    let mut physical_qubits: Vec<usize> = (0..num_physical_qubits).collect();
-    physical_qubits.shuffle(&mut rng);
-    let mut phys_to_logic = vec![0; num_physical_qubits];
-    physical_qubits
-        .iter()
-        .enumerate()
-        .for_each(|(logic, phys)| phys_to_logic[*phys] = logic);
-    let mut initial_layout = NLayout {
-        logic_to_phys: physical_qubits,
-        phys_to_logic,
-    };
+    let mut initial_layout = resolve_partial_layout(&partial_layout, num_physical_qubits, &mut rng);
     let mut rev_dag_nodes: Vec<(usize, Vec<usize>, HashSet<usize>)> =
         dag_nodes.iter().rev().cloned().collect();
     for _iter in 0..max_iterations {
         // forward and reverse
         for _direction in 0..2 {
-            let dag = apply_layout(&dag_nodes, &initial_layout, num_physical_qubits, num_clbits);
+            let dag = apply_layout(
+                &dag_nodes,
+                node_blocks,
+                &initial_layout,
+                num_physical_qubits,
+                num_clbits,
+            );
             let mut pass_final_layout = NLayout {
                 logic_to_phys: (0..num_physical_qubits).collect(),
                 phys_to_logic: (0..num_physical_qubits).collect(),
             };
+            // Routing (including recursing into `dag`'s control-flow blocks, see
+            // `sabre_swap::route_control_flow_node`) already reconciles every block's branch onto
+            // a single layout, so `pass_final_layout` reflects the state after any nested
+            // blocks, with no extra stitching needed here.
             build_swap_map_inner(
                 num_physical_qubits,
                 &dag,
@@ -148,9 +372,15 @@ fn layout_trial(
             std::mem::swap(&mut dag_nodes, &mut rev_dag_nodes);
         }
     }
-    let layout_dag = apply_layout(&dag_nodes, &initial_layout, num_physical_qubits, num_clbits);
+    let layout_dag = apply_layout(
+        &dag_nodes,
+        node_blocks,
+        &initial_layout,
+        num_physical_qubits,
+        num_clbits,
+    );
     let mut final_layout = initial_layout.clone();
-    let (swap_map, gate_order) = build_swap_map_inner(
+    let (swap_map, gate_order, _gate_order_depths, node_block_results) = build_swap_map_inner(
         num_physical_qubits,
         &layout_dag,
         neighbor_table,
@@ -161,11 +391,47 @@ fn layout_trial(
         num_swap_trials,
         Some(false),
     );
-    ([initial_layout, final_layout], swap_map, gate_order)
+    (
+        [initial_layout, final_layout],
+        swap_map,
+        gate_order,
+        node_block_results,
+        layout_dag,
+    )
+}
+
+/// Remap the qubits of a control-flow node's nested block DAGs (and, recursively, theirs) through
+/// `layout`, so they stay in lock-step with the outer nodes that `apply_layout` remaps alongside
+/// them.
+fn remap_node_blocks(
+    node_blocks: &HashMap<usize, Vec<SabreDAG>>,
+    layout: &NLayout,
+) -> HashMap<usize, Vec<SabreDAG>> {
+    node_blocks
+        .iter()
+        .map(|(&node, blocks)| (node, blocks.iter().map(|b| remap_sabre_dag(b, layout)).collect()))
+        .collect()
+}
+
+fn remap_sabre_dag(block: &SabreDAG, layout: &NLayout) -> SabreDAG {
+    let mut dag = block.dag.clone();
+    for (_, qargs) in dag.node_weights_mut() {
+        for q in qargs.iter_mut() {
+            *q = layout.logic_to_phys[*q];
+        }
+    }
+    SabreDAG {
+        dag,
+        first_layer: block.first_layer.clone(),
+        node_blocks: remap_node_blocks(&block.node_blocks, layout),
+        // Qubit remapping never changes the DAG's edges, so the precedence depths are unaffected.
+        node_depths: block.node_depths.clone(),
+    }
 }
 
 fn apply_layout(
     dag_nodes: &[(usize, Vec<usize>, HashSet<usize>)],
+    node_blocks: &HashMap<usize, Vec<SabreDAG>>,
     layout: &NLayout,
     num_qubits: usize,
     num_clbits: usize,
@@ -177,11 +443,13 @@ fn apply_layout(
             (*node_index, new_qargs, cargs.clone())
         })
         .collect();
-    build_sabre_dag(layout_dag_nodes, num_qubits, num_clbits)
+    let layout_node_blocks = remap_node_blocks(node_blocks, layout);
+    build_sabre_dag(layout_dag_nodes, layout_node_blocks, num_qubits, num_clbits)
 }
 
 fn build_sabre_dag(
     layout_dag_nodes: Vec<(usize, Vec<usize>, HashSet<usize>)>,
+    node_blocks: HashMap<usize, Vec<SabreDAG>>,
     num_qubits: usize,
     num_clbits: usize,
 ) -> SabreDAG {
@@ -213,7 +481,13 @@ fn build_sabre_dag(
             first_layer.push(gate_index);
         }
     }
-    SabreDAG { dag, first_layer }
+    let node_depths = SabreDAG::compute_node_depths(&dag, &first_layer);
+    SabreDAG {
+        dag,
+        first_layer,
+        node_blocks,
+        node_depths,
+    }
 }
 
 fn compose_layout(initial_layout: &NLayout, final_layout: &NLayout) -> NLayout {
@@ -0,0 +1,256 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2022
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+use ndarray::prelude::*;
+use retworkx_core::petgraph::prelude::*;
+
+use crate::nlayout::NLayout;
+
+/// Given a candidate swap's virtual qubit and the front-layer's current occupant of that qubit
+/// (if any), the physical position that qubit would have *after* the swap were applied.
+fn phys_after_swap(layout: &NLayout, swap: [usize; 2], qubit: usize) -> usize {
+    if qubit == swap[0] {
+        layout.logic_to_phys[swap[1]]
+    } else if qubit == swap[1] {
+        layout.logic_to_phys[swap[0]]
+    } else {
+        layout.logic_to_phys[qubit]
+    }
+}
+
+/// The set of DAG nodes that are currently "active": already visited by the routing traversal,
+/// but not yet routable because their two qubits are not adjacent under the current layout.
+///
+/// Besides the nodes themselves, this tracks, for every virtual qubit, the other qubit of the
+/// front-layer gate that touches it (if any). A qubit can be the target of at most one
+/// front-layer gate at a time, which is what lets [FrontLayer::score] and
+/// [ExtendedSet::score] below compute a candidate swap's effect on the total distance in O(1)
+/// rather than by rescoring every node in the set: swapping virtual qubits `a` and `b` can only
+/// change the distance of the (at most two) gates that have `a` or `b` as a partner.
+pub struct FrontLayer {
+    /// `partner[q]` is `Some((node, other))` when virtual qubit `q` is one end of the 2q gate
+    /// `node`, whose other qubit is `other`.
+    partner: Vec<Option<(NodeIndex, usize)>>,
+    /// The qubits that currently have an entry in `partner`, i.e. that are touched by some node
+    /// in this set.
+    active: Vec<usize>,
+    /// The nodes currently in this set, paired with their qubits, in insertion order.
+    nodes: Vec<(NodeIndex, [usize; 2])>,
+}
+
+impl FrontLayer {
+    pub fn new(num_qubits: usize) -> Self {
+        FrontLayer {
+            partner: vec![None; num_qubits],
+            active: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn is_active(&self, qubit: usize) -> bool {
+        self.partner[qubit].is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&NodeIndex, &[usize; 2])> + '_ {
+        self.nodes.iter().map(|(node, qubits)| (node, qubits))
+    }
+
+    pub fn iter_nodes(&self) -> impl Iterator<Item = &NodeIndex> + '_ {
+        self.nodes.iter().map(|(node, _)| node)
+    }
+
+    pub fn iter_active(&self) -> impl Iterator<Item = &usize> + '_ {
+        self.active.iter()
+    }
+
+    pub fn insert(&mut self, node: NodeIndex, qubits: [usize; 2]) {
+        self.partner[qubits[0]] = Some((node, qubits[1]));
+        self.partner[qubits[1]] = Some((node, qubits[0]));
+        self.active.push(qubits[0]);
+        self.active.push(qubits[1]);
+        self.nodes.push((node, qubits));
+    }
+
+    pub fn remove(&mut self, node: &NodeIndex) {
+        let Some(position) = self.nodes.iter().position(|(n, _)| n == node) else {
+            return;
+        };
+        let (_, qubits) = self.nodes.swap_remove(position);
+        self.partner[qubits[0]] = None;
+        self.partner[qubits[1]] = None;
+        self.active.retain(|&q| q != qubits[0] && q != qubits[1]);
+    }
+
+    /// Given a candidate `swap`, push onto `routable` every node in this set that becomes
+    /// routable (its two qubits become adjacent in `coupling`) if `swap` were applied.
+    pub fn routable_after(
+        &self,
+        routable: &mut Vec<NodeIndex>,
+        swap: &[usize; 2],
+        layout: &NLayout,
+        coupling: &DiGraph<(), ()>,
+    ) {
+        for &qubit in swap {
+            let Some((node, partner)) = self.partner[qubit] else {
+                continue;
+            };
+            if routable.contains(&node) {
+                continue;
+            }
+            let phys_qubit = phys_after_swap(layout, *swap, qubit);
+            let phys_partner = phys_after_swap(layout, *swap, partner);
+            if coupling.contains_edge(NodeIndex::new(phys_qubit), NodeIndex::new(phys_partner))
+                || coupling.contains_edge(NodeIndex::new(phys_partner), NodeIndex::new(phys_qubit))
+            {
+                routable.push(node);
+            }
+        }
+    }
+
+    /// The relative change in total distance that applying `swap` would cause: only the
+    /// (at most two) nodes whose qubits the swap touches can change distance, so this sums just
+    /// their `dist_after - dist_before` rather than rescoring the whole set.
+    pub fn score(&self, swap: [usize; 2], layout: &NLayout, dist: &ArrayView2<f64>) -> f64 {
+        let [a, b] = swap;
+        let mut total = 0.0;
+        let mut counted_shared_gate = false;
+        for &qubit in &[a, b] {
+            let Some((_, partner)) = self.partner[qubit] else {
+                continue;
+            };
+            let other = if qubit == a { b } else { a };
+            if partner == other {
+                // `a` and `b` are the two ends of the same front-layer gate: swapping them just
+                // swaps which physical qubit each end sits on, so the gate's distance (and hence
+                // this swap's contribution) is unchanged. Count it (as zero) only once.
+                if !counted_shared_gate {
+                    counted_shared_gate = true;
+                }
+                continue;
+            }
+            let before = dist[[layout.logic_to_phys[qubit], layout.logic_to_phys[partner]]];
+            let after = dist[[
+                phys_after_swap(layout, swap, qubit),
+                layout.logic_to_phys[partner],
+            ]];
+            total += after - before;
+        }
+        total
+    }
+
+    /// The absolute total distance across every node in this set. Swap selection does not call
+    /// it: `choose_best_swap` scores only relative changes, and its decay component is a
+    /// per-qubit penalty rather than a mix with this absolute score.
+    pub fn total_score(&self, layout: &NLayout, dist: &ArrayView2<f64>) -> f64 {
+        self.nodes
+            .iter()
+            .map(|(_, qubits)| {
+                dist[[
+                    layout.logic_to_phys[qubits[0]],
+                    layout.logic_to_phys[qubits[1]],
+                ]]
+            })
+            .sum()
+    }
+}
+
+/// The lookahead window: a bounded set of DAG nodes that are reachable soon after the front
+/// layer, used to break ties and steer swap selection towards gates that will need to be routed
+/// next. Scoring uses the same relative, partner-tracking formulation as [FrontLayer].
+pub struct ExtendedSet {
+    max_size: usize,
+    partner: Vec<Option<(NodeIndex, usize)>>,
+    nodes: Vec<(NodeIndex, [usize; 2])>,
+}
+
+impl ExtendedSet {
+    pub fn new(num_qubits: usize, max_size: usize) -> Self {
+        ExtendedSet {
+            max_size,
+            partner: vec![None; num_qubits],
+            nodes: Vec::with_capacity(max_size),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The maximum number of nodes this set was constructed to hold.
+    pub fn cap(&self) -> usize {
+        self.max_size
+    }
+
+    pub fn insert(&mut self, node: NodeIndex, qubits: &[usize; 2]) {
+        if self.nodes.len() >= self.max_size {
+            return;
+        }
+        self.partner[qubits[0]] = Some((node, qubits[1]));
+        self.partner[qubits[1]] = Some((node, qubits[0]));
+        self.nodes.push((node, *qubits));
+    }
+
+    pub fn clear(&mut self) {
+        for &(_, qubits) in &self.nodes {
+            self.partner[qubits[0]] = None;
+            self.partner[qubits[1]] = None;
+        }
+        self.nodes.clear();
+    }
+
+    /// As [FrontLayer::score]: only the nodes partnered on `swap`'s two qubits can change
+    /// distance, so this is O(1) rather than O(len()).
+    pub fn score(&self, swap: [usize; 2], layout: &NLayout, dist: &ArrayView2<f64>) -> f64 {
+        let [a, b] = swap;
+        let mut total = 0.0;
+        for &qubit in &[a, b] {
+            let Some((_, partner)) = self.partner[qubit] else {
+                continue;
+            };
+            let other = if qubit == a { b } else { a };
+            if partner == other {
+                continue;
+            }
+            let before = dist[[layout.logic_to_phys[qubit], layout.logic_to_phys[partner]]];
+            let after = dist[[
+                phys_after_swap(layout, swap, qubit),
+                layout.logic_to_phys[partner],
+            ]];
+            total += after - before;
+        }
+        total
+    }
+
+    pub fn total_score(&self, layout: &NLayout, dist: &ArrayView2<f64>) -> f64 {
+        self.nodes
+            .iter()
+            .map(|(_, qubits)| {
+                dist[[
+                    layout.logic_to_phys[qubits[0]],
+                    layout.logic_to_phys[qubits[1]],
+                ]]
+            })
+            .sum()
+    }
+}
@@ -18,6 +18,7 @@ pub mod sabre_dag;
 pub mod swap_map;
 
 use std::cmp::Ordering;
+use std::collections::VecDeque;
 
 use hashbrown::HashMap;
 use ndarray::prelude::*;
@@ -41,24 +42,161 @@ use crate::nlayout::NLayout;
 use layer::{ExtendedSet, FrontLayer};
 use neighbor_table::NeighborTable;
 use sabre_dag::SabreDAG;
-use swap_map::SwapMap;
+use swap_map::{BlockResult, NodeBlockResults, SwapMap};
 
-const EXTENDED_SET_SIZE: usize = 20; // Size of lookahead window.
-const DECAY_RATE: f64 = 0.001; // Decay coefficient for penalizing serial swaps.
-const DECAY_RESET_INTERVAL: u8 = 5; // How often to reset all decay rates to 1.
-const EXTENDED_SET_WEIGHT: f64 = 0.5; // Weight of lookahead window compared to front_layer.
+/// How much a single heuristic component (the front layer's score, the extended set's score)
+/// contributes to a candidate swap's total score: either a fixed constant, or one that scales
+/// with the size of the set being scored.
+#[pyclass]
+#[derive(Clone, Copy, Debug)]
+pub struct SetScaling {
+    value: f64,
+    scales_with_size: bool,
+}
+
+#[pymethods]
+impl SetScaling {
+    /// A weight that does not depend on how many nodes are in the set being scored.
+    #[staticmethod]
+    pub fn constant(value: f64) -> Self {
+        SetScaling {
+            value,
+            scales_with_size: false,
+        }
+    }
+
+    /// A weight that scales linearly with the number of nodes in the set being scored.
+    #[staticmethod]
+    pub fn size(value: f64) -> Self {
+        SetScaling {
+            value,
+            scales_with_size: true,
+        }
+    }
+
+    fn weight(&self, set_size: usize) -> f64 {
+        if self.scales_with_size {
+            self.value * set_size as f64
+        } else {
+            self.value
+        }
+    }
+}
 
+/// A composable configuration of the Sabre routing heuristic: each component is independently
+/// optional, so callers can mix and match (e.g. a decay penalty with no lookahead window at
+/// all), rather than being limited to the three fixed presets this used to be.
 #[pyclass]
-pub enum Heuristic {
-    Basic,
-    Lookahead,
-    Decay,
+#[derive(Clone, Debug, Default)]
+pub struct Heuristic {
+    basic: Option<SetScaling>,
+    // Weight, and the size of the lookahead window to build.
+    lookahead: Option<(SetScaling, usize)>,
+    // Decay rate per swap, and the number of search steps between decay resets.
+    decay: Option<(f64, u8)>,
+    // Boltzmann temperature for probabilistic swap selection; `None` means always take the
+    // (uniformly tie-broken) minimum-scoring swap, as before.
+    temperature: Option<f64>,
+    // A `num_qubits x num_qubits` matrix of extra cost for using physical edge `[ii, jj]` in that
+    // direction; zero where the coupling map natively supports that orientation. Scored with the
+    // same before/after distance-diffing as `basic`, just against this matrix instead of `dist`.
+    directed_penalty: Option<Array2<f64>>,
+}
+
+#[pymethods]
+impl Heuristic {
+    #[new]
+    pub fn new() -> Self {
+        Heuristic::default()
+    }
+
+    /// Score candidate swaps by their effect on the front layer's total distance.
+    pub fn with_basic(&self, weight: SetScaling) -> Self {
+        Heuristic {
+            basic: Some(weight),
+            ..self.clone()
+        }
+    }
+
+    /// Score candidate swaps by their effect on a lookahead window of `extended_set_size` nodes
+    /// beyond the front layer.
+    pub fn with_lookahead(&self, weight: SetScaling, extended_set_size: usize) -> Self {
+        Heuristic {
+            lookahead: Some((weight, extended_set_size)),
+            ..self.clone()
+        }
+    }
+
+    /// Penalize swaps that reuse a qubit that has recently been swapped, to discourage the
+    /// search from oscillating on the same pair. `reset_interval` is the number of search steps
+    /// after which every qubit's decay penalty is reset to zero.
+    pub fn with_decay(&self, rate: f64, reset_interval: u8) -> Self {
+        Heuristic {
+            decay: Some((rate, reset_interval)),
+            ..self.clone()
+        }
+    }
+
+    /// Instead of always taking the (uniformly tie-broken) minimum-scoring swap, sample one
+    /// candidate swap from a Boltzmann distribution over all of them at the given temperature:
+    /// `w_i = exp(-(score_i - min_score) / temperature)`. Higher temperatures explore more;
+    /// a temperature at or below zero is treated as greedy argmin, matching the `T -> 0` limit.
+    pub fn with_temperature(&self, temperature: f64) -> Self {
+        Heuristic {
+            temperature: Some(temperature),
+            ..self.clone()
+        }
+    }
+
+    /// Penalize swaps that leave a front-layer gate (lookahead gates are not penalized) on a physical edge
+    /// that only natively supports the opposite orientation, so the search can steer away from
+    /// directions that a post-routing direction-fixing pass would otherwise have to repair.
+    /// `penalty` is a `num_qubits x num_qubits` matrix where `penalty[[ii, jj]]` is the extra
+    /// cost of a two-qubit gate landing on physical qubits `(ii, jj)` in that order -- `0.0`
+    /// where the coupling map's native direction already matches, positive where it's reversed.
+    /// Pass an all-zero matrix (or simply don't call this) for undirected/symmetric coupling.
+    pub fn with_directed_coupling(&self, penalty: PyReadonlyArray2<f64>) -> Self {
+        Heuristic {
+            directed_penalty: Some(penalty.as_array().to_owned()),
+            ..self.clone()
+        }
+    }
 }
 
 struct TrialResult {
     out_map: HashMap<usize, Vec<[usize; 2]>>,
     gate_order: Vec<usize>,
+    // Parallel to `gate_order`: the precedence depth (`SabreDAG::node_depths`) of the node at the
+    // same position. Two consecutive entries sharing a depth are an intra-layer tie that this
+    // traversal order has already broken deterministically, so Python-side replay can walk
+    // `gate_order` directly instead of recomputing its own topological order to find ties.
+    gate_order_depths: Vec<u32>,
     layout: NLayout,
+    node_block_results: NodeBlockResults,
+}
+
+/// The total number of swaps a trial result used, counting both its own top-level `out_map` and
+/// every swap recorded recursively in its control-flow blocks. Used to compare trials against
+/// each other; a trial that hides its cost inside a block's routing would otherwise look
+/// artificially cheap.
+fn trial_swap_count(result: &TrialResult) -> usize {
+    result.out_map.values().map(|x| x.len()).sum::<usize>()
+        + node_block_results_swap_count(&result.node_block_results)
+}
+
+fn block_result_swap_count(block: &BlockResult) -> usize {
+    block.swap_map.map.values().map(|x| x.len()).sum::<usize>()
+        + block.swap_epilogue.len()
+        + node_block_results_swap_count(&block.node_block_results)
+}
+
+fn node_block_results_swap_count(results: &NodeBlockResults) -> usize {
+    results
+        .results
+        .values()
+        .flatten()
+        .map(block_result_swap_count)
+        .sum()
 }
 
 /// Return a set of candidate swaps that affect qubits in front_layer.
@@ -105,7 +243,7 @@ fn populate_extended_set(
     let mut to_visit = front_layer.iter_nodes().copied().collect::<Vec<_>>();
     let mut decremented: HashMap<usize, u32> = HashMap::new();
     let mut i = 0;
-    while i < to_visit.len() && extended_set.len() < EXTENDED_SET_SIZE {
+    while i < to_visit.len() && extended_set.len() < extended_set.cap() {
         for edge in dag.dag.edges_directed(to_visit[i], Direction::Outgoing) {
             let successor_node = edge.target();
             let successor_index = successor_node.index();
@@ -138,10 +276,14 @@ fn cmap_from_neighor_table(neighbor_table: &NeighborTable) -> DiGraph<(), ()> {
 /// Run sabre swap on a circuit
 ///
 /// Returns:
-///     (SwapMap, gate_order): A tuple where the first element is a mapping of
-///     DAGCircuit node ids to a list of virtual qubit swaps that should be
-///     added before that operation. The second element is a numpy array of
-///     node ids that represents the traversal order used by sabre.
+///     (SwapMap, gate_order, gate_order_depths, node_block_results): A tuple where the first
+///     element is a mapping of DAGCircuit node ids to a list of virtual qubit swaps that should be
+///     added before that operation. The second element is a numpy array of node ids that
+///     represents the traversal order used by sabre. The third is a numpy array, parallel to
+///     `gate_order`, of each entry's precedence depth (see `SabreDAG::node_depths`) -- a small
+///     side table letting replay recognise same-layer ties without recomputing a topological
+///     sort of its own. The fourth element records, per control-flow node, how each of its body
+///     blocks was routed; see [NodeBlockResults].
 #[pyfunction]
 pub fn build_swap_map(
     py: Python,
@@ -154,9 +296,9 @@ pub fn build_swap_map(
     layout: &mut NLayout,
     num_trials: usize,
     run_in_parallel: Option<bool>,
-) -> (SwapMap, PyObject) {
+) -> (SwapMap, PyObject, PyObject, NodeBlockResults) {
     let dist = distance_matrix.as_array();
-    let (swap_map, gate_order) = build_swap_map_inner(
+    let (swap_map, gate_order, gate_order_depths, node_block_results) = build_swap_map_inner(
         num_qubits,
         dag,
         neighbor_table,
@@ -167,9 +309,22 @@ pub fn build_swap_map(
         num_trials,
         run_in_parallel,
     );
-    (swap_map, gate_order.into_pyarray(py).into())
+    (
+        swap_map,
+        gate_order.into_pyarray(py).into(),
+        gate_order_depths.into_pyarray(py).into(),
+        node_block_results,
+    )
 }
 
+/// Run every trial independently and keep whichever used the fewest swaps.
+///
+/// Each trial gets its own `Pcg64Mcg` stream, seeded up front from `outer_rng` (itself seeded from
+/// `seed`) rather than sharing one RNG behind a lock: `seed_vec[k]` seeds trial `k`, so trial `k`'s
+/// random draws never contend with any other trial's, and `into_par_iter()` can run every trial
+/// fully in parallel with no synchronization inside `swap_map_trial`. The result stays
+/// reproducible for a given `seed` regardless of thread scheduling -- `seed_vec` is generated
+/// before trials start, and ties in swap count are broken by trial index, not completion order.
 pub fn build_swap_map_inner(
     num_qubits: usize,
     dag: &SabreDAG,
@@ -180,7 +335,7 @@ pub fn build_swap_map_inner(
     layout: &mut NLayout,
     num_trials: usize,
     run_in_parallel: Option<bool>,
-) -> (SwapMap, Vec<usize>) {
+) -> (SwapMap, Vec<usize>, Vec<u32>, NodeBlockResults) {
     let run_in_parallel = match run_in_parallel {
         Some(run_in_parallel) => run_in_parallel,
         None => getenv_use_multiple_threads() && num_trials > 1,
@@ -210,12 +365,7 @@ pub fn build_swap_map_inner(
                     ),
                 )
             })
-            .min_by_key(|(index, result)| {
-                [
-                    result.out_map.values().map(|x| x.len()).sum::<usize>(),
-                    *index,
-                ]
-            })
+            .min_by_key(|(index, result)| [trial_swap_count(result), *index])
             .unwrap()
             .1
     } else {
@@ -233,7 +383,7 @@ pub fn build_swap_map_inner(
                     layout.clone(),
                 )
             })
-            .min_by_key(|result| result.out_map.values().map(|x| x.len()).sum::<usize>())
+            .min_by_key(trial_swap_count)
             .unwrap()
     };
     *layout = result.layout;
@@ -242,6 +392,8 @@ pub fn build_swap_map_inner(
             map: result.out_map,
         },
         result.gate_order,
+        result.gate_order_depths,
+        result.node_block_results,
     )
 }
 
@@ -258,12 +410,15 @@ fn swap_map_trial(
     let max_iterations_without_progress = 10 * neighbor_table.neighbors.len();
     let mut out_map: HashMap<usize, Vec<[usize; 2]>> = HashMap::new();
     let mut gate_order = Vec::with_capacity(dag.dag.node_count());
+    let mut gate_order_depths = Vec::with_capacity(dag.dag.node_count());
     let mut front_layer = FrontLayer::new(num_qubits);
-    let mut extended_set = ExtendedSet::new(num_qubits, EXTENDED_SET_SIZE);
+    let extended_set_size = heuristic.lookahead.map_or(0, |(_, size)| size);
+    let mut extended_set = ExtendedSet::new(num_qubits, extended_set_size);
     let mut required_predecessors: Vec<u32> = vec![0; dag.dag.node_count()];
     let mut num_search_steps: u8 = 0;
     let mut qubits_decay: Vec<f64> = vec![1.; num_qubits];
     let mut rng = Pcg64Mcg::seed_from_u64(seed);
+    let mut node_block_results = NodeBlockResults::default();
 
     for node in dag.dag.node_indices() {
         for edge in dag.dag.edges(node) {
@@ -273,9 +428,15 @@ fn swap_map_trial(
     route_reachable_nodes(
         &dag.first_layer,
         dag,
-        &layout,
+        &mut layout,
         coupling_graph,
+        neighbor_table,
+        dist,
+        heuristic,
+        &mut rng,
         &mut gate_order,
+        &mut gate_order_depths,
+        &mut node_block_results,
         &mut front_layer,
         &mut required_predecessors,
     );
@@ -306,13 +467,15 @@ fn swap_map_trial(
             front_layer.routable_after(&mut routable_nodes, &best_swap, &layout, coupling_graph);
             current_swaps.push(best_swap);
             layout.swap_logical(best_swap[0], best_swap[1]);
-            num_search_steps += 1;
-            if num_search_steps >= DECAY_RESET_INTERVAL {
-                qubits_decay.fill(1.);
-                num_search_steps = 0;
-            } else {
-                qubits_decay[best_swap[0]] += DECAY_RATE;
-                qubits_decay[best_swap[1]] += DECAY_RATE;
+            if let Some((rate, reset_interval)) = heuristic.decay {
+                num_search_steps += 1;
+                if num_search_steps >= reset_interval {
+                    qubits_decay.fill(1.);
+                    num_search_steps = 0;
+                } else {
+                    qubits_decay[best_swap[0]] += rate;
+                    qubits_decay[best_swap[1]] += rate;
+                }
             }
         }
         // If we exceeded the number of allowed attempts without successfully routing a node, we
@@ -324,20 +487,37 @@ fn swap_map_trial(
         // keep the other path faster.
         if routable_nodes.is_empty() {
             undo_swaps(&mut current_swaps, &mut layout);
-            let (node, qubits) = closest_operation(&front_layer, &layout, dist);
+            let (_, qubits) = closest_operation(&front_layer, &layout, dist);
             swaps_to_route(&mut current_swaps, &qubits, &layout, coupling_graph);
             for &[a, b] in current_swaps.iter() {
                 layout.swap_logical(a, b);
             }
-            routable_nodes.push(node);
+            // The escape swaps were only guaranteed to route the single closest node, but they
+            // can easily make other front-layer gates adjacent too as a side effect.  Re-scan the
+            // whole front layer against the now-updated layout and route every node that's
+            // routable, rather than leaving the others stranded with the swaps already applied.
+            for (&node, &[a, b]) in front_layer.iter() {
+                if coupling_graph.contains_edge(
+                    NodeIndex::new(layout.logic_to_phys[a]),
+                    NodeIndex::new(layout.logic_to_phys[b]),
+                ) {
+                    routable_nodes.push(node);
+                }
+            }
         }
         update_route(
             &routable_nodes,
             current_swaps,
             dag,
-            &layout,
+            &mut layout,
             coupling_graph,
+            neighbor_table,
+            dist,
+            heuristic,
+            &mut rng,
             &mut gate_order,
+            &mut gate_order_depths,
+            &mut node_block_results,
             &mut out_map,
             &mut front_layer,
             &mut extended_set,
@@ -349,7 +529,9 @@ fn swap_map_trial(
     TrialResult {
         out_map,
         gate_order,
+        gate_order_depths,
         layout,
+        node_block_results,
     }
 }
 
@@ -361,9 +543,15 @@ fn update_route(
     nodes: &[NodeIndex],
     swaps: Vec<[usize; 2]>,
     dag: &SabreDAG,
-    layout: &NLayout,
+    layout: &mut NLayout,
     coupling: &DiGraph<(), ()>,
+    neighbor_table: &NeighborTable,
+    dist: &ArrayView2<f64>,
+    heuristic: &Heuristic,
+    rng: &mut Pcg64Mcg,
     gate_order: &mut Vec<usize>,
+    gate_order_depths: &mut Vec<u32>,
+    node_block_results: &mut NodeBlockResults,
     out_map: &mut HashMap<usize, Vec<[usize; 2]>>,
     front_layer: &mut FrontLayer,
     extended_set: &mut ExtendedSet,
@@ -381,7 +569,13 @@ fn update_route(
         dag,
         layout,
         coupling,
+        neighbor_table,
+        dist,
+        heuristic,
+        rng,
         gate_order,
+        gate_order_depths,
+        node_block_results,
         front_layer,
         required_predecessors,
     );
@@ -400,9 +594,15 @@ fn update_route(
 fn route_reachable_nodes(
     to_visit: &[NodeIndex],
     dag: &SabreDAG,
-    layout: &NLayout,
+    layout: &mut NLayout,
     coupling: &DiGraph<(), ()>,
+    neighbor_table: &NeighborTable,
+    dist: &ArrayView2<f64>,
+    heuristic: &Heuristic,
+    rng: &mut Pcg64Mcg,
     gate_order: &mut Vec<usize>,
+    gate_order_depths: &mut Vec<u32>,
+    node_block_results: &mut NodeBlockResults,
     front_layer: &mut FrontLayer,
     required_predecessors: &mut [u32],
 ) {
@@ -424,6 +624,19 @@ fn route_reachable_nodes(
             }
             _ => {
                 gate_order.push(*py_node);
+                gate_order_depths.push(dag.node_depths[&node]);
+                if let Some(blocks) = dag.node_blocks.get(py_node) {
+                    route_control_flow_node(
+                        *py_node,
+                        blocks,
+                        layout,
+                        neighbor_table,
+                        dist,
+                        heuristic,
+                        rng,
+                        node_block_results,
+                    );
+                }
                 for edge in dag.dag.edges_directed(node, Direction::Outgoing) {
                     let successor_node = edge.target();
                     let successor_index = successor_node.index();
@@ -437,6 +650,172 @@ fn route_reachable_nodes(
     }
 }
 
+/// Route every body block of a control-flow node, each as an independent trial starting from a
+/// clone of the layout the node was reached with, then reconcile their differing final layouts
+/// back to a single common one (the first block's) by appending a token-swap epilogue to every
+/// other block. `layout` is updated in place to that common final layout, so the rest of the
+/// outer traversal continues from a single agreed-upon placement regardless of which block is
+/// actually taken at runtime.
+fn route_control_flow_node(
+    py_node: usize,
+    blocks: &[SabreDAG],
+    layout: &mut NLayout,
+    neighbor_table: &NeighborTable,
+    dist: &ArrayView2<f64>,
+    heuristic: &Heuristic,
+    rng: &mut Pcg64Mcg,
+    node_block_results: &mut NodeBlockResults,
+) {
+    let num_qubits = layout.logic_to_phys.len();
+    let coupling_graph = cmap_from_neighor_table(neighbor_table);
+    // Each block gets a single routing trial (rather than the multi-trial search `build_swap_map`
+    // does at the top level) to keep a deeply-nested circuit's routing cost linear rather than
+    // exponential in its nesting depth.
+    let trials: Vec<TrialResult> = blocks
+        .iter()
+        .map(|block| {
+            swap_map_trial(
+                num_qubits,
+                block,
+                neighbor_table,
+                dist,
+                &coupling_graph,
+                heuristic,
+                rng.gen(),
+                layout.clone(),
+            )
+        })
+        .collect();
+    let canonical_layout = trials[0].layout.clone();
+    let results = trials
+        .into_iter()
+        .map(|trial| {
+            let mut swap_epilogue = Vec::new();
+            let mut reconciled = trial.layout;
+            swaps_to_permute(
+                &mut swap_epilogue,
+                &reconciled,
+                &canonical_layout,
+                neighbor_table,
+            );
+            for &[a, b] in &swap_epilogue {
+                reconciled.swap_logical(a, b);
+            }
+            BlockResult {
+                swap_map: SwapMap { map: trial.out_map },
+                gate_order: trial.gate_order,
+                gate_order_depths: trial.gate_order_depths,
+                swap_epilogue,
+                node_block_results: trial.node_block_results,
+            }
+        })
+        .collect();
+    node_block_results.results.insert(py_node, results);
+    *layout = canonical_layout;
+}
+
+/// Build a spanning tree of the connected graph described by `neighbor_table`, as an adjacency
+/// list over the same physical-qubit indices, by a BFS out of physical qubit 0. The coupling
+/// graph backing `neighbor_table` is connected, so this always reaches every qubit.
+fn spanning_tree(neighbor_table: &NeighborTable, num_qubits: usize) -> Vec<Vec<usize>> {
+    let mut tree_adj: Vec<Vec<usize>> = vec![Vec::new(); num_qubits];
+    let mut visited = vec![false; num_qubits];
+    let mut queue = VecDeque::with_capacity(num_qubits);
+    visited[0] = true;
+    queue.push_back(0);
+    while let Some(node) = queue.pop_front() {
+        for &neighbor in &neighbor_table.neighbors[node] {
+            if !visited[neighbor] {
+                visited[neighbor] = true;
+                tree_adj[node].push(neighbor);
+                tree_adj[neighbor].push(node);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    tree_adj
+}
+
+/// The unique path (inclusive of both ends) between `start` and `end` through `tree_adj`,
+/// considering only nodes with `active[node]` set.
+fn tree_path(tree_adj: &[Vec<usize>], active: &[bool], start: usize, end: usize) -> Vec<usize> {
+    let mut parent = vec![usize::MAX; tree_adj.len()];
+    let mut queue = VecDeque::new();
+    parent[start] = start;
+    queue.push_back(start);
+    while let Some(node) = queue.pop_front() {
+        if node == end {
+            break;
+        }
+        for &neighbor in &tree_adj[node] {
+            if active[neighbor] && parent[neighbor] == usize::MAX {
+                parent[neighbor] = node;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    let mut path = vec![end];
+    while *path.last().unwrap() != start {
+        path.push(parent[*path.last().unwrap()]);
+    }
+    path.reverse();
+    path
+}
+
+/// Append swaps to `swaps` (and apply them to a scratch copy of `from`) that bring `from`'s
+/// virtual-to-physical mapping into agreement with `to`'s, using only swaps between physical
+/// qubits that are neighbours in `neighbor_table`.
+///
+/// This walks a spanning tree of the coupling graph leaf-by-leaf: at each active leaf `l`, the
+/// one virtual qubit whose target is `l` is walked in, one swap per tree edge, along the tree
+/// path from wherever it currently sits; `l` is then permanently finished and pruned from the
+/// active tree (its neighbours' degrees drop, producing new leaves to process). Because a
+/// finished leaf is removed from the tree, later swaps -- which only ever touch the still-active
+/// subtree -- can never disturb it again, so this is guaranteed to converge in a bounded number
+/// of swaps regardless of the coupling graph's shape. It does not attempt to find a minimal swap
+/// count, only a correct one.
+fn swaps_to_permute(
+    swaps: &mut Vec<[usize; 2]>,
+    from: &NLayout,
+    to: &NLayout,
+    neighbor_table: &NeighborTable,
+) {
+    let mut current = from.clone();
+    let num_qubits = current.logic_to_phys.len();
+    let tree_adj = spanning_tree(neighbor_table, num_qubits);
+    let mut active = vec![true; num_qubits];
+    let mut degree: Vec<usize> = tree_adj.iter().map(Vec::len).collect();
+    let mut leaves: VecDeque<usize> = (0..num_qubits).filter(|&n| degree[n] <= 1).collect();
+
+    while let Some(leaf) = leaves.pop_front() {
+        if !active[leaf] {
+            continue;
+        }
+        let wanted = to.phys_to_logic[leaf];
+        let path = tree_path(&tree_adj, &active, current.logic_to_phys[wanted], leaf);
+        for window in path.windows(2) {
+            let virtual_a = current.phys_to_logic[window[0]];
+            let virtual_b = current.phys_to_logic[window[1]];
+            swaps.push([virtual_a, virtual_b]);
+            current.swap_logical(virtual_a, virtual_b);
+        }
+
+        active[leaf] = false;
+        for &neighbor in &tree_adj[leaf] {
+            if active[neighbor] {
+                degree[neighbor] -= 1;
+                if degree[neighbor] == 1 {
+                    leaves.push_back(neighbor);
+                }
+            }
+        }
+    }
+    debug_assert!(
+        (0..num_qubits).all(|v| current.logic_to_phys[v] == to.logic_to_phys[v]),
+        "swaps_to_permute failed to reconcile `from` with `to`",
+    );
+}
+
 /// Walk through the swaps in the given vector, undoing them on the layout and removing them.
 fn undo_swaps(swaps: &mut Vec<[usize; 2]>, layout: &mut NLayout) {
     swaps
@@ -517,6 +896,14 @@ fn swaps_to_route(
 }
 
 /// Return the swap of two virtual qubits that produces the best score of all possible swaps.
+///
+/// The score is the sum of whichever components `heuristic` has enabled: the front layer's
+/// distance sum (`with_basic`), a lookahead window's distance sum over the extended set
+/// (`with_lookahead`), a per-qubit decay penalty (`with_decay`) that discourages reusing a
+/// qubit swapped recently, and the front layer's directed-coupling penalty
+/// (`with_directed_coupling`). The weights -- and the decay step/reset interval -- are set
+/// through the `with_*` builder methods on the `Heuristic` pyclass, so they're tunable from
+/// Python without a rebuild.
 fn choose_best_swap(
     layer: &FrontLayer,
     extended_set: &ExtendedSet,
@@ -527,30 +914,45 @@ fn choose_best_swap(
     heuristic: &Heuristic,
     rng: &mut Pcg64Mcg,
 ) -> [usize; 2] {
+    let swaps: Vec<[usize; 2]> = obtain_swaps(layer, neighbor_table, layout).collect();
+    let mut scores = vec![0.0_f64; swaps.len()];
+
+    // Each enabled component is scored in its own pass over the candidate swaps, rather than
+    // branching on the heuristic inside a single shared loop, so components compose freely
+    // (e.g. a decay penalty applies whether or not a lookahead window is configured at all).
+    if let Some(basic) = heuristic.basic {
+        let weight = basic.weight(layer.len());
+        for (score, &swap) in scores.iter_mut().zip(&swaps) {
+            *score += weight * layer.score(swap, layout, dist);
+        }
+    }
+    if let Some((weight, _)) = heuristic.lookahead {
+        let weight = weight.weight(extended_set.len());
+        for (score, &swap) in scores.iter_mut().zip(&swaps) {
+            *score += weight * extended_set.score(swap, layout, dist);
+        }
+    }
+    if heuristic.decay.is_some() {
+        for (score, &swap) in scores.iter_mut().zip(&swaps) {
+            *score += qubits_decay[swap[0]].max(qubits_decay[swap[1]]) - 1.0;
+        }
+    }
+    if let Some(penalty) = heuristic.directed_penalty.as_ref() {
+        let penalty = penalty.view();
+        for (score, &swap) in scores.iter_mut().zip(&swaps) {
+            *score += layer.score(swap, layout, &penalty);
+        }
+    }
+
+    if let Some(temperature) = heuristic.temperature {
+        if temperature > 0.0 {
+            return sample_boltzmann(&swaps, &scores, temperature, rng);
+        }
+    }
+
     let mut min_score = f64::MAX;
     let mut best_swaps: Vec<[usize; 2]> = Vec::new();
-    // The decay heuristic is the only one that actually needs the absolute score.
-    let absolute_score = match heuristic {
-        Heuristic::Decay => {
-            layer.total_score(layout, dist)
-                + EXTENDED_SET_WEIGHT * extended_set.total_score(layout, dist)
-        }
-        _ => 0.0,
-    };
-    for swap in obtain_swaps(layer, neighbor_table, layout) {
-        let score = match heuristic {
-            Heuristic::Basic => layer.score(swap, layout, dist),
-            Heuristic::Lookahead => {
-                layer.score(swap, layout, dist)
-                    + EXTENDED_SET_WEIGHT * extended_set.score(swap, layout, dist)
-            }
-            Heuristic::Decay => {
-                qubits_decay[swap[0]].max(qubits_decay[swap[1]])
-                    * (absolute_score
-                        + layer.score(swap, layout, dist)
-                        + EXTENDED_SET_WEIGHT * extended_set.score(swap, layout, dist))
-            }
-        };
+    for (&swap, &score) in swaps.iter().zip(&scores) {
         if score < min_score {
             min_score = score;
             best_swaps.clear();
@@ -565,12 +967,52 @@ fn choose_best_swap(
     *best_swaps.choose(rng).unwrap()
 }
 
+/// Sample one candidate swap from a Boltzmann/softmax distribution over every `(swap, score)`
+/// pair: `w_i = exp(-(score_i - min_score) / temperature)`, normalized. Subtracting `min_score`
+/// before exponentiating only rescales every weight by the same constant factor (it cancels out
+/// once normalized), so it doesn't change the distribution, but it keeps the exponent from
+/// overflowing when scores are large in magnitude.
+///
+/// `choose_best_swap` already collects the score of every candidate swap whose endpoints are
+/// both reachable from the front layer (`obtain_swaps`) into the parallel `swaps`/`scores`
+/// vectors before this function ever runs, so there's no separate "first collect, then sample"
+/// step to add here -- this *is* that step. `heuristic.temperature` is carried on the `Heuristic`
+/// passed into each routing trial (down through `swap_map_trial` to `choose_best_swap`), so
+/// it's already selectable per call and falls back to the greedy
+/// `min_score`/`best_swaps` path below whenever it's `None` or `<= 0.0`, without disturbing the
+/// per-trial `Pcg64Mcg` seeding ([`build_swap_map_inner`]'s doc comment) that keeps trials
+/// reproducible.
+fn sample_boltzmann(
+    swaps: &[[usize; 2]],
+    scores: &[f64],
+    temperature: f64,
+    rng: &mut Pcg64Mcg,
+) -> [usize; 2] {
+    let min_score = scores.iter().copied().fold(f64::MAX, f64::min);
+    let weights: Vec<f64> = scores
+        .iter()
+        .map(|&score| (-(score - min_score) / temperature).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    let mut target = rng.gen::<f64>() * total;
+    for (&swap, &weight) in swaps.iter().zip(&weights) {
+        target -= weight;
+        if target <= 0.0 {
+            return swap;
+        }
+    }
+    *swaps.last().unwrap()
+}
+
 #[pymodule]
 pub fn sabre_swap(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_wrapped(wrap_pyfunction!(build_swap_map))?;
     m.add_class::<Heuristic>()?;
+    m.add_class::<SetScaling>()?;
     m.add_class::<NeighborTable>()?;
     m.add_class::<SabreDAG>()?;
     m.add_class::<SwapMap>()?;
+    m.add_class::<BlockResult>()?;
+    m.add_class::<NodeBlockResults>()?;
     Ok(())
 }
@@ -0,0 +1,134 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2022
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+use hashbrown::HashMap;
+use hashbrown::HashSet;
+use pyo3::prelude::*;
+use retworkx_core::petgraph::prelude::*;
+use retworkx_core::petgraph::visit::EdgeRef;
+
+/// A DAG of 1q/2q operations used as the input to Sabre routing, built ahead of time from the
+/// Python-space `DAGCircuit` so the routing loop itself never needs to touch Python objects.
+///
+/// Each node stores the originating `DAGCircuit` node id (so the result can be mapped back) and
+/// its qubits; the edges of `dag` encode the same topological ordering as the source circuit.
+///
+/// A node whose operation is a control-flow block (`if_else`, `for_loop`, `while_loop`, ...) has
+/// no representation of its body in `dag` itself -- instead, its nested bodies are recorded in
+/// `node_blocks`, keyed by the node's originating id, each one a complete `SabreDAG` over the
+/// same qubits as the outer node. Routing recurses into these when it reaches such a node; a node
+/// absent from `node_blocks` is an ordinary operation with no blocks.
+#[pyclass(module = "qiskit._accelerate.sabre_swap")]
+#[derive(Clone, Debug)]
+pub struct SabreDAG {
+    pub dag: DiGraph<(usize, Vec<usize>), ()>,
+    pub first_layer: Vec<NodeIndex>,
+    pub node_blocks: HashMap<usize, Vec<SabreDAG>>,
+    /// Every node's depth in `dag`'s precedence structure: `first_layer`'s nodes are depth 0, and
+    /// every other node is one more than the deepest of its predecessors. Nodes at the same depth
+    /// have no dependency on each other, so could in principle be reordered; this is exposed
+    /// alongside `gate_order` so that Python-side replay can tell, for any two consecutive
+    /// `gate_order` entries, whether they come from the same depth (and are thus a tie the
+    /// traversal order itself already broke deterministically) rather than recomputing its own
+    /// topological sort to find out.
+    pub node_depths: HashMap<NodeIndex, u32>,
+}
+
+#[pymethods]
+impl SabreDAG {
+    #[new]
+    #[pyo3(signature = (num_qubits, num_clbits, nodes, node_blocks=None))]
+    pub fn new(
+        num_qubits: usize,
+        num_clbits: usize,
+        nodes: Vec<(usize, Vec<usize>, HashSet<usize>)>,
+        node_blocks: Option<HashMap<usize, Vec<SabreDAG>>>,
+    ) -> PyResult<Self> {
+        let mut dag: DiGraph<(usize, Vec<usize>), ()> =
+            Graph::with_capacity(nodes.len(), 2 * nodes.len());
+        let mut first_layer = Vec::new();
+        let mut qubit_pos: Vec<Option<NodeIndex>> = vec![None; num_qubits];
+        let mut clbit_pos: Vec<Option<NodeIndex>> = vec![None; num_clbits];
+        for (py_index, qargs, cargs) in nodes {
+            let gate_index = dag.add_node((py_index, qargs.clone()));
+            let mut is_front = true;
+            for x in &qargs {
+                if let Some(predecessor) = qubit_pos[*x] {
+                    is_front = false;
+                    dag.add_edge(predecessor, gate_index, ());
+                }
+                qubit_pos[*x] = Some(gate_index);
+            }
+            for x in &cargs {
+                if let Some(predecessor) = clbit_pos[*x] {
+                    is_front = false;
+                    dag.add_edge(predecessor, gate_index, ());
+                }
+                clbit_pos[*x] = Some(gate_index);
+            }
+            if is_front {
+                first_layer.push(gate_index);
+            }
+        }
+        let node_depths = Self::compute_node_depths(&dag, &first_layer);
+        Ok(SabreDAG {
+            dag,
+            first_layer,
+            node_blocks: node_blocks.unwrap_or_default(),
+            node_depths,
+        })
+    }
+
+    /// Attach the nested body DAGs of a control-flow node, keyed by its own node id. Called
+    /// separately from `__new__` because a block's `SabreDAG` can only be built once its own
+    /// (inner) node ids are known, which in general happens after the outer DAG is constructed.
+    pub fn set_blocks(&mut self, node: usize, blocks: Vec<SabreDAG>) {
+        self.node_blocks.insert(node, blocks);
+        self.node_depths = Self::compute_node_depths(&self.dag, &self.first_layer);
+    }
+}
+
+impl SabreDAG {
+    /// Group every node of `dag` into layers by longest-path depth from `first_layer` (depth 0),
+    /// walking the precedence structure breadth-first so that a node's depth is only finalized
+    /// once every one of its predecessors' depths is known.
+    pub(crate) fn compute_node_depths(
+        dag: &DiGraph<(usize, Vec<usize>), ()>,
+        first_layer: &[NodeIndex],
+    ) -> HashMap<NodeIndex, u32> {
+        let mut required_predecessors: Vec<u32> = vec![0; dag.node_count()];
+        for node in dag.node_indices() {
+            for edge in dag.edges(node) {
+                required_predecessors[edge.target().index()] += 1;
+            }
+        }
+        let mut node_depths = HashMap::with_capacity(dag.node_count());
+        let mut current: Vec<NodeIndex> = first_layer.to_vec();
+        let mut depth = 0;
+        while !current.is_empty() {
+            let mut next = Vec::new();
+            for &node in &current {
+                node_depths.insert(node, depth);
+                for edge in dag.edges_directed(node, Direction::Outgoing) {
+                    let successor = edge.target();
+                    required_predecessors[successor.index()] -= 1;
+                    if required_predecessors[successor.index()] == 0 {
+                        next.push(successor);
+                    }
+                }
+            }
+            current = next;
+            depth += 1;
+        }
+        node_depths
+    }
+}
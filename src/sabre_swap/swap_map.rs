@@ -0,0 +1,136 @@
+// This code is part of Qiskit.
+//
+// (C) Copyright IBM 2022
+//
+// This code is licensed under the Apache License, Version 2.0. You may
+// obtain a copy of this license in the LICENSE.txt file in the root directory
+// of this source tree or at http://www.apache.org/licenses/LICENSE-2.0.
+//
+// Any modifications or derivative works of this code must retain this
+// copyright notice, and modified files need to carry a notice indicating
+// that they have been altered from the originals.
+
+use hashbrown::HashMap;
+use numpy::IntoPyArray;
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+
+use crate::nlayout::NLayout;
+
+/// A mapping of `DAGCircuit` node ids to the virtual-qubit swaps that must be inserted
+/// immediately before that node for it to be routable.
+#[pyclass(mapping, module = "qiskit._accelerate.sabre_swap")]
+#[derive(Clone, Debug)]
+pub struct SwapMap {
+    pub map: HashMap<usize, Vec<[usize; 2]>>,
+}
+
+#[pymethods]
+impl SwapMap {
+    fn __getitem__(&self, py: Python, object: usize) -> PyResult<PyObject> {
+        match self.map.get(&object) {
+            Some(val) => Ok(val.to_object(py)),
+            None => Err(PyIndexError::new_err(format!(
+                "Node index {object} not found in SwapMap"
+            ))),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Replay the virtual-qubit swaps recorded for `node`, in the order they were committed,
+    /// against `layout`, returning the concrete `(physical_a, physical_b)` pairs a `SwapGate`
+    /// should be inserted on. `layout` is mutated in place after each swap, so by the time this
+    /// returns it already reflects the state after the whole permutation layer has been applied
+    /// -- exactly mirroring how a permutation circuit is assembled by applying its swaps one at a
+    /// time -- letting the Python caller append `SwapGate`s directly without re-deriving the
+    /// physical qubits itself or re-reading `self` node by node. Returns `None` if `node` has no
+    /// swaps recorded (it either needed none, or isn't present in this map at all).
+    fn physical_swaps(&self, node: usize, layout: &mut NLayout) -> Option<Vec<(usize, usize)>> {
+        self.map.get(&node).map(|swaps| {
+            swaps
+                .iter()
+                .map(|&[a, b]| {
+                    let physical = (layout.logic_to_phys[a], layout.logic_to_phys[b]);
+                    layout.swap_logical(a, b);
+                    physical
+                })
+                .collect()
+        })
+    }
+}
+
+/// The routing result of a single control-flow body block, produced by routing it as though it
+/// were a standalone circuit starting from the layout its control-flow node was reached with.
+///
+/// `swap_map` and `gate_order` are exactly as they would be for a top-level call to
+/// `build_swap_map`, scoped to this block. `swap_epilogue` is the extra sequence of swaps
+/// appended after the block's own gates to reconcile this block's final layout with whichever
+/// common layout every other block (and the outer circuit) agree on after the control-flow node,
+/// since each block is otherwise free to permute qubits differently. `node_block_results` holds
+/// the recursively-nested results for any control-flow nodes within this block itself.
+#[pyclass(module = "qiskit._accelerate.sabre_swap")]
+#[derive(Clone, Debug)]
+pub struct BlockResult {
+    pub swap_map: SwapMap,
+    pub gate_order: Vec<usize>,
+    /// Parallel to `gate_order`; see `SabreDAG::node_depths` and `mod.rs`'s `TrialResult`.
+    pub gate_order_depths: Vec<u32>,
+    pub swap_epilogue: Vec<[usize; 2]>,
+    pub node_block_results: NodeBlockResults,
+}
+
+#[pymethods]
+impl BlockResult {
+    #[getter]
+    fn swap_map(&self) -> SwapMap {
+        self.swap_map.clone()
+    }
+
+    #[getter]
+    fn gate_order(&self, py: Python) -> PyObject {
+        self.gate_order.clone().into_pyarray(py).into()
+    }
+
+    #[getter]
+    fn gate_order_depths(&self, py: Python) -> PyObject {
+        self.gate_order_depths.clone().into_pyarray(py).into()
+    }
+
+    #[getter]
+    fn swap_epilogue(&self) -> Vec<[usize; 2]> {
+        self.swap_epilogue.clone()
+    }
+
+    #[getter]
+    fn node_block_results(&self) -> NodeBlockResults {
+        self.node_block_results.clone()
+    }
+}
+
+/// A mapping of control-flow `DAGCircuit` node ids to the per-block [BlockResult] of routing each
+/// of that node's body blocks. A node with no entry here is not a control-flow operation (or is
+/// one with no blocks routed, which should not normally occur).
+#[pyclass(mapping, module = "qiskit._accelerate.sabre_swap")]
+#[derive(Clone, Debug, Default)]
+pub struct NodeBlockResults {
+    pub results: HashMap<usize, Vec<BlockResult>>,
+}
+
+#[pymethods]
+impl NodeBlockResults {
+    fn __getitem__(&self, py: Python, object: usize) -> PyResult<PyObject> {
+        match self.results.get(&object) {
+            Some(val) => Ok(val.clone().into_py(py)),
+            None => Err(PyIndexError::new_err(format!(
+                "Node index {object} not found in NodeBlockResults"
+            ))),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.results.len()
+    }
+}